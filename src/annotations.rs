@@ -0,0 +1,89 @@
+//! Validation of annotation maps shared by the image and runtime specs.
+
+use crate::error::{FindingCode, OciSpecError, ValidationError, ValidationErrorKind};
+use std::collections::HashMap;
+
+/// The namespace reserved for the annotations of the OCI specs. Keys in it
+/// which are not defined by a spec must not be used.
+pub(crate) const RESERVED_ANNOTATION_NAMESPACE: &str = "org.opencontainers.";
+
+/// The keys of the reserved namespace which the specs define: the predefined
+/// annotations of the image spec and the annotations it defines for the
+/// conversion of an image configuration into a runtime config.
+const STANDARD_ANNOTATIONS: &[&str] = &[
+    "org.opencontainers.image.created",
+    "org.opencontainers.image.authors",
+    "org.opencontainers.image.url",
+    "org.opencontainers.image.documentation",
+    "org.opencontainers.image.source",
+    "org.opencontainers.image.version",
+    "org.opencontainers.image.revision",
+    "org.opencontainers.image.vendor",
+    "org.opencontainers.image.licenses",
+    "org.opencontainers.image.ref.name",
+    "org.opencontainers.image.title",
+    "org.opencontainers.image.description",
+    "org.opencontainers.image.base.digest",
+    "org.opencontainers.image.base.name",
+    "org.opencontainers.image.os",
+    "org.opencontainers.image.architecture",
+    "org.opencontainers.image.variant",
+    "org.opencontainers.image.os.version",
+    "org.opencontainers.image.os.features",
+    "org.opencontainers.image.author",
+    "org.opencontainers.image.stopSignal",
+    "org.opencontainers.image.exposedPorts",
+];
+
+/// Check that no key of `annotations` is empty and that keys in the reserved
+/// `org.opencontainers` namespace are defined by a spec. The image and the
+/// runtime spec share this rule, as runtimes pass the annotations of an image
+/// on to the container. Keys are checked in sorted order, so that the
+/// reported key does not depend on the order of the map.
+pub(crate) fn validate_annotations(
+    annotations: &HashMap<String, String>,
+) -> Result<(), OciSpecError> {
+    let mut keys: Vec<_> = annotations.keys().collect();
+    keys.sort();
+    for key in keys {
+        if key.is_empty() {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Missing,
+                "annotations",
+                "annotation key must not be empty",
+            )
+            .with_code(FindingCode::AnnotationKeyEmpty)
+            .into());
+        }
+        if key.starts_with(RESERVED_ANNOTATION_NAMESPACE)
+            && !STANDARD_ANNOTATIONS.contains(&key.as_str())
+        {
+            return Err(ValidationError::new(
+                ValidationErrorKind::InvalidFormat,
+                format!("annotations.{key}"),
+                "annotation key uses the reserved org.opencontainers namespace",
+            )
+            .with_code(FindingCode::AnnotationKeyReserved)
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns true if `key` follows the recommended reverse domain notation,
+/// e.g. `com.example.myKey`: the key is namespaced by at least two domain
+/// labels of lowercase letters, digits and dashes.
+#[cfg(feature = "runtime")]
+pub(crate) fn is_reverse_domain(key: &str) -> bool {
+    let mut labels = key.split('.');
+    let is_label = |label: Option<&str>| {
+        label.is_some_and(|label| {
+            !label.is_empty()
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+    };
+    is_label(labels.next()) && is_label(labels.next()) && labels.next().is_some()
+}
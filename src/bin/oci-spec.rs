@@ -0,0 +1,78 @@
+//! Validate OCI runtime configs, image manifests and image layouts.
+
+use oci_spec::{
+    image::{ImageManifest, OciLayout},
+    runtime::Spec,
+    Result,
+};
+use std::{env, error::Error, path::Path, process::ExitCode};
+
+const USAGE: &str = "\
+usage: oci-spec <command> <path>...
+
+Validate OCI documents and print path annotated errors.
+
+commands:
+    config      validate runtime spec config.json files and lint them
+    manifest    validate image manifests
+    layout      validate image layout directories";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some((command, paths)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+
+    let validate: fn(&Path) -> Result<()> = match command.as_str() {
+        "config" => validate_config,
+        "manifest" => |path| ImageManifest::from_file_validated(path).map(|_| ()),
+        "layout" => |path| OciLayout::validate(path),
+        "help" | "-h" | "--help" => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        command => {
+            eprintln!("unknown command {command:?}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    if paths.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    let mut code = ExitCode::SUCCESS;
+    for path in paths {
+        match validate(Path::new(path)) {
+            Ok(()) => println!("{path}: ok"),
+            Err(err) => {
+                eprintln!("{path}: {}", error_chain(&err));
+                code = ExitCode::FAILURE;
+            }
+        }
+    }
+    code
+}
+
+fn validate_config(path: &Path) -> Result<()> {
+    let spec = Spec::load_validated(path)?;
+    spec.validate_utf8_paths()?;
+    for warning in spec.lint() {
+        eprintln!("{}: warning: {warning}", path.display());
+    }
+    Ok(())
+}
+
+/// Join the messages of `err` and its sources, e.g. to include the line and
+/// column of a serde error.
+fn error_chain(err: &dyn Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message.push_str(": ");
+        message.push_str(&err.to_string());
+        source = err.source();
+    }
+    message
+}
@@ -1,4 +1,4 @@
-//! [OCI distribution spec](https://github.com/opencontainers/distribution-spec) types and definitions.
+//! [OCI distribution spec](https://github.com/opencontainers/distribution-spec) types and definitions, re-exported from [v1].
 //!
 //! The Open Container Initiative Distribution Specification (a.k.a. "OCI Distribution Spec")
 //! defines an API protocol to facilitate and standardize the distribution of content.
@@ -11,14 +11,6 @@
 //! To support other artifact types, please see the [Open Container Initiative Artifact Authors
 //! Guide](https://github.com/opencontainers/artifacts) (a.k.a. "OCI Artifacts").
 
-mod error;
-mod reference;
-mod repository;
-mod tag;
-mod version;
+pub mod v1;
 
-pub use error::*;
-pub use reference::*;
-pub use repository::*;
-pub use tag::*;
-pub use version::*;
+pub use v1::*;
@@ -0,0 +1,147 @@
+//! Blob upload types of the distribution spec.
+
+use crate::error::{oci_error, OciSpecError, Result};
+use derive_builder::Builder;
+use getset::{Getters, Setters};
+
+#[derive(Builder, Clone, Debug, Eq, Getters, Setters, PartialEq)]
+#[builder(
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(error = "OciSpecError")
+)]
+#[getset(get = "pub", set = "pub")]
+/// A request to mount a blob from another repository of the same registry
+/// instead of uploading it again.
+#[non_exhaustive]
+pub struct BlobMountRequest {
+    /// The namespace of the repository the blob is mounted into.
+    name: String,
+
+    /// The digest of the blob to mount.
+    digest: String,
+
+    /// The repository the blob is mounted from. Registries may try to locate
+    /// the blob themselves if it is not provided.
+    #[builder(default)]
+    from: Option<String>,
+}
+
+impl BlobMountRequest {
+    /// The path and query of the `POST` request, e.g.
+    /// `/v2/library/app/blobs/uploads/?mount=sha256:...&from=library/base`.
+    pub fn url(&self) -> String {
+        let mut url = format!("/v2/{}/blobs/uploads/?mount={}", self.name, self.digest);
+        if let Some(from) = &self.from {
+            url.push_str("&from=");
+            url.push_str(from);
+        }
+        url
+    }
+}
+
+/// The outcome of a [BlobMountRequest].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum BlobMountResponse {
+    /// The blob was mounted, the registry responded with `201 Created`.
+    Mounted {
+        /// The location of the mounted blob.
+        location: String,
+        /// The digest of the blob, if returned in the `Docker-Content-Digest`
+        /// header.
+        digest: Option<String>,
+    },
+
+    /// The registry could not mount the blob and started an upload session
+    /// instead, responding with `202 Accepted`. The blob has to be uploaded
+    /// to `location`.
+    UploadStarted {
+        /// The location of the upload session.
+        location: String,
+    },
+}
+
+impl BlobMountResponse {
+    /// Interpret the response to a [BlobMountRequest] from its status code and
+    /// the values of the `Location` and `Docker-Content-Digest` headers.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other] if the status is
+    /// neither `201` nor `202` or the `Location` header is missing.
+    pub fn from_response(
+        status: u16,
+        location: Option<&str>,
+        digest: Option<&str>,
+    ) -> Result<Self> {
+        let location = || {
+            location
+                .map(String::from)
+                .ok_or_else(|| oci_error("blob mount response is missing the Location header"))
+        };
+        match status {
+            201 => Ok(Self::Mounted {
+                location: location()?,
+                digest: digest.map(String::from),
+            }),
+            202 => Ok(Self::UploadStarted {
+                location: location()?,
+            }),
+            status => Err(oci_error(format!(
+                "unexpected blob mount response status {status}"
+            ))),
+        }
+    }
+
+    /// Returns true if the blob still has to be uploaded.
+    pub fn needs_upload(&self) -> bool {
+        matches!(self, Self::UploadStarted { .. })
+    }
+
+    /// The value of the `Location` header.
+    pub fn location(&self) -> &str {
+        match self {
+            Self::Mounted { location, .. } | Self::UploadStarted { location } => location,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356";
+
+    #[test]
+    fn blob_mount_url() -> Result<()> {
+        let request = BlobMountRequestBuilder::default()
+            .name("library/app")
+            .digest(DIGEST)
+            .from("library/base")
+            .build()?;
+        assert_eq!(
+            request.url(),
+            format!("/v2/library/app/blobs/uploads/?mount={DIGEST}&from=library/base")
+        );
+        assert!(BlobMountRequestBuilder::default()
+            .name("app")
+            .build()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn blob_mount_response() -> Result<()> {
+        let location = format!("/v2/library/app/blobs/{DIGEST}");
+        let mounted = BlobMountResponse::from_response(201, Some(&location), Some(DIGEST))?;
+        assert!(!mounted.needs_upload());
+        assert_eq!(mounted.location(), location);
+
+        let fallback =
+            BlobMountResponse::from_response(202, Some("/v2/library/app/blobs/uploads/1"), None)?;
+        assert!(fallback.needs_upload());
+
+        assert!(BlobMountResponse::from_response(202, None, None).is_err());
+        assert!(BlobMountResponse::from_response(404, Some(&location), None).is_err());
+        Ok(())
+    }
+}
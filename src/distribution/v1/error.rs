@@ -15,6 +15,7 @@ pub const ERR_REGISTRY: &str = "distribution: registry returned error";
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, StrumDisplay, EnumString)]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
 pub enum ErrorCode {
     /// Blob unknown to registry.
     BlobUnknown,
@@ -44,7 +45,13 @@ pub enum ErrorCode {
     Unsupported,
     /// Too many requests.
     #[serde(rename = "TOOMANYREQUESTS")]
+    #[strum(serialize = "TOOMANYREQUESTS")]
     TooManyRequests,
+    /// Any code not defined by the spec, e.g. a vendor specific one, which
+    /// is kept as returned by the registry.
+    #[serde(untagged)]
+    #[strum(default)]
+    Other(String),
 }
 
 #[derive(Builder, Clone, Debug, Deserialize, Eq, Error, Getters, PartialEq, Serialize)]
@@ -55,6 +62,7 @@ pub enum ErrorCode {
 )]
 #[getset(get = "pub")]
 /// ErrorResponse is returned by a registry on an invalid request.
+#[non_exhaustive]
 pub struct ErrorResponse {
     /// Available errors within the response.
     errors: Vec<ErrorInfo>,
@@ -81,6 +89,7 @@ impl ErrorResponse {
 )]
 #[getset(get = "pub")]
 /// Describes a server error returned from a registry.
+#[non_exhaustive]
 pub struct ErrorInfo {
     /// The code field MUST be a unique identifier, containing only uppercase alphabetic
     /// characters and underscores.
@@ -211,4 +220,25 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn error_code_other() -> Result<()> {
+        let code: ErrorCode = serde_json::from_str(r#""TOOMANYREQUESTS""#)?;
+        assert_eq!(code, ErrorCode::TooManyRequests);
+        assert_eq!(code.to_string(), "TOOMANYREQUESTS");
+        assert_eq!("TOOMANYREQUESTS".parse::<ErrorCode>(), Ok(code));
+
+        let info: ErrorInfo = serde_json::from_str(
+            r#"{"code": "TOO_MANY_REQUESTS_PER_USER", "message": "slow down"}"#,
+        )?;
+        let code = ErrorCode::Other("TOO_MANY_REQUESTS_PER_USER".to_string());
+        assert_eq!(info.code(), &code);
+        assert_eq!(code.to_string(), "TOO_MANY_REQUESTS_PER_USER");
+        assert_eq!("TOO_MANY_REQUESTS_PER_USER".parse::<ErrorCode>(), Ok(code));
+        assert_eq!(
+            serde_json::to_string(&info)?,
+            r#"{"code":"TOO_MANY_REQUESTS_PER_USER","message":"slow down"}"#
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,23 @@
+//! Types and definitions of the 1.x releases of the [OCI distribution spec](https://github.com/opencontainers/distribution-spec).
+
+mod blob;
+mod error;
+mod pagination;
+mod rate_limit;
+mod reference;
+mod referrers;
+mod repository;
+mod tag;
+mod version;
+mod warning;
+
+pub use blob::*;
+pub use error::*;
+pub use pagination::*;
+pub use rate_limit::*;
+pub use reference::*;
+pub use referrers::*;
+pub use repository::*;
+pub use tag::*;
+pub use version::*;
+pub use warning::*;
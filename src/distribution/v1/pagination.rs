@@ -0,0 +1,145 @@
+//! Pagination of the list endpoints of the distribution spec.
+
+use crate::error::OciSpecError;
+use derive_builder::Builder;
+use getset::{Getters, Setters};
+
+#[derive(Builder, Clone, Debug, Default, Eq, Getters, Setters, PartialEq)]
+#[builder(
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(error = "OciSpecError")
+)]
+#[getset(get = "pub", set = "pub")]
+/// The pagination parameters of the `/v2/_catalog` and `/v2/<name>/tags/list`
+/// endpoints.
+#[non_exhaustive]
+pub struct Pagination {
+    /// The maximum number of results to return.
+    #[builder(default)]
+    n: Option<usize>,
+
+    /// The last result of the previous page, the response starts after it.
+    #[builder(default)]
+    last: Option<String>,
+}
+
+impl Pagination {
+    /// Encode the parameters as query string without the leading `?`, e.g.
+    /// `n=100&last=library%2Fbusybox`.
+    pub fn to_query(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(n) = self.n {
+            params.push(format!("n={n}"));
+        }
+        if let Some(last) = &self.last {
+            params.push(format!("last={}", percent_encode(last)));
+        }
+        params.join("&")
+    }
+
+    /// Parse the parameters of the next page from the value of a `Link`
+    /// response header, e.g. `</v2/_catalog?n=2&last=b>; rel="next"`.
+    ///
+    /// Returns `None` if the header does not contain a `next` link, which
+    /// means the last page has been reached.
+    pub fn from_link_header(header: &str) -> Option<Self> {
+        header.split(',').find_map(|link| {
+            let mut parts = link.split(';');
+            let target = parts.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+            let is_next = parts.any(|param| {
+                param.split_once('=').is_some_and(|(key, value)| {
+                    key.trim().eq_ignore_ascii_case("rel")
+                        && value.trim().trim_matches('"').eq_ignore_ascii_case("next")
+                })
+            });
+            is_next.then(|| Self::from_url(target))
+        })
+    }
+
+    /// Parse the parameters from the query of `url`, unknown parameters are
+    /// ignored.
+    fn from_url(url: &str) -> Self {
+        let query = url.split_once('?').map(|(_, query)| query).unwrap_or("");
+        let mut pagination = Self::default();
+        for (key, value) in query.split('&').filter_map(|param| param.split_once('=')) {
+            match key {
+                "n" => pagination.n = value.parse().ok(),
+                "last" => pagination.last = Some(percent_decode(value)),
+                _ => {}
+            }
+        }
+        pagination
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            b => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn pagination_query() -> Result<()> {
+        let pagination = PaginationBuilder::default()
+            .n(100usize)
+            .last("library/busybox")
+            .build()?;
+        assert_eq!(pagination.to_query(), "n=100&last=library%2Fbusybox");
+        assert_eq!(Pagination::default().to_query(), "");
+        Ok(())
+    }
+
+    #[test]
+    fn pagination_link_header() {
+        let pagination = Pagination::from_link_header(
+            r#"</v2/_catalog?n=2&last=library%2Fbusybox>; rel="next""#,
+        )
+        .unwrap();
+        assert_eq!(pagination.n(), &Some(2));
+        assert_eq!(pagination.last().as_deref(), Some("library/busybox"));
+
+        let pagination = Pagination::from_link_header(
+            r#"<https://r.example/v2/_catalog?n=1>; rel="prev", <https://r.example/v2/_catalog?last=b&n=1>; rel=next"#,
+        )
+        .unwrap();
+        assert_eq!(pagination.last().as_deref(), Some("b"));
+
+        assert!(Pagination::from_link_header(r#"</v2/_catalog?n=2>; rel="prev""#).is_none());
+        assert!(Pagination::from_link_header("").is_none());
+    }
+}
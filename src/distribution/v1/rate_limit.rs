@@ -0,0 +1,214 @@
+//! Rate limit header types returned by registries.
+
+use crate::error::{oci_error, OciSpecError, Result};
+use getset::{CopyGetters, Getters};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    time::Duration,
+};
+
+/// The header with the quota of the rate limit, e.g. `100;w=21600`.
+pub const HEADER_RATE_LIMIT_LIMIT: &str = "RateLimit-Limit";
+
+/// The header with the remaining quota of the rate limit, e.g. `76;w=21600`.
+pub const HEADER_RATE_LIMIT_REMAINING: &str = "RateLimit-Remaining";
+
+/// The header with the number of seconds until the quota resets.
+pub const HEADER_RATE_LIMIT_RESET: &str = "RateLimit-Reset";
+
+/// The header Docker Hub uses for what the rate limit is counted for, e.g.
+/// the IP address of the client.
+pub const HEADER_DOCKER_RATE_LIMIT_SOURCE: &str = "Docker-RateLimit-Source";
+
+#[derive(Clone, Copy, CopyGetters, Debug, Eq, Hash, PartialEq)]
+#[getset(get_copy = "pub")]
+/// A quota of a rate limit header, a number of requests with an optional
+/// time window, e.g. `100;w=21600` for 100 requests per 6 hours.
+#[non_exhaustive]
+pub struct RateLimitQuota {
+    /// The number of requests.
+    value: u64,
+
+    /// The time window the number of requests applies to.
+    window: Option<Duration>,
+}
+
+impl RateLimitQuota {
+    /// Create a new quota of `value` requests per `window`.
+    pub fn new(value: u64, window: Option<Duration>) -> Self {
+        Self { value, window }
+    }
+}
+
+impl FromStr for RateLimitQuota {
+    type Err = OciSpecError;
+
+    /// Parse a quota with optional parameters, of which only the window
+    /// `w` is known and others like `comment` are ignored.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || oci_error(format!("invalid rate limit quota: {s}"));
+
+        let mut params = s.split(';').map(str::trim);
+        let value = params
+            .next()
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(invalid)?;
+        let mut window = None;
+        for param in params {
+            let (name, param_value) = param.split_once('=').ok_or_else(invalid)?;
+            if name.trim().eq_ignore_ascii_case("w") {
+                let seconds = param_value.trim().parse().map_err(|_| invalid())?;
+                window = Some(Duration::from_secs(seconds));
+            }
+        }
+        Ok(Self { value, window })
+    }
+}
+
+impl Display for RateLimitQuota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)?;
+        if let Some(window) = self.window {
+            write!(f, ";w={}", window.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, Getters, PartialEq)]
+#[getset(get = "pub")]
+/// The rate limit a registry reports in the `RateLimit-*` headers of a
+/// response, which tools like mirrors use to throttle before the registry
+/// starts rejecting requests with `TOOMANYREQUESTS`.
+#[non_exhaustive]
+pub struct RateLimit {
+    /// The quota of the rate limit from `RateLimit-Limit`.
+    limit: Option<RateLimitQuota>,
+
+    /// Further quota policies listed in `RateLimit-Limit` after the quota,
+    /// e.g. a burst limit.
+    policies: Vec<RateLimitQuota>,
+
+    /// The remaining quota from `RateLimit-Remaining`.
+    remaining: Option<RateLimitQuota>,
+
+    /// The time until the quota resets from `RateLimit-Reset`.
+    reset: Option<Duration>,
+
+    /// What the rate limit is counted for from `Docker-RateLimit-Source`.
+    source: Option<String>,
+}
+
+impl RateLimit {
+    /// Parse the rate limit from the headers of a response, given as pairs
+    /// of header name and value. Header names are matched case-insensitively
+    /// and the `X-RateLimit-*` variants are accepted as well. Returns `None`
+    /// if the response does not report a rate limit.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other] if a rate limit
+    /// header is malformed.
+    /// # Example
+    /// ```
+    /// use oci_spec::distribution::RateLimit;
+    /// use std::time::Duration;
+    ///
+    /// let rate_limit = RateLimit::from_headers([
+    ///     ("ratelimit-limit", "100;w=21600"),
+    ///     ("ratelimit-remaining", "0;w=21600"),
+    ///     ("docker-ratelimit-source", "192.0.2.1"),
+    /// ])
+    /// .unwrap()
+    /// .unwrap();
+    /// assert_eq!(rate_limit.limit().unwrap().value(), 100);
+    /// assert_eq!(rate_limit.window(), Some(Duration::from_secs(21600)));
+    /// assert!(rate_limit.is_exhausted());
+    /// ```
+    pub fn from_headers<'a, I>(headers: I) -> Result<Option<Self>>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut rate_limit = Self::default();
+        let mut found = false;
+        for (name, value) in headers {
+            let name = name.trim();
+            let name = name
+                .get(..2)
+                .filter(|prefix| prefix.eq_ignore_ascii_case("x-"))
+                .map_or(name, |_| &name[2..]);
+            let value = value.trim();
+            if name.eq_ignore_ascii_case(HEADER_RATE_LIMIT_LIMIT) {
+                let mut quotas = value
+                    .split(',')
+                    .map(|quota| quota.trim().parse::<RateLimitQuota>());
+                rate_limit.limit = quotas.next().transpose()?;
+                rate_limit.policies = quotas.collect::<Result<_>>()?;
+            } else if name.eq_ignore_ascii_case(HEADER_RATE_LIMIT_REMAINING) {
+                rate_limit.remaining = Some(value.parse()?);
+            } else if name.eq_ignore_ascii_case(HEADER_RATE_LIMIT_RESET) {
+                let seconds = value
+                    .parse()
+                    .map_err(|_| oci_error(format!("invalid rate limit reset: {value}")))?;
+                rate_limit.reset = Some(Duration::from_secs(seconds));
+            } else if name.eq_ignore_ascii_case(HEADER_DOCKER_RATE_LIMIT_SOURCE) {
+                rate_limit.source = Some(value.to_string());
+                continue;
+            } else {
+                continue;
+            }
+            found = true;
+        }
+        Ok(found.then_some(rate_limit))
+    }
+
+    /// Returns the time window of the rate limit, from the quota or the
+    /// remaining quota.
+    pub fn window(&self) -> Option<Duration> {
+        self.limit
+            .and_then(|limit| limit.window)
+            .or_else(|| self.remaining.and_then(|remaining| remaining.window))
+    }
+
+    /// Returns true if no requests remain until the quota resets.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining.is_some_and(|remaining| remaining.value == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rate_limit_headers() -> Result<()> {
+        let rate_limit = RateLimit::from_headers([
+            ("Content-Type", "application/json"),
+            (
+                "X-RateLimit-Limit",
+                "10, 10;w=1, 50;w=60;comment=\"sliding\"",
+            ),
+            ("RateLimit-Remaining", "7"),
+            ("ratelimit-reset", "30"),
+        ])?
+        .expect("rate limit is reported");
+        assert_eq!(rate_limit.limit(), &Some(RateLimitQuota::new(10, None)));
+        assert_eq!(
+            rate_limit.policies(),
+            &[
+                RateLimitQuota::new(10, Some(Duration::from_secs(1))),
+                RateLimitQuota::new(50, Some(Duration::from_secs(60))),
+            ]
+        );
+        assert_eq!(rate_limit.remaining().map(|r| r.value()), Some(7));
+        assert_eq!(rate_limit.reset(), &Some(Duration::from_secs(30)));
+        assert_eq!(rate_limit.window(), None);
+        assert!(!rate_limit.is_exhausted());
+        assert_eq!(rate_limit.policies()[1].to_string(), "50;w=60");
+
+        assert!(RateLimit::from_headers([("Docker-RateLimit-Source", "192.0.2.1")])?.is_none());
+        assert!(RateLimit::from_headers([("RateLimit-Limit", "many")]).is_err());
+        assert!(RateLimit::from_headers([("RateLimit-Remaining", "1;w")]).is_err());
+        assert!(RateLimit::from_headers([("RateLimit-Reset", "-1")]).is_err());
+        Ok(())
+    }
+}
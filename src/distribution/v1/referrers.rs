@@ -0,0 +1,146 @@
+//! Response headers of the referrers API of the distribution spec.
+
+use getset::Getters;
+use std::fmt::{self, Display};
+
+/// The response header a registry sets on a manifest `PUT` when it processed
+/// the subject of the manifest, containing the digest of the subject.
+pub const OCI_SUBJECT_HEADER: &str = "OCI-Subject";
+
+/// The response header a registry sets on a referrers response when it
+/// applied the requested filters, containing a comma separated list of them.
+pub const OCI_FILTERS_APPLIED_HEADER: &str = "OCI-Filters-Applied";
+
+/// The filter of the referrers API selecting referrers by their artifact
+/// type.
+pub const FILTER_ARTIFACT_TYPE: &str = "artifactType";
+
+/// Whether a registry processed the subject of a pushed manifest, as
+/// reported by the [OCI_SUBJECT_HEADER].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SubjectProcessing {
+    /// The registry processed the subject, the manifest is listed by the
+    /// referrers API of the contained subject digest.
+    Processed(String),
+
+    /// The registry did not report processing the subject. Clients have to
+    /// update the referrers tag of the subject themselves, see
+    /// [referrers_fallback_tag].
+    NotProcessed,
+}
+
+impl SubjectProcessing {
+    /// Interpret the value of the `OCI-Subject` header of a manifest `PUT`
+    /// response, `None` if the header is missing.
+    pub fn from_header(value: Option<&str>) -> Self {
+        match value.map(str::trim).filter(|value| !value.is_empty()) {
+            Some(digest) => Self::Processed(digest.to_string()),
+            None => Self::NotProcessed,
+        }
+    }
+
+    /// Returns true if the registry processed the subject.
+    pub fn is_processed(&self) -> bool {
+        matches!(self, Self::Processed(_))
+    }
+
+    /// The digest of the subject reported by the registry, if processed.
+    pub fn digest(&self) -> Option<&str> {
+        match self {
+            Self::Processed(digest) => Some(digest),
+            Self::NotProcessed => None,
+        }
+    }
+}
+
+/// The tag of the referrers tag schema clients fall back to if a registry
+/// does not support the referrers API, e.g. `sha256-<hex>` for the subject
+/// `sha256:<hex>`. The algorithm is truncated to 32 and the encoded digest
+/// to 64 characters as required by the spec.
+pub fn referrers_fallback_tag(subject: &str) -> String {
+    let (algorithm, encoded) = subject.split_once(':').unwrap_or(("", subject));
+    let truncate = |s: &str, len: usize| s.chars().take(len).collect::<String>();
+    format!("{}-{}", truncate(algorithm, 32), truncate(encoded, 64))
+}
+
+#[derive(Clone, Debug, Default, Eq, Getters, PartialEq)]
+/// The filters a registry applied to a referrers response, as reported by
+/// the [OCI_FILTERS_APPLIED_HEADER].
+///
+/// If a requested filter was not applied, clients have to filter the
+/// returned referrers themselves.
+#[non_exhaustive]
+pub struct FiltersApplied {
+    /// The applied filters, e.g. [FILTER_ARTIFACT_TYPE].
+    #[getset(get = "pub")]
+    filters: Vec<String>,
+}
+
+impl FiltersApplied {
+    /// Interpret the value of the `OCI-Filters-Applied` header of a
+    /// referrers response, `None` if the header is missing.
+    pub fn from_header(value: Option<&str>) -> Self {
+        let filters = value
+            .into_iter()
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|filter| !filter.is_empty())
+            .map(String::from)
+            .collect();
+        Self { filters }
+    }
+
+    /// Returns true if the registry applied `filter`.
+    pub fn contains(&self, filter: &str) -> bool {
+        self.filters.iter().any(|applied| applied == filter)
+    }
+
+    /// Returns true if the registry applied the `artifactType` filter.
+    pub fn artifact_type(&self) -> bool {
+        self.contains(FILTER_ARTIFACT_TYPE)
+    }
+}
+
+impl Display for FiltersApplied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.filters.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356";
+
+    #[test]
+    fn subject_header() {
+        let processed = SubjectProcessing::from_header(Some(DIGEST));
+        assert!(processed.is_processed());
+        assert_eq!(processed.digest(), Some(DIGEST));
+
+        for missing in [None, Some(""), Some("  ")] {
+            assert_eq!(
+                SubjectProcessing::from_header(missing),
+                SubjectProcessing::NotProcessed
+            );
+        }
+        assert_eq!(
+            referrers_fallback_tag(DIGEST),
+            "sha256-c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356"
+        );
+    }
+
+    #[test]
+    fn filters_applied_header() {
+        let filters = FiltersApplied::from_header(Some("artifactType, annotation"));
+        assert!(filters.artifact_type());
+        assert!(filters.contains("annotation"));
+        assert_eq!(filters.to_string(), "artifactType,annotation");
+
+        let filters = FiltersApplied::from_header(None);
+        assert!(!filters.artifact_type());
+        assert!(filters.filters().is_empty());
+    }
+}
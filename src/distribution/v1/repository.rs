@@ -13,6 +13,10 @@ use serde::{Deserialize, Serialize};
 )]
 #[getset(get = "pub", set = "pub")]
 /// RepositoryList returns a catalog of repositories maintained on the registry.
+///
+/// It is the response of the `/v2/_catalog` endpoint, which can be paginated
+/// using [Pagination](super::Pagination).
+#[non_exhaustive]
 pub struct RepositoryList {
     /// The items of the RepositoryList.
     repositories: Vec<String>,
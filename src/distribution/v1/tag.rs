@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 )]
 #[getset(get = "pub", set = "pub")]
 /// A list of tags for a given repository.
+#[non_exhaustive]
 pub struct TagList {
     /// The namespace of the repository.
     name: String,
@@ -0,0 +1,168 @@
+//! Warning header types of the distribution spec.
+
+use crate::error::{oci_error, OciSpecError, Result};
+use getset::{CopyGetters, Getters};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// The warn-code registries use for the warnings defined by the distribution
+/// spec.
+pub const WARNING_CODE_MISCELLANEOUS: u16 = 299;
+
+#[derive(Clone, CopyGetters, Debug, Eq, Getters, PartialEq)]
+/// A warning returned by a registry in a `Warning` response header, e.g.
+/// `299 - "this repository is deprecated"`.
+///
+/// Registries use them to tell clients about deprecations or approaching
+/// rate limits, which should be shown to the user.
+#[non_exhaustive]
+pub struct RegistryWarning {
+    /// The warn-code, registries use [WARNING_CODE_MISCELLANEOUS].
+    #[getset(get_copy = "pub")]
+    code: u16,
+
+    /// The warn-agent, usually `-`.
+    #[getset(get = "pub")]
+    agent: String,
+
+    /// The warning message.
+    #[getset(get = "pub")]
+    text: String,
+}
+
+impl RegistryWarning {
+    /// Parse every warning of the values of all `Warning` headers of a
+    /// response. Each header value may contain a comma separated list of
+    /// warnings.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other] if a warning is
+    /// malformed.
+    pub fn parse_headers<'a, I>(values: I) -> Result<Vec<Self>>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut warnings = Vec::new();
+        for value in values {
+            let mut rest = value.trim();
+            while !rest.is_empty() {
+                let (warning, remaining) = Self::parse_one(rest)?;
+                warnings.push(warning);
+                rest = remaining.trim_start();
+                rest = match rest.strip_prefix(',') {
+                    Some(remaining) => remaining.trim_start(),
+                    None if rest.is_empty() => rest,
+                    None => return Err(oci_error(format!("invalid warning header: {value}"))),
+                };
+            }
+        }
+        Ok(warnings)
+    }
+
+    /// Parse a single warning from the start of `s`, returning the remaining
+    /// input. An optional warn-date after the text is skipped.
+    fn parse_one(s: &str) -> Result<(Self, &str)> {
+        let invalid = || oci_error(format!("invalid warning: {s}"));
+
+        let (code, rest) = s.split_once(' ').ok_or_else(invalid)?;
+        let code = match code.parse::<u16>() {
+            Ok(code) if (100..1000).contains(&code) => code,
+            _ => return Err(invalid()),
+        };
+        let (agent, rest) = rest.trim_start().split_once(' ').ok_or_else(invalid)?;
+        let (text, mut rest) = parse_quoted(rest.trim_start()).ok_or_else(invalid)?;
+
+        let trimmed = rest.trim_start();
+        if trimmed.starts_with('"') {
+            let (_, after_date) = parse_quoted(trimmed).ok_or_else(invalid)?;
+            rest = after_date;
+        }
+
+        let warning = Self {
+            code,
+            agent: agent.to_string(),
+            text,
+        };
+        Ok((warning, rest))
+    }
+}
+
+/// Parse a quoted-string from the start of `s`, returning its unescaped
+/// content and the remaining input.
+fn parse_quoted(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut text = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((text, &s[i + 2..])),
+            '\\' => text.push(chars.next()?.1),
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+impl FromStr for RegistryWarning {
+    type Err = OciSpecError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::parse_headers([s])?.as_slice() {
+            [warning] => Ok(warning.clone()),
+            _ => Err(oci_error(format!("expected a single warning: {s}"))),
+        }
+    }
+}
+
+impl Display for RegistryWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = self.text.replace('\\', "\\\\").replace('"', "\\\"");
+        write!(f, "{} {} \"{text}\"", self.code, self.agent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_warning() -> Result<()> {
+        let warning: RegistryWarning = r#"299 - "this repository is \"deprecated\"""#.parse()?;
+        assert_eq!(warning.code(), WARNING_CODE_MISCELLANEOUS);
+        assert_eq!(warning.agent(), "-");
+        assert_eq!(warning.text(), r#"this repository is "deprecated""#);
+        assert_eq!(
+            warning.to_string(),
+            r#"299 - "this repository is \"deprecated\"""#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_warning_headers() -> Result<()> {
+        let warnings = RegistryWarning::parse_headers([
+            r#"299 - "first, with comma", 299 registry.example "second" "Sat, 25 Aug 2012 23:34:45 GMT""#,
+            r#"299 - "third""#,
+        ])?;
+        let texts: Vec<_> = warnings.iter().map(|w| w.text().as_str()).collect();
+        assert_eq!(texts, ["first, with comma", "second", "third"]);
+        assert_eq!(warnings[1].agent(), "registry.example");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid_warnings() {
+        for invalid in [
+            "299",
+            "299 -",
+            "abc - \"text\"",
+            "299 - \"unterminated",
+            "299 - \"a\" x",
+        ] {
+            assert!(
+                RegistryWarning::parse_headers([invalid]).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+}
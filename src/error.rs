@@ -1,6 +1,12 @@
 //! Error types of the crate.
 
-use std::{borrow::Cow, io};
+#[cfg(any(feature = "distribution", feature = "image", feature = "runtime"))]
+use std::borrow::Cow;
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+use strum_macros::{Display as StrumDisplay, EnumString, IntoStaticStr};
 use thiserror::Error;
 
 /// Spezialized result type for oci spec operations. It is
@@ -12,6 +18,7 @@ pub type Result<T> = std::result::Result<T, OciSpecError>;
 
 /// Error type for oci spec errors.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum OciSpecError {
     /// Will be returned if an error occurs that cannot
     /// be mapped to a more specialized error variant.
@@ -28,11 +35,331 @@ pub enum OciSpecError {
     #[error("serde failed")]
     SerDe(#[from] serde_json::Error),
 
+    /// Will be returned when an io operation on a file fails, e.g. when
+    /// loading or saving a spec.
+    #[error("io operation on {} failed", path.display())]
+    FileIo {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying io error.
+        #[source]
+        source: io::Error,
+    },
+
+    /// Will be returned when the content of a file cannot be serialized or
+    /// deserialized.
+    #[error("serde of {} failed", path.display())]
+    FileSerDe {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying serde error.
+        #[source]
+        source: serde_json::Error,
+    },
+
     /// Builder specific errors.
     #[error("uninitialized field")]
     Builder(#[from] derive_builder::UninitializedFieldError),
+
+    /// Will be returned if a value does not pass validation.
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+}
+
+impl OciSpecError {
+    /// Returns the underlying io error, if the error was caused by an io operation.
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            Self::Io(err) | Self::FileIo { source: err, .. } => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Returns the path of the file the error relates to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::FileIo { path, .. } | Self::FileSerDe { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of a [ValidationError].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidationErrorKind {
+    /// A field that is required in the current context is not set.
+    Missing,
+    /// A value is outside of the range accepted for the field.
+    OutOfRange,
+    /// A value does not match the format required for the field.
+    InvalidFormat,
+    /// Two or more fields have values which cannot be used together.
+    Conflict,
+}
+
+/// Stable code of the rule a [ValidationError] or a lint finding is about.
+///
+/// Unlike the messages, codes do not change between releases, so that tools
+/// can filter, suppress and document findings. The codes are displayed and
+/// parsed in screaming snake case, e.g. `MOUNT_ID_MAPPINGS_WITHOUT_USERNS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, StrumDisplay, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum FindingCode {
+    /// A required field is not set, used if no specific code applies.
+    FieldMissing,
+    /// A value is out of range, used if no specific code applies.
+    ValueOutOfRange,
+    /// A value has an invalid format, used if no specific code applies.
+    InvalidFormat,
+    /// Fields conflict with each other, used if no specific code applies.
+    Conflict,
+
+    /// A path is not valid UTF-8.
+    PathNotUtf8,
+    /// A document exceeds a limit of the parse options.
+    ParseLimitExceeded,
+    /// An annotation key is empty.
+    AnnotationKeyEmpty,
+    /// An annotation key uses the reserved `org.opencontainers` namespace.
+    AnnotationKeyReserved,
+
+    /// The root filesystem path is empty.
+    RootPathEmpty,
+    /// The root filesystem is not a directory.
+    RootNotDirectory,
+    /// The root filesystem directory is empty.
+    RootEmpty,
+    /// Only one of the uid and gid mappings of a mount is set.
+    MountIdMappingsIncomplete,
+    /// A mount has ID mappings without a user namespace or idmap option.
+    MountIdMappingsWithoutUserns,
+    /// An idmapped mount without ID mappings lacks a user namespace.
+    IdmapMountWithoutUserns,
+    /// An idmapped mount without ID mappings lacks the container mappings.
+    IdmapMountMappingsMissing,
+    /// The console size is out of range.
+    ConsoleSizeOutOfRange,
+    /// A Windows user name is invalid.
+    WindowsUsernameInvalid,
+    /// A process field is not supported with a windows section.
+    WindowsUnsupportedField,
+    /// The CPU affinity of exec'd processes is invalid.
+    ExecCpuAffinityInvalid,
+    /// The memory+swap limit is lower than the memory limit.
+    MemorySwapBelowLimit,
+    /// The memory reservation is greater than the memory limit.
+    MemoryReservationAboveLimit,
+    /// The realtime runtime is greater than the realtime period.
+    CpuRealtimeRuntimeAbovePeriod,
+    /// A CPU or memory node list is invalid.
+    CpuListInvalid,
+    /// A device type is invalid.
+    DeviceTypeInvalid,
+    /// A device path is not an absolute path under `/dev`.
+    DevicePathInvalid,
+    /// A character or block device lacks its major or minor number.
+    DeviceNumberMissing,
+    /// A device path is defined more than once.
+    DeviceDuplicate,
+    /// A device redefines one of the default devices.
+    DeviceRedefinesDefault,
+    /// The PID of a container state is invalid.
+    StatePidInvalid,
+    /// A file descriptor name of a container state is empty.
+    StateFdNameEmpty,
+    /// A file descriptor name of a container state is not unique.
+    StateFdNameDuplicate,
+    /// The seccomp file descriptor was not passed with a container state.
+    StateSeccompFdMissing,
+
+    /// A platform has no operating system.
+    PlatformOsMissing,
+    /// A platform has no architecture.
+    PlatformArchitectureMissing,
+    /// A platform variant is invalid for the architecture.
+    PlatformVariantInvalid,
+    /// A platform OS feature is unknown.
+    PlatformOsFeatureUnknown,
+    /// A platform string is not in the `os/arch[/variant]` format.
+    PlatformFormatInvalid,
+    /// A compilation target cannot be mapped to a platform.
+    PlatformTargetUnknown,
+    /// A descriptor URL is not an absolute http or https URL.
+    DescriptorUrlInvalid,
+    /// A descriptor size is negative or overflows the int64 range of the spec.
+    DescriptorSizeInvalid,
+    /// A media type is not allowed for the field.
+    MediaTypeInvalid,
+    /// A subject descriptor sets a platform.
+    SubjectPlatformSet,
+    /// The root filesystem type of an image configuration is not `layers`.
+    RootfsTypeInvalid,
+    /// A DiffID of an image configuration is not a valid digest.
+    DiffIdInvalid,
+    /// A reference name of an image index is invalid.
+    RefNameInvalid,
+    /// A reference name points to different manifests in merged indexes.
+    RefNameConflict,
+    /// The image layout version is not set.
+    LayoutVersionMissing,
+    /// A blob path is not in the `blobs/<alg>/<encoded>` format.
+    BlobPathInvalid,
+    /// A blob referenced by a descriptor does not exist.
+    BlobMissing,
+    /// The size of a blob does not match its descriptor.
+    BlobSizeMismatch,
+    /// Image indexes reference each other in a cycle or are nested too
+    /// deeply.
+    IndexNestingInvalid,
+
+    /// Lint: the process may gain additional privileges.
+    NoNewPrivilegesDisabled,
+    /// Lint: `/sys` is mounted writable.
+    WritableSysfs,
+    /// Lint: the device cgroup allows access to all devices.
+    BroadDeviceAccess,
+    /// Lint: no seccomp profile is applied.
+    SeccompDisabled,
+    /// Lint: the process keeps a dangerous capability.
+    DangerousCapability,
+    /// Lint: an annotation key is not in reverse domain notation.
+    AnnotationKeyFormat,
+    /// Lint: the OOM score adjustment has no effect as the OOM killer is
+    /// disabled.
+    OomScoreAdjConflict,
+}
+
+impl FindingCode {
+    /// Returns the code as string, e.g. `BLOB_MISSING`.
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+}
+
+impl From<ValidationErrorKind> for FindingCode {
+    fn from(kind: ValidationErrorKind) -> Self {
+        match kind {
+            ValidationErrorKind::Missing => Self::FieldMissing,
+            ValidationErrorKind::OutOfRange => Self::ValueOutOfRange,
+            ValidationErrorKind::InvalidFormat => Self::InvalidFormat,
+            ValidationErrorKind::Conflict => Self::Conflict,
+        }
+    }
+}
+
+/// Structured error describing why a value did not pass validation.
+///
+/// The path is JSON-pointer-like, using the serialized field names separated by
+/// dots, e.g. `linux.resources.cpu.period`. Validation of nested types reports
+/// the path relative to the validated type, callers which embed it can use
+/// [ValidationError::with_parent] to make the path absolute.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub struct ValidationError {
+    kind: ValidationErrorKind,
+    code: FindingCode,
+    path: String,
+    message: String,
+    expected: Option<String>,
+    actual: Option<String>,
+}
+
+impl ValidationError {
+    /// Create a new validation error of `kind` for the field at `path`, with
+    /// the generic [FindingCode] of the kind.
+    pub fn new(
+        kind: ValidationErrorKind,
+        path: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            code: kind.into(),
+            path: path.into(),
+            message: message.into(),
+            expected: None,
+            actual: None,
+        }
+    }
+
+    /// Set the code of the rule the value violates.
+    pub fn with_code(mut self, code: FindingCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Attach a description of the expected value.
+    pub fn with_expected(mut self, expected: impl Into<String>) -> Self {
+        self.expected = Some(expected.into());
+        self
+    }
+
+    /// Attach the actual value which failed validation.
+    pub fn with_actual(mut self, actual: impl Into<String>) -> Self {
+        self.actual = Some(actual.into());
+        self
+    }
+
+    /// Prefix the field path with the path of the parent object.
+    pub fn with_parent(mut self, parent: impl AsRef<str>) -> Self {
+        let parent = parent.as_ref();
+        if !parent.is_empty() {
+            self.path = if self.path.is_empty() {
+                parent.to_owned()
+            } else {
+                format!("{parent}.{}", self.path)
+            };
+        }
+        self
+    }
+
+    /// The kind of the validation error.
+    pub fn kind(&self) -> ValidationErrorKind {
+        self.kind
+    }
+
+    /// The stable code of the rule the value violates.
+    pub fn code(&self) -> FindingCode {
+        self.code
+    }
+
+    /// The path of the field which failed validation.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The human readable description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The expected value, if known.
+    pub fn expected(&self) -> Option<&str> {
+        self.expected.as_deref()
+    }
+
+    /// The actual value, if known.
+    pub fn actual(&self) -> Option<&str> {
+        self.actual.as_deref()
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        write!(f, "{}", self.message)?;
+        match (&self.expected, &self.actual) {
+            (Some(expected), Some(actual)) => write!(f, " (expected {expected}, got {actual})"),
+            (Some(expected), None) => write!(f, " (expected {expected})"),
+            (None, Some(actual)) => write!(f, " (got {actual})"),
+            (None, None) => Ok(()),
+        }
+    }
 }
 
+#[cfg(any(feature = "distribution", feature = "image", feature = "runtime"))]
 pub(crate) fn oci_error<'a, M>(message: M) -> OciSpecError
 where
     M: Into<Cow<'a, str>>,
@@ -43,3 +370,71 @@ where
         Cow::Owned(s) => OciSpecError::Other(s),
     }
 }
+
+pub(crate) fn file_io_error(path: &Path) -> impl FnOnce(io::Error) -> OciSpecError + '_ {
+    move |source| OciSpecError::FileIo {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+pub(crate) fn file_serde_error(path: &Path) -> impl FnOnce(serde_json::Error) -> OciSpecError + '_ {
+    move |source| OciSpecError::FileSerDe {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// Make the path of a validation error of a nested value relative to its
+/// parent, see [ValidationError::with_parent]. Other errors are returned
+/// unchanged.
+#[cfg(any(feature = "image", feature = "runtime"))]
+pub(crate) fn with_parent(parent: impl AsRef<str>) -> impl FnOnce(OciSpecError) -> OciSpecError {
+    move |err| match err {
+        OciSpecError::Validation(err) => err.with_parent(parent).into(),
+        err => err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation_error_display() {
+        let err =
+            ValidationError::new(ValidationErrorKind::OutOfRange, "period", "must be >= 1000")
+                .with_actual("10")
+                .with_parent("cpu")
+                .with_parent("linux.resources");
+        assert_eq!(err.path(), "linux.resources.cpu.period");
+        assert_eq!(err.code(), FindingCode::ValueOutOfRange);
+        assert_eq!(err.code().as_str(), "VALUE_OUT_OF_RANGE");
+        assert_eq!(
+            "BLOB_SIZE_MISMATCH".parse::<FindingCode>(),
+            Ok(FindingCode::BlobSizeMismatch)
+        );
+        assert_eq!(
+            OciSpecError::from(err).to_string(),
+            "linux.resources.cpu.period: must be >= 1000 (got 10)"
+        );
+    }
+
+    #[test]
+    fn file_io_error_chain() {
+        use std::error::Error;
+
+        let path = Path::new("/does/not/exist/config.json");
+        let err = file_io_error(path)(io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(
+            err.to_string(),
+            "io operation on /does/not/exist/config.json failed"
+        );
+        assert_eq!(err.path(), Some(path));
+        assert_eq!(
+            err.io_error().map(io::Error::kind),
+            Some(io::ErrorKind::NotFound)
+        );
+        assert!(err.source().is_some());
+    }
+}
@@ -0,0 +1,552 @@
+//! Serialization in the style of Go's `encoding/json` for the structs of
+//! the Go reference implementations of the specifications. The output has
+//! not been compared against output of the Go implementations, so it is not
+//! guaranteed to be byte-identical.
+//!
+//! Go orders the fields of an object by their declaration in the Go struct,
+//! omits `omitempty` fields holding a zero value (`false`, `0`, `""`, or an
+//! empty array or object) and escapes `<`, `>`, `&`, U+2028 and U+2029 in
+//! strings. Maps are written with sorted keys.
+//!
+//! Only the structs described by the schemas below get the Go field order
+//! and `omitempty` handling. Every other object is written with sorted keys
+//! and all of its members, independent of whether serde_json keeps the
+//! insertion order, so the output is stable but matches Go only for the
+//! fields covered by a schema and for maps.
+
+use crate::error::Result;
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde_json::{ser::Formatter, Map, Value};
+use std::io::{self, Write};
+
+/// The fields of a Go struct in declaration order.
+pub(crate) struct GoStruct(&'static [GoField]);
+
+/// A field of a Go struct.
+pub(crate) struct GoField {
+    name: &'static str,
+    omit_empty: bool,
+    nested: Nested,
+}
+
+/// How the value of a [GoField] is written.
+enum Nested {
+    /// The value is written as it is, with the keys of objects sorted.
+    Plain,
+    /// The value, or every element of an array value, is a Go struct.
+    Struct(&'static GoStruct),
+    /// The value is an array of strings which is held in a set on the Rust
+    /// side and is sorted to get a stable order.
+    #[cfg(feature = "runtime")]
+    SortedStrings,
+}
+
+impl GoField {
+    const fn new(name: &'static str, omit_empty: bool, nested: Nested) -> Self {
+        Self {
+            name,
+            omit_empty,
+            nested,
+        }
+    }
+}
+
+/// A field which is always written.
+const fn field(name: &'static str) -> GoField {
+    GoField::new(name, false, Nested::Plain)
+}
+
+/// A value typed `omitempty` field, which is dropped if it holds a zero value.
+const fn omit(name: &'static str) -> GoField {
+    GoField::new(name, true, Nested::Plain)
+}
+
+/// A field holding a Go struct or an array of Go structs.
+const fn nested(name: &'static str, omit_empty: bool, schema: &'static GoStruct) -> GoField {
+    GoField::new(name, omit_empty, Nested::Struct(schema))
+}
+
+#[cfg(feature = "runtime")]
+pub(crate) mod runtime {
+    use super::*;
+
+    const USER: GoStruct = GoStruct(&[
+        field("uid"),
+        field("gid"),
+        field("umask"),
+        omit("additionalGids"),
+        omit("username"),
+    ]);
+
+    const CAPABILITIES: GoStruct = GoStruct(&[
+        GoField::new("bounding", true, Nested::SortedStrings),
+        GoField::new("effective", true, Nested::SortedStrings),
+        GoField::new("inheritable", true, Nested::SortedStrings),
+        GoField::new("permitted", true, Nested::SortedStrings),
+        GoField::new("ambient", true, Nested::SortedStrings),
+    ]);
+
+    const BOX: GoStruct = GoStruct(&[field("height"), field("width")]);
+
+    const RLIMIT: GoStruct = GoStruct(&[field("type"), field("hard"), field("soft")]);
+
+    const PROCESS: GoStruct = GoStruct(&[
+        omit("terminal"),
+        nested("consoleSize", false, &BOX),
+        nested("user", false, &USER),
+        omit("args"),
+        omit("commandLine"),
+        omit("env"),
+        field("cwd"),
+        nested("capabilities", false, &CAPABILITIES),
+        nested("rlimits", true, &RLIMIT),
+        omit("noNewPrivileges"),
+        omit("apparmorProfile"),
+        field("oomScoreAdj"),
+        field("scheduler"),
+        omit("selinuxLabel"),
+        field("ioPriority"),
+        field("execCPUAffinity"),
+    ]);
+
+    const ROOT: GoStruct = GoStruct(&[field("path"), omit("readonly")]);
+
+    const MOUNT: GoStruct = GoStruct(&[
+        field("destination"),
+        omit("type"),
+        omit("source"),
+        omit("options"),
+        nested("uidMappings", true, &ID_MAPPING),
+        nested("gidMappings", true, &ID_MAPPING),
+    ]);
+
+    const HOOK: GoStruct = GoStruct(&[field("path"), omit("args"), omit("env"), field("timeout")]);
+
+    const HOOKS: GoStruct = GoStruct(&[
+        nested("prestart", true, &HOOK),
+        nested("createRuntime", true, &HOOK),
+        nested("createContainer", true, &HOOK),
+        nested("startContainer", true, &HOOK),
+        nested("poststart", true, &HOOK),
+        nested("poststop", true, &HOOK),
+    ]);
+
+    const DEVICE_CGROUP: GoStruct = GoStruct(&[
+        field("allow"),
+        omit("type"),
+        field("major"),
+        field("minor"),
+        omit("access"),
+    ]);
+
+    const MEMORY: GoStruct = GoStruct(&[
+        field("limit"),
+        field("reservation"),
+        field("swap"),
+        field("kernel"),
+        field("kernelTCP"),
+        field("swappiness"),
+        field("disableOOMKiller"),
+        field("useHierarchy"),
+        field("checkBeforeUpdate"),
+    ]);
+
+    const CPU: GoStruct = GoStruct(&[
+        field("shares"),
+        field("quota"),
+        field("burst"),
+        field("period"),
+        field("realtimeRuntime"),
+        field("realtimePeriod"),
+        omit("cpus"),
+        omit("mems"),
+        field("idle"),
+    ]);
+
+    const RESOURCES: GoStruct = GoStruct(&[
+        nested("devices", true, &DEVICE_CGROUP),
+        nested("memory", false, &MEMORY),
+        nested("cpu", false, &CPU),
+        field("pids"),
+        field("blockIO"),
+        omit("hugepageLimits"),
+        field("network"),
+        omit("rdma"),
+        omit("unified"),
+    ]);
+
+    const ID_MAPPING: GoStruct = GoStruct(&[field("containerID"), field("hostID"), field("size")]);
+
+    const NAMESPACE: GoStruct = GoStruct(&[field("type"), omit("path")]);
+
+    const DEVICE: GoStruct = GoStruct(&[
+        field("path"),
+        field("type"),
+        field("major"),
+        field("minor"),
+        field("fileMode"),
+        field("uid"),
+        field("gid"),
+    ]);
+
+    const SECCOMP_ARG: GoStruct = GoStruct(&[
+        field("index"),
+        field("value"),
+        omit("valueTwo"),
+        field("op"),
+    ]);
+
+    const SYSCALL: GoStruct = GoStruct(&[
+        field("names"),
+        field("action"),
+        field("errnoRet"),
+        nested("args", true, &SECCOMP_ARG),
+    ]);
+
+    const SECCOMP: GoStruct = GoStruct(&[
+        field("defaultAction"),
+        field("defaultErrnoRet"),
+        omit("architectures"),
+        omit("flags"),
+        omit("listenerPath"),
+        omit("listenerMetadata"),
+        nested("syscalls", true, &SYSCALL),
+    ]);
+
+    const LINUX: GoStruct = GoStruct(&[
+        nested("uidMappings", true, &ID_MAPPING),
+        nested("gidMappings", true, &ID_MAPPING),
+        omit("sysctl"),
+        nested("resources", false, &RESOURCES),
+        omit("cgroupsPath"),
+        nested("namespaces", true, &NAMESPACE),
+        nested("devices", true, &DEVICE),
+        omit("netDevices"),
+        nested("seccomp", false, &SECCOMP),
+        omit("rootfsPropagation"),
+        omit("maskedPaths"),
+        omit("readonlyPaths"),
+        omit("mountLabel"),
+        field("intelRdt"),
+        field("memoryPolicy"),
+        field("personality"),
+        omit("timeOffsets"),
+    ]);
+
+    /// The `Spec` struct of `github.com/opencontainers/runtime-spec/specs-go`.
+    pub(crate) const SPEC: GoStruct = GoStruct(&[
+        field("ociVersion"),
+        nested("process", false, &PROCESS),
+        nested("root", false, &ROOT),
+        omit("hostname"),
+        omit("domainname"),
+        nested("mounts", true, &MOUNT),
+        nested("hooks", false, &HOOKS),
+        omit("annotations"),
+        nested("linux", false, &LINUX),
+        field("solaris"),
+        field("windows"),
+        field("vm"),
+        field("zos"),
+    ]);
+}
+
+#[cfg(feature = "image")]
+pub(crate) mod image {
+    use super::*;
+
+    const PLATFORM: GoStruct = GoStruct(&[
+        field("architecture"),
+        field("os"),
+        omit("os.version"),
+        omit("os.features"),
+        omit("variant"),
+    ]);
+
+    const DESCRIPTOR: GoStruct = GoStruct(&[
+        field("mediaType"),
+        field("digest"),
+        field("size"),
+        omit("urls"),
+        omit("annotations"),
+        omit("data"),
+        nested("platform", false, &PLATFORM),
+        omit("artifactType"),
+    ]);
+
+    /// The `Manifest` struct of `github.com/opencontainers/image-spec/specs-go/v1`.
+    pub(crate) const MANIFEST: GoStruct = GoStruct(&[
+        field("schemaVersion"),
+        omit("mediaType"),
+        omit("artifactType"),
+        nested("config", false, &DESCRIPTOR),
+        nested("layers", false, &DESCRIPTOR),
+        nested("subject", false, &DESCRIPTOR),
+        omit("annotations"),
+    ]);
+
+    /// The `Index` struct of `github.com/opencontainers/image-spec/specs-go/v1`.
+    pub(crate) const INDEX: GoStruct = GoStruct(&[
+        field("schemaVersion"),
+        omit("mediaType"),
+        omit("artifactType"),
+        nested("manifests", false, &DESCRIPTOR),
+        nested("subject", false, &DESCRIPTOR),
+        omit("annotations"),
+    ]);
+
+    const CONFIG: GoStruct = GoStruct(&[
+        omit("User"),
+        omit("ExposedPorts"),
+        omit("Env"),
+        omit("Entrypoint"),
+        omit("Cmd"),
+        omit("Volumes"),
+        omit("WorkingDir"),
+        omit("Labels"),
+        omit("StopSignal"),
+        omit("ArgsEscaped"),
+    ]);
+
+    const ROOTFS: GoStruct = GoStruct(&[field("type"), field("diff_ids")]);
+
+    const HISTORY: GoStruct = GoStruct(&[
+        omit("created"),
+        omit("created_by"),
+        omit("author"),
+        omit("comment"),
+        omit("empty_layer"),
+    ]);
+
+    /// The `Image` struct of `github.com/opencontainers/image-spec/specs-go/v1`.
+    pub(crate) const IMAGE: GoStruct = GoStruct(&[
+        field("created"),
+        omit("author"),
+        field("architecture"),
+        field("os"),
+        omit("os.version"),
+        omit("os.features"),
+        omit("variant"),
+        nested("config", false, &CONFIG),
+        nested("rootfs", false, &ROOTFS),
+        nested("history", true, &HISTORY),
+    ]);
+}
+
+fn is_zero(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(b) => !b,
+        Value::Number(n) => n.as_f64() == Some(0.0),
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.is_empty(),
+    }
+}
+
+/// A [Value] which is serialized the way Go would write the field it is held in.
+struct GoValue<'a>(&'a Value, &'a Nested);
+
+impl Serialize for GoValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match (self.0, self.1) {
+            (Value::Array(items), Nested::Struct(_)) => {
+                serializer.collect_seq(items.iter().map(|item| GoValue(item, self.1)))
+            }
+            (Value::Object(map), Nested::Struct(schema)) => {
+                GoObject(map, schema).serialize(serializer)
+            }
+            #[cfg(feature = "runtime")]
+            (Value::Array(items), Nested::SortedStrings) => {
+                let mut items: Vec<&Value> = items.iter().collect();
+                items.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+                serializer.collect_seq(items)
+            }
+            (value, _) => Sorted(value).serialize(serializer),
+        }
+    }
+}
+
+/// A [Value] whose objects are written with sorted keys, like Go writes maps.
+struct Sorted<'a>(&'a Value);
+
+impl Serialize for Sorted<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Array(items) => serializer.collect_seq(items.iter().map(Sorted)),
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by_key(|(key, _)| *key);
+                serializer.collect_map(entries.into_iter().map(|(k, v)| (k, Sorted(v))))
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+/// A JSON object written in the declaration order of a Go struct, without
+/// its zero valued `omitempty` fields. Unknown members are written sorted
+/// after the known ones.
+struct GoObject<'a>(&'a Map<String, Value>, &'a GoStruct);
+
+impl Serialize for GoObject<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let GoObject(map, schema) = self;
+        let mut object = serializer.serialize_map(None)?;
+        for field in schema.0 {
+            match map.get(field.name) {
+                Some(value) if !(field.omit_empty && is_zero(value)) => {
+                    object.serialize_entry(field.name, &GoValue(value, &field.nested))?;
+                }
+                _ => {}
+            }
+        }
+        let mut unknown: Vec<_> = map
+            .iter()
+            .filter(|(key, _)| !schema.0.iter().any(|field| field.name == *key))
+            .collect();
+        unknown.sort_by_key(|(key, _)| *key);
+        for (key, value) in unknown {
+            object.serialize_entry(key, &Sorted(value))?;
+        }
+        object.end()
+    }
+}
+
+/// A compact [Formatter] which escapes strings the way Go's `encoding/json`
+/// does by default.
+struct GoFormatter;
+
+impl Formatter for GoFormatter {
+    fn write_string_fragment<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        let mut start = 0;
+        for (i, c) in fragment.char_indices() {
+            let escaped = match c {
+                '<' => "\\u003c",
+                '>' => "\\u003e",
+                '&' => "\\u0026",
+                '\u{2028}' => "\\u2028",
+                '\u{2029}' => "\\u2029",
+                _ => continue,
+            };
+            writer.write_all(&fragment.as_bytes()[start..i])?;
+            writer.write_all(escaped.as_bytes())?;
+            start = i + c.len_utf8();
+        }
+        writer.write_all(&fragment.as_bytes()[start..])
+    }
+}
+
+/// Write `item` to `writer` in the style of Go's `json.Marshal` of the Go
+/// struct described by `schema`, see the module documentation for the limits.
+pub(crate) fn to_writer_go<W: Write, T: Serialize>(
+    item: &T,
+    writer: &mut W,
+    schema: &'static GoStruct,
+) -> Result<()> {
+    // The members of a serde_json object may be sorted, so the Go order is
+    // applied while writing instead of by rebuilding the objects.
+    let value = serde_json::to_value(item)?;
+    let mut serializer = serde_json::Serializer::with_formatter(writer, GoFormatter);
+    GoValue(&value, &Nested::Struct(schema)).serialize(&mut serializer)?;
+    Ok(())
+}
+
+/// Serialize `item` to a string in the style of Go's `json.Marshal` of the
+/// Go struct described by `schema`.
+pub(crate) fn to_string_go<T: Serialize>(item: &T, schema: &'static GoStruct) -> Result<String> {
+    let mut out = Vec::new();
+    to_writer_go(item, &mut out, schema)?;
+    Ok(String::from_utf8(out).expect("serde_json writes valid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "image")]
+    use crate::image::{DescriptorBuilder, ImageManifestBuilder, MediaType};
+    #[cfg(feature = "runtime")]
+    use crate::runtime::{ProcessBuilder, Spec};
+    use serde::Serialize;
+    #[cfg(feature = "image")]
+    use std::str::FromStr;
+
+    #[test]
+    #[cfg(feature = "runtime")]
+    fn go_spec_field_order() {
+        // Written by hand from the Go struct definitions for the equivalent of
+        // `Spec::default()`, this checks the field order of the schemas only.
+        let expected = std::fs::read_to_string("test/data/config_go.json").unwrap();
+        assert_eq!(Spec::default().to_string_go().unwrap(), expected.trim_end());
+    }
+
+    #[test]
+    #[cfg(feature = "runtime")]
+    fn go_omits_zero_values() {
+        let mut spec = Spec::default();
+        spec.set_hostname(Some(String::new()));
+        spec.set_mounts(Some(vec![]));
+        spec.set_process(Some(
+            ProcessBuilder::default()
+                .terminal(false)
+                .args(vec!["echo".to_string(), "<a&b>".to_string()])
+                .build()
+                .unwrap(),
+        ));
+        spec.set_linux(None);
+        let json = spec.to_string_go().unwrap();
+        assert!(!json.contains("hostname"));
+        assert!(!json.contains("mounts"));
+        assert!(!json.contains("terminal"));
+        assert!(json.contains(r#""args":["echo","\u003ca\u0026b\u003e"]"#));
+    }
+
+    #[test]
+    fn sorted_plain_objects() {
+        let value = serde_json::json!({"b": {"z": 1, "a": [{"y": 2, "x": 3}]}, "a": 0});
+        let mut out = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut out);
+        super::Sorted(&value).serialize(&mut serializer).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"{"a":0,"b":{"a":[{"x":3,"y":2}],"z":1}}"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn go_manifest() {
+        let config = DescriptorBuilder::default()
+            .media_type(MediaType::ImageConfig)
+            .digest(
+                crate::image::Digest::from_str(
+                    "sha256:b5b2b2c507a0944348e0303114d8d93aaaa081732b86451d9bce1f432a537bc7",
+                )
+                .unwrap(),
+            )
+            .size(7023u64)
+            .data("e30=")
+            .urls(vec![])
+            .build()
+            .unwrap();
+        let manifest = ImageManifestBuilder::default()
+            .schema_version(2u32)
+            .media_type(MediaType::ImageManifest)
+            .config(config)
+            .layers(vec![])
+            .build()
+            .unwrap();
+        assert_eq!(
+            manifest.to_string_go().unwrap(),
+            concat!(
+                r#"{"schemaVersion":2,"mediaType":"application/vnd.oci.image.manifest.v1+json","#,
+                r#""config":{"mediaType":"application/vnd.oci.image.config.v1+json","#,
+                r#""digest":"sha256:b5b2b2c507a0944348e0303114d8d93aaaa081732b86451d9bce1f432a537bc7","#,
+                r#""size":7023,"data":"e30="},"layers":[]}"#
+            )
+        );
+    }
+}
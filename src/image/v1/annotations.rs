@@ -1,3 +1,9 @@
+use super::Digest;
+use crate::error::Result;
+#[cfg(feature = "distribution")]
+use crate::{distribution::Reference, error::oci_error};
+use std::collections::HashMap;
+
 /// AnnotationCreated is the annotation key for the date and time on which the
 /// image was built (date-time string as defined by RFC 3339).
 pub const ANNOTATION_CREATED: &str = "org.opencontainers.image.created";
@@ -55,3 +61,38 @@ pub const ANNOTATION_BASE_IMAGE_DIGEST: &str = "org.opencontainers.image.base.di
 /// AnnotationBaseImageName is the annotation key for the image reference of the
 /// image's base image.
 pub const ANNOTATION_BASE_IMAGE_NAME: &str = "org.opencontainers.image.base.name";
+
+/// Parse the base image name annotation of `annotations`.
+#[cfg(feature = "distribution")]
+pub(crate) fn base_image_name(
+    annotations: Option<&HashMap<String, String>>,
+) -> Result<Option<Reference>> {
+    annotations
+        .and_then(|annotations| annotations.get(ANNOTATION_BASE_IMAGE_NAME))
+        .map(|name| {
+            name.parse()
+                .map_err(|err| oci_error(format!("invalid base image name {name}: {err}")))
+        })
+        .transpose()
+}
+
+/// Parse the base image digest annotation of `annotations`.
+pub(crate) fn base_image_digest(
+    annotations: Option<&HashMap<String, String>>,
+) -> Result<Option<Digest>> {
+    annotations
+        .and_then(|annotations| annotations.get(ANNOTATION_BASE_IMAGE_DIGEST))
+        .map(|digest| digest.parse())
+        .transpose()
+}
+
+/// Record the base image `name` and its manifest `digest` in `annotations`.
+#[cfg(feature = "distribution")]
+pub(crate) fn set_base_image(
+    annotations: &mut HashMap<String, String>,
+    name: &Reference,
+    digest: &Digest,
+) {
+    annotations.insert(ANNOTATION_BASE_IMAGE_NAME.to_string(), name.to_string());
+    annotations.insert(ANNOTATION_BASE_IMAGE_DIGEST.to_string(), digest.to_string());
+}
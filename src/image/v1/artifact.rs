@@ -20,6 +20,7 @@ use std::{
 )]
 /// The OCI Artifact manifest describes content addressable artifacts
 /// in order to store them along side container images in a registry.
+#[non_exhaustive]
 pub struct ArtifactManifest {
     /// This property MUST be used and contain the media type
     /// `application/vnd.oci.artifact.manifest.v1+json`.
@@ -56,7 +57,10 @@ pub struct ArtifactManifest {
     /// manifest. This OPTIONAL property MUST use the annotation rules.
     /// See Pre-Defined Annotation Keys. Annotations MAY be used to filter
     /// the response from the referrers API.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
     #[getset(get_mut = "pub", get = "pub", set = "pub")]
     #[builder(default)]
     annotations: Option<HashMap<String, String>>,
@@ -67,8 +71,8 @@ impl ArtifactManifest {
     ///
     /// # Errors
     ///
-    /// - [OciSpecError::Io] if the file does not exist
-    /// - [OciSpecError::SerDe] if the image manifest cannot be deserialized.
+    /// - [OciSpecError::FileIo] if the file does not exist
+    /// - [OciSpecError::FileSerDe] if the image manifest cannot be deserialized.
     ///
     /// # Example
     ///
@@ -105,7 +109,7 @@ impl ArtifactManifest {
     ///
     /// # Errors
     ///
-    /// - [OciSpecError::SerDe](crate::OciSpecError::SerDe) if the image manifest cannot be serialized.
+    /// - [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if the image manifest cannot be serialized.
     ///
     /// # Example
     ///
@@ -124,7 +128,7 @@ impl ArtifactManifest {
     ///
     /// # Errors
     ///
-    /// - [OciSpecError::SerDe](crate::OciSpecError::SerDe) if the image manifest cannot be serialized.
+    /// - [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if the image manifest cannot be serialized.
     ///
     /// # Example
     ///
@@ -213,6 +217,24 @@ impl ArtifactManifest {
     }
 }
 
+impl TryFrom<serde_json::Value> for ArtifactManifest {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a JSON value into an artifact manifest.
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        crate::from_value(value)
+    }
+}
+
+impl TryFrom<ArtifactManifest> for serde_json::Value {
+    type Error = OciSpecError;
+
+    /// Attempts to convert an artifact manifest into a JSON value.
+    fn try_from(manifest: ArtifactManifest) -> Result<Self> {
+        crate::to_value(&manifest)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,18 +1,17 @@
-use super::{Arch, Os};
+use super::{Arch, Digest, Os, PlatformBuilder};
 use crate::{
-    error::{OciSpecError, Result},
-    from_file, from_reader, to_file, to_string, to_writer,
+    error::{FindingCode, OciSpecError, Result, ValidationError, ValidationErrorKind},
+    from_file, from_reader, to_file, to_string, to_writer, ParseOptions,
 };
 use derive_builder::Builder;
 use getset::{CopyGetters, Getters, MutGetters, Setters};
 use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
-#[cfg(test)]
-use std::collections::BTreeMap;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::Display,
     io::{Read, Write},
     path::Path,
+    str::FromStr,
 };
 
 /// In theory, this key is not standard.  In practice, it's used by at least the
@@ -43,6 +42,7 @@ pub const LABEL_VERSION: &str = "version";
 /// basic information about the image such as date created, author, as
 /// well as execution/runtime configuration like its entrypoint, default
 /// arguments, networking, and volumes.
+#[non_exhaustive]
 pub struct ImageConfiguration {
     /// An combined date and time at which the image was created,
     /// formatted as defined by [RFC 3339, section 5.6.](https://tools.ietf.org/html/rfc3339#section-5.6)
@@ -102,9 +102,9 @@ pub struct ImageConfiguration {
 impl ImageConfiguration {
     /// Attempts to load an image configuration from a file.
     /// # Errors
-    /// This function will return an [OciSpecError::Io](crate::OciSpecError::Io)
+    /// This function will return an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
     /// if the file does not exist or an
-    /// [OciSpecError::SerDe](crate::OciSpecError::SerDe) if the image configuration
+    /// [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if the image configuration
     /// cannot be deserialized.
     /// # Example
     /// ``` no_run
@@ -132,10 +132,113 @@ impl ImageConfiguration {
         from_reader(reader)
     }
 
+    /// Attempts to load an image configuration from a file, enforcing the limits of `options`.
+    /// # Errors
+    /// This function will return the errors of [ImageConfiguration::from_file] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if a limit is exceeded.
+    pub fn from_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ParseOptions,
+    ) -> Result<ImageConfiguration> {
+        crate::parse::from_file_with_options(path, options)
+    }
+
+    /// Attempts to load an image configuration from a stream, enforcing the limits of `options`.
+    /// # Errors
+    /// This function will return the errors of [ImageConfiguration::from_reader] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if a limit is exceeded.
+    pub fn from_reader_with_options<R: Read>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> Result<ImageConfiguration> {
+        crate::parse::from_reader_with_options(reader, options)
+    }
+
+    /// Attempts to load an image configuration from a file and validates it,
+    /// see [ImageConfiguration::validate].
+    /// # Errors
+    /// This function will return the errors of [ImageConfiguration::from_file] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if the configuration
+    /// does not pass validation.
+    pub fn from_file_validated<P: AsRef<Path>>(path: P) -> Result<ImageConfiguration> {
+        let config = Self::from_file(path)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Attempts to load an image configuration from a stream and validates
+    /// it, see [ImageConfiguration::validate].
+    /// # Errors
+    /// This function will return the errors of [ImageConfiguration::from_reader] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if the configuration
+    /// does not pass validation.
+    pub fn from_reader_validated<R: Read>(reader: R) -> Result<ImageConfiguration> {
+        let config = Self::from_reader(reader)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Validates the configuration, which deserialization does not do: the
+    /// platform fields like for a [Platform](super::Platform), that the
+    /// rootfs type is `layers` and that the DiffIDs are digests.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation](crate::OciSpecError::Validation)
+    /// for the first value which does not pass validation.
+    /// # Example
+    /// ```
+    /// use oci_spec::image::ImageConfiguration;
+    ///
+    /// let json = r#"{
+    ///     "architecture": "amd64",
+    ///     "os": "linux",
+    ///     "rootfs": { "type": "layers", "diff_ids": ["not-a-digest"] }
+    /// }"#;
+    /// assert!(ImageConfiguration::from_reader(json.as_bytes()).is_ok());
+    /// assert!(ImageConfiguration::from_reader_validated(json.as_bytes()).is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let mut platform = PlatformBuilder::default()
+            .architecture(self.architecture.clone())
+            .os(self.os.clone());
+        if let Some(os_features) = &self.os_features {
+            platform = platform.os_features(os_features.clone());
+        }
+        if let Some(variant) = &self.variant {
+            platform = platform.variant(variant.clone());
+        }
+        platform.build()?;
+
+        if self.rootfs.typ != "layers" {
+            return Err(ValidationError::new(
+                ValidationErrorKind::InvalidFormat,
+                "rootfs.type",
+                "rootfs type must be layers",
+            )
+            .with_code(FindingCode::RootfsTypeInvalid)
+            .with_expected("layers")
+            .with_actual(self.rootfs.typ.as_str())
+            .into());
+        }
+
+        for (i, diff_id) in self.rootfs.diff_ids.iter().enumerate() {
+            if Digest::from_str(diff_id).is_err() {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::InvalidFormat,
+                    format!("rootfs.diff_ids[{i}]"),
+                    "DiffID must be a digest",
+                )
+                .with_code(FindingCode::DiffIdInvalid)
+                .with_actual(diff_id.as_str())
+                .into());
+            }
+        }
+        Ok(())
+    }
+
     /// Attempts to write an image configuration to a file as JSON. If the file already exists, it
     /// will be overwritten.
     /// # Errors
-    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// This function will return an [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
     /// the image configuration cannot be serialized.
     /// # Example
     /// ``` no_run
@@ -151,7 +254,7 @@ impl ImageConfiguration {
     /// Attempts to write an image configuration to a file as pretty printed JSON. If the file
     /// already exists, it will be overwritten.
     /// # Errors
-    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// This function will return an [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
     /// the image configuration cannot be serialized.
     /// # Example
     /// ``` no_run
@@ -226,6 +329,37 @@ impl ImageConfiguration {
         to_string(&self, true)
     }
 
+    /// Attempts to write the image configuration to a string in the style of Go's
+    /// `json.Marshal` of the equivalent Go struct: in Go field order,
+    /// without zero valued `omitempty` fields and with Go string escaping.
+    /// Only the top level objects and the nested structs known to this
+    /// crate are reordered, other objects are written with sorted keys.
+    /// The output is not checked against the Go implementations and is
+    /// not guaranteed to be byte-identical to theirs.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image configuration cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageConfiguration;
+    ///
+    /// let item = ImageConfiguration::from_file("config.json").unwrap();
+    /// let json_str = item.to_string_go().unwrap();
+    /// ```
+    pub fn to_string_go(&self) -> Result<String> {
+        crate::go_compat::to_string_go(self, &crate::go_compat::image::IMAGE)
+    }
+
+    /// Attempts to write the image configuration to a stream in the style of Go's
+    /// `json.Marshal`, see [Self::to_string_go].
+    /// # Errors
+    /// This function will return an [OciSpecError::Io](crate::OciSpecError::Io) or
+    /// [OciSpecError::SerDe](crate::OciSpecError::SerDe) if the image configuration cannot be
+    /// written.
+    pub fn to_writer_go<W: Write>(&self, writer: &mut W) -> Result<()> {
+        crate::go_compat::to_writer_go(self, writer, &crate::go_compat::image::IMAGE)
+    }
+
     /// Extract the labels of the configuration, if present.
     pub fn labels_of_config(&self) -> Option<&HashMap<String, String>> {
         self.config().as_ref().and_then(|c| c.labels().as_ref())
@@ -250,6 +384,92 @@ impl ImageConfiguration {
         self.labels_of_config()
             .and_then(|v| v.get(key).map(|s| s.as_str()))
     }
+
+    /// The reference of the image this image is based on, parsed from the
+    /// `org.opencontainers.image.base.name` label of the configuration.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if the label is not a valid reference.
+    #[cfg(feature = "distribution")]
+    pub fn base_image_name(&self) -> Result<Option<crate::distribution::Reference>> {
+        super::annotations::base_image_name(self.labels_of_config())
+    }
+
+    /// The manifest digest of the image this image is based on, parsed from
+    /// the `org.opencontainers.image.base.digest` label of the configuration.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if the label is not a valid digest.
+    pub fn base_image_digest(&self) -> Result<Option<super::Digest>> {
+        super::annotations::base_image_digest(self.labels_of_config())
+    }
+
+    /// Record the image this image is based on in the
+    /// `org.opencontainers.image.base.name` and `base.digest` labels of the
+    /// configuration.
+    #[cfg(feature = "distribution")]
+    pub fn set_base_image(
+        &mut self,
+        name: &crate::distribution::Reference,
+        digest: &super::Digest,
+    ) {
+        let config = self.config.get_or_insert_with(Config::default);
+        super::annotations::set_base_image(
+            config.labels.get_or_insert_with(HashMap::new),
+            name,
+            digest,
+        );
+    }
+
+    /// Convert the `ImageConfiguration` back into a [`ImageConfigurationBuilder`], so that it can be modified and
+    /// validated again when calling `build()`.
+    pub fn into_builder(self) -> ImageConfigurationBuilder {
+        let ImageConfiguration {
+            created,
+            author,
+            architecture,
+            os,
+            os_version,
+            os_features,
+            variant,
+            config,
+            rootfs,
+            history,
+        } = self;
+        ImageConfigurationBuilder {
+            created: Some(created),
+            author: Some(author),
+            architecture: Some(architecture),
+            os: Some(os),
+            os_version: Some(os_version),
+            os_features: Some(os_features),
+            variant: Some(variant),
+            config: Some(config),
+            rootfs: Some(rootfs),
+            history: Some(history),
+        }
+    }
+
+    /// Create a [`ImageConfigurationBuilder`] pre-populated with a copy of this image configuration.
+    pub fn to_builder(&self) -> ImageConfigurationBuilder {
+        self.clone().into_builder()
+    }
+
+    /// Consumes the image configuration and returns its rootfs.
+    pub fn into_rootfs(self) -> RootFs {
+        self.rootfs
+    }
+
+    /// Take the execution parameters out of the image configuration, leaving `None` in their
+    /// place.
+    pub fn take_config(&mut self) -> Option<Config> {
+        self.config.take()
+    }
+
+    /// Take the layer history out of the image configuration, leaving `None` in its place.
+    pub fn take_history(&mut self) -> Option<Vec<History>> {
+        self.history.take()
+    }
 }
 
 /// This ToString trait is automatically implemented for any type which implements the Display trait.
@@ -291,6 +511,7 @@ impl Display for ImageConfiguration {
 #[getset(get = "pub", set = "pub")]
 /// The execution parameters which SHOULD be used as a base when
 /// running a container using the image.
+#[non_exhaustive]
 pub struct Config {
     /// The username or UID which is a platform-specific
     /// structure that allows specific control over which
@@ -351,7 +572,10 @@ pub struct Config {
     working_dir: Option<String>,
     /// The field contains arbitrary metadata for the container.
     /// This property MUST use the annotation rules.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
     #[getset(get_mut = "pub", get = "pub", set = "pub")]
     labels: Option<HashMap<String, String>>,
     /// The field contains the system call signal that will be
@@ -377,11 +601,8 @@ fn deserialize_as_vec<'de, D>(deserializer: D) -> std::result::Result<Option<Vec
 where
     D: Deserializer<'de>,
 {
-    // ensure stable order of keys in json document for comparison between expected and actual
-    #[cfg(test)]
+    // ensure a stable order of the resulting values
     let opt = Option::<BTreeMap<String, GoMapSerde>>::deserialize(deserializer)?;
-    #[cfg(not(test))]
-    let opt = Option::<HashMap<String, GoMapSerde>>::deserialize(deserializer)?;
 
     if let Some(data) = opt {
         let vec: Vec<String> = data.keys().cloned().collect();
@@ -400,11 +621,8 @@ where
 {
     match target {
         Some(values) => {
-            // ensure stable order of keys in json document for comparison between expected and actual
-            #[cfg(test)]
+            // ensure stable order of keys in json document
             let map: BTreeMap<_, _> = values.iter().map(|v| (v, GoMapSerde {})).collect();
-            #[cfg(not(test))]
-            let map: HashMap<_, _> = values.iter().map(|v| (v, GoMapSerde {})).collect();
 
             let mut map_ser = serializer.serialize_map(Some(map.len()))?;
             for (key, value) in map {
@@ -427,6 +645,7 @@ where
 )]
 #[getset(get = "pub", set = "pub")]
 /// RootFs references the layer content addresses used by the image.
+#[non_exhaustive]
 pub struct RootFs {
     /// MUST be set to layers.
     #[serde(rename = "type")]
@@ -466,6 +685,7 @@ impl Default for RootFs {
     build_fn(error = "OciSpecError")
 )]
 /// Describes the history of a layer.
+#[non_exhaustive]
 pub struct History {
     /// A combined date and time at which the layer was created,
     /// formatted as defined by [RFC 3339, section 5.6.](https://tools.ietf.org/html/rfc3339#section-5.6).
@@ -492,6 +712,24 @@ pub struct History {
     empty_layer: Option<bool>,
 }
 
+impl TryFrom<serde_json::Value> for ImageConfiguration {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a JSON value into an image configuration.
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        crate::from_value(value)
+    }
+}
+
+impl TryFrom<ImageConfiguration> for serde_json::Value {
+    type Error = OciSpecError;
+
+    /// Attempts to convert an image configuration into a JSON value.
+    fn try_from(config: ImageConfiguration) -> Result<Self> {
+        crate::to_value(&config)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs, path::PathBuf};
@@ -712,4 +950,80 @@ mod tests {
 
         assert!(config.history().is_none());
     }
+
+    #[cfg(feature = "distribution")]
+    #[test]
+    fn base_image_labels() -> Result<()> {
+        use crate::image::{ANNOTATION_BASE_IMAGE_DIGEST, ANNOTATION_BASE_IMAGE_NAME};
+
+        let mut config = ImageConfiguration::default();
+        assert_eq!(config.base_image_name()?, None);
+        assert_eq!(config.base_image_digest()?, None);
+
+        let name = "registry.example.com/base/debian:12".parse().unwrap();
+        let digest: super::super::Digest =
+            "sha256:beefdbd8a1da6d2915566fde36db9db0b524eb737fc57cd1367effd16dc0d06d".parse()?;
+        config.set_base_image(&name, &digest);
+        assert_eq!(
+            config.get_config_annotation(ANNOTATION_BASE_IMAGE_NAME),
+            Some("registry.example.com/base/debian:12")
+        );
+        assert_eq!(config.base_image_name()?, Some(name));
+        assert_eq!(config.base_image_digest()?, Some(digest));
+
+        let labels = config.config.as_mut().unwrap().labels.as_mut().unwrap();
+        labels.insert(
+            ANNOTATION_BASE_IMAGE_NAME.to_string(),
+            "Not A Ref".to_string(),
+        );
+        labels.insert(
+            ANNOTATION_BASE_IMAGE_DIGEST.to_string(),
+            "sha256".to_string(),
+        );
+        assert!(config.base_image_name().is_err());
+        assert!(config.base_image_digest().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn validate_deserialized() {
+        let json = r#"{
+            "architecture": "arm64",
+            "os": "linux",
+            "variant": "v8",
+            "rootfs": {
+                "type": "layers",
+                "diff_ids": ["sha256:5b0bcabd1ed22e9fb1310cf6c2dec7cdef19f0ad69efa1f392e94a4333501270"]
+            }
+        }"#;
+        let config = ImageConfiguration::from_reader_validated(json.as_bytes()).unwrap();
+
+        let error = |config: &ImageConfiguration| match config.validate() {
+            Err(OciSpecError::Validation(err)) => (err.path().to_string(), err.code()),
+            res => panic!("expected a validation error, got {res:?}"),
+        };
+        let mut invalid = config.clone();
+        invalid.set_variant(Some("v5".to_string()));
+        assert_eq!(
+            error(&invalid),
+            ("variant".to_string(), FindingCode::PlatformVariantInvalid)
+        );
+
+        let mut invalid = config.clone();
+        invalid.rootfs_mut().set_typ("tar".to_string());
+        assert_eq!(
+            error(&invalid),
+            ("rootfs.type".to_string(), FindingCode::RootfsTypeInvalid)
+        );
+
+        let mut invalid = config;
+        invalid
+            .rootfs_mut()
+            .diff_ids_mut()
+            .push("sha256".to_string());
+        assert_eq!(
+            error(&invalid),
+            ("rootfs.diff_ids[1]".to_string(), FindingCode::DiffIdInvalid)
+        );
+    }
 }
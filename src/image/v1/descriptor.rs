@@ -0,0 +1,1048 @@
+use super::{
+    Arch, Digest, MediaType, Os, ANNOTATION_CREATED, ANNOTATION_REF_NAME, ANNOTATION_TITLE,
+};
+use crate::{
+    annotations::validate_annotations,
+    error::{with_parent, FindingCode, OciSpecError, ValidationError, ValidationErrorKind},
+};
+use derive_builder::Builder;
+use getset::{CopyGetters, Getters, Setters};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+#[derive(
+    Builder, Clone, CopyGetters, Debug, Deserialize, Eq, Getters, Setters, PartialEq, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[builder(
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(validate = "Self::validate", error = "OciSpecError")
+)]
+/// A Content Descriptor (or simply Descriptor) describes the disposition of
+/// the targeted content. It includes the type of the content, a content
+/// identifier (digest), and the byte-size of the raw content.
+/// Descriptors SHOULD be embedded in other formats to securely reference
+/// external content.
+#[non_exhaustive]
+pub struct Descriptor {
+    /// This REQUIRED property contains the media type of the referenced
+    /// content. Values MUST comply with RFC 6838, including the naming
+    /// requirements in its section 4.2.
+    #[getset(get = "pub", set = "pub")]
+    media_type: MediaType,
+    /// This REQUIRED property is the digest of the targeted content,
+    /// conforming to the requirements outlined in Digests. Retrieved
+    /// content SHOULD be verified against this digest when consumed via
+    /// untrusted sources.
+    #[getset(get = "pub", set = "pub")]
+    digest: Digest,
+    /// This REQUIRED property specifies the size, in bytes, of the raw
+    /// content. This property exists so that a client will have an
+    /// expected size for the content before processing. If the
+    /// length of the retrieved content does not match the specified
+    /// length, the content SHOULD NOT be trusted.
+    ///
+    /// The spec defines the size as int64. The size is kept as serialized,
+    /// so that descriptors of non-conformant producers with negative sizes
+    /// can be read, see [Descriptor::size] and [Descriptor::validate].
+    size: i128,
+    /// This OPTIONAL property specifies a list of URIs from which this
+    /// object MAY be downloaded. Each entry MUST conform to [RFC 3986](https://tools.ietf.org/html/rfc3986).
+    /// Entries SHOULD use the http and https schemes, as defined
+    /// in [RFC 7230](https://tools.ietf.org/html/rfc7230#section-2.7).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    urls: Option<Vec<String>>,
+    /// This OPTIONAL property contains arbitrary metadata for this
+    /// descriptor. This OPTIONAL property MUST use the annotation
+    /// rules.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    annotations: Option<HashMap<String, String>>,
+    /// This OPTIONAL property describes the minimum runtime requirements of
+    /// the image. This property SHOULD be present if its target is
+    /// platform-specific.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    platform: Option<Platform>,
+    /// This OPTIONAL property contains the type of an artifact when the descriptor points to an
+    /// artifact. This is the value of the config descriptor mediaType when the descriptor
+    /// references an image manifest. If defined, the value MUST comply with RFC 6838, including
+    /// the naming requirements in its section 4.2, and MAY be registered with IANA.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    artifact_type: Option<MediaType>,
+    /// This OPTIONAL property contains an embedded representation of the referenced content.
+    /// Values MUST conform to the Base 64 encoding, as defined in RFC 4648. The decoded data MUST
+    /// be identical to the referenced content and SHOULD be verified against the digest and size
+    /// fields by content consumers. See Embedded Content for when this is appropriate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    data: Option<String>,
+}
+
+#[derive(
+    Builder, Clone, Debug, Default, Deserialize, Eq, Getters, Setters, PartialEq, Serialize,
+)]
+#[builder(
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(validate = "Self::validate", error = "OciSpecError")
+)]
+#[getset(get = "pub", set = "pub")]
+/// Describes the minimum runtime requirements of the image.
+#[non_exhaustive]
+pub struct Platform {
+    /// This REQUIRED property specifies the CPU architecture.
+    /// Image indexes SHOULD use, and implementations SHOULD understand,
+    /// values listed in the Go Language document for GOARCH.
+    architecture: Arch,
+    /// This REQUIRED property specifies the operating system.
+    /// Image indexes SHOULD use, and implementations SHOULD understand,
+    /// values listed in the Go Language document for GOOS.
+    os: Os,
+    /// This OPTIONAL property specifies the version of the operating system
+    /// targeted by the referenced blob. Implementations MAY refuse to use
+    /// manifests where os.version is not known to work with the host OS
+    /// version. Valid values are implementation-defined. e.g.
+    /// 10.0.14393.1066 on windows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    os_version: Option<String>,
+    /// This OPTIONAL property specifies an array of strings, each
+    /// specifying a mandatory OS feature. When os is windows, image
+    /// indexes SHOULD use, and implementations SHOULD understand
+    /// the following values:
+    /// - win32k: image requires win32k.sys on the host (Note: win32k.sys is
+    ///   missing on Nano Server)
+    ///
+    /// When os is not windows, values are implementation-defined and SHOULD
+    /// be submitted to this specification for standardization.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    os_features: Option<Vec<String>>,
+    /// This OPTIONAL property specifies the variant of the CPU.
+    /// Image indexes SHOULD use, and implementations SHOULD understand,
+    /// variant values listed in the [Platform Variants]
+    /// (<https://github.com/opencontainers/image-spec/blob/main/image-index.md#platform-variants>)
+    /// table.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    variant: Option<String>,
+    /// This property is RESERVED for future versions of the specification.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    features: Option<Vec<String>>,
+}
+
+/// The values of `os.features` defined by the image spec for windows.
+const WINDOWS_OS_FEATURES: &[&str] = &["win32k"];
+
+impl Platform {
+    /// Run the validation of [PlatformBuilder], e.g. for a deserialized
+    /// value.
+    pub(crate) fn validate(&self) -> Result<(), OciSpecError> {
+        PlatformBuilder {
+            architecture: Some(self.architecture.clone()),
+            os: Some(self.os.clone()),
+            os_features: Some(self.os_features.clone()),
+            variant: Some(self.variant.clone()),
+            ..Default::default()
+        }
+        .validate()
+    }
+}
+
+impl PlatformBuilder {
+    fn validate(&self) -> Result<(), OciSpecError> {
+        if let Some(Os::Other(os)) = &self.os {
+            if os.is_empty() {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Missing,
+                    "os",
+                    "operating system must not be empty",
+                )
+                .with_code(FindingCode::PlatformOsMissing)
+                .into());
+            }
+        }
+
+        if let Some(Arch::Other(arch)) = &self.architecture {
+            if arch.is_empty() {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Missing,
+                    "architecture",
+                    "architecture must not be empty",
+                )
+                .with_code(FindingCode::PlatformArchitectureMissing)
+                .into());
+            }
+        }
+
+        if let (Some(arch), Some(Some(variant))) = (&self.architecture, &self.variant) {
+            if !is_valid_variant(arch, variant) {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Conflict,
+                    "variant",
+                    format!("{variant} is not a valid variant of {arch}"),
+                )
+                .with_code(FindingCode::PlatformVariantInvalid)
+                .into());
+            }
+        }
+
+        if let (Some(Os::Windows), Some(Some(features))) = (&self.os, &self.os_features) {
+            if let Some(feature) = features
+                .iter()
+                .find(|feature| !WINDOWS_OS_FEATURES.contains(&feature.as_str()))
+            {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::InvalidFormat,
+                    "os.features",
+                    "unknown windows os feature",
+                )
+                .with_code(FindingCode::PlatformOsFeatureUnknown)
+                .with_expected(WINDOWS_OS_FEATURES.join(", "))
+                .with_actual(feature.as_str())
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns true if `variant` is a known variant of `arch`, as listed in the
+/// platform variants table of the image spec and the Go toolchain settings.
+fn is_valid_variant(arch: &Arch, variant: &str) -> bool {
+    let versioned = |major: &str| {
+        variant.strip_prefix(major).is_some_and(|rest| {
+            rest.is_empty()
+                || rest
+                    .strip_prefix('.')
+                    .is_some_and(|minor| minor.parse::<u8>().is_ok())
+        })
+    };
+    match arch {
+        Arch::ARM => matches!(variant, "v5" | "v6" | "v7" | "v8"),
+        Arch::ARM64 => versioned("v8") || versioned("v9"),
+        Arch::Amd64 => matches!(variant, "v1" | "v2" | "v3" | "v4"),
+        Arch::PowerPC64le => matches!(variant, "power8" | "power9" | "power10"),
+        Arch::RISCV64 => matches!(variant, "rva20u64" | "rva22u64"),
+        Arch::Other(_) => true,
+        _ => false,
+    }
+}
+
+impl FromStr for Platform {
+    type Err = OciSpecError;
+
+    /// Parse a platform in the `os/arch[/variant]` format used by registries
+    /// and container tooling, e.g. `linux/arm64/v8`. Like architectures,
+    /// operating systems without a variant of their own are kept as
+    /// [Os::Other], e.g. `wasip1/wasm`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |message: &str| {
+            ValidationError::new(ValidationErrorKind::InvalidFormat, "platform", message)
+                .with_code(FindingCode::PlatformFormatInvalid)
+                .with_expected("os/arch[/variant]")
+                .with_actual(s)
+        };
+
+        let parts: Vec<&str> = s.split('/').collect();
+        let (os, arch, variant) = match parts[..] {
+            [os, arch] => (os, arch, None),
+            [os, arch, variant] => (os, arch, Some(variant)),
+            _ => return Err(invalid("invalid number of components").into()),
+        };
+        if parts.iter().any(|part| part.is_empty()) {
+            return Err(invalid("empty component").into());
+        }
+
+        let platform = Platform {
+            architecture: Arch::from(arch),
+            os: Os::from(os),
+            variant: variant.map(String::from),
+            ..Default::default()
+        };
+        platform.validate()?;
+        Ok(platform)
+    }
+}
+
+impl Platform {
+    /// Derive the platform of binaries built for the Rust target `triple`,
+    /// e.g. `aarch64-unknown-linux-gnu` or `armv7-unknown-linux-gnueabihf`.
+    ///
+    /// The variant of 32 bit ARM targets is inferred from the architecture
+    /// version of the triple, `arm-*` targets are ARMv6.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] with the code
+    /// [FindingCode::PlatformTargetUnknown] if the architecture or operating
+    /// system of the triple has no OCI name.
+    /// # Example
+    /// ```
+    /// use oci_spec::image::Platform;
+    ///
+    /// let platform = Platform::from_target_triple("armv7-unknown-linux-gnueabihf").unwrap();
+    /// assert_eq!(platform.to_string(), "linux/arm/v7");
+    /// ```
+    pub fn from_target_triple(triple: &str) -> Result<Self, OciSpecError> {
+        let unknown = |message: &str| {
+            OciSpecError::from(
+                ValidationError::new(ValidationErrorKind::InvalidFormat, "target", message)
+                    .with_code(FindingCode::PlatformTargetUnknown)
+                    .with_actual(triple),
+            )
+        };
+
+        let mut components = triple.split('-');
+        let arch = components.next().unwrap_or_default();
+        let rest: Vec<&str> = components.collect();
+        let has = |name: &str| rest.iter().any(|c| c.starts_with(name));
+        let os = if has("android") {
+            Os::Android
+        } else if has("linux") {
+            Os::Linux
+        } else if has("windows") {
+            Os::Windows
+        } else if has("ios") {
+            Os::iOS
+        } else if has("darwin") {
+            Os::Darwin
+        } else if rest.iter().any(|c| matches!(*c, "wasip1" | "wasi")) {
+            // `wasi` is the former name of the `wasip1` target.
+            Os::Other("wasip1".to_string())
+        } else if has("wasi") {
+            return Err(unknown("only WASI preview 1 has an OCI name"));
+        } else if let Some(os) = rest.iter().find_map(|c| {
+            [
+                ("aix", Os::AIX),
+                ("dragonfly", Os::DragonFlyBSD),
+                ("freebsd", Os::FreeBSD),
+                ("hurd", Os::Hurd),
+                ("illumos", Os::Illumos),
+                ("netbsd", Os::NetBSD),
+                ("openbsd", Os::OpenBSD),
+                ("solaris", Os::Solaris),
+            ]
+            .into_iter()
+            .find_map(|(name, os)| (*c == name).then_some(os))
+        }) {
+            os
+        } else {
+            return Err(unknown("unknown operating system"));
+        };
+
+        let (architecture, variant) = match arch {
+            "x86_64" => (Arch::Amd64, None),
+            "i386" | "i586" | "i686" => (Arch::i386, None),
+            "aarch64" | "arm64" | "arm64e" => (Arch::ARM64, None),
+            "aarch64_be" => (Arch::ARM64be, None),
+            "armeb" | "armebv7r" => (Arch::ARMbe, None),
+            "arm" => (Arch::ARM, Some("v6")),
+            arch if arch.starts_with("armv") || arch.starts_with("thumbv") => {
+                let version = arch.trim_start_matches("arm").trim_start_matches("thumb");
+                let variant = ["v5", "v6", "v7", "v8"]
+                    .into_iter()
+                    .find(|v| version.starts_with(v))
+                    .ok_or_else(|| unknown("unknown ARM architecture version"))?;
+                (Arch::ARM, Some(variant))
+            }
+            "loongarch64" => (Arch::LoongArch64, None),
+            "mips" => (Arch::Mips, None),
+            "mipsel" => (Arch::Mipsle, None),
+            "mips64" => (Arch::Mips64, None),
+            "mips64el" => (Arch::Mips64le, None),
+            "powerpc" => (Arch::PowerPC, None),
+            "powerpc64" => (Arch::PowerPC64, None),
+            "powerpc64le" => (Arch::PowerPC64le, None),
+            arch if arch.starts_with("riscv64") => (Arch::RISCV64, None),
+            arch if arch.starts_with("riscv32") => (Arch::RISCV, None),
+            "s390x" => (Arch::s390x, None),
+            "sparc" => (Arch::SPARC, None),
+            "sparc64" | "sparcv9" => (Arch::SPARC64, None),
+            "wasm32" => (Arch::Wasm, None),
+            _ => return Err(unknown("unknown architecture")),
+        };
+
+        Ok(Platform {
+            architecture,
+            os,
+            variant: variant.map(String::from),
+            ..Default::default()
+        })
+    }
+
+    /// Derive the platform of binaries built by Go for the `GOOS` and
+    /// `GOARCH` pair, with the value of `GOARM` for 32 bit ARM, e.g. `7` or
+    /// `6,softfloat`. `GOARM` defaults to `7` like for cross-compilation.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] with the code
+    /// [FindingCode::PlatformTargetUnknown] if `goos` or `goarch` are empty or
+    /// `goarm` is invalid.
+    /// # Example
+    /// ```
+    /// use oci_spec::image::Platform;
+    ///
+    /// let platform = Platform::from_go("linux", "arm", Some("6")).unwrap();
+    /// assert_eq!(platform.to_string(), "linux/arm/v6");
+    /// ```
+    pub fn from_go(goos: &str, goarch: &str, goarm: Option<&str>) -> Result<Self, OciSpecError> {
+        let unknown = |message: &str, actual: &str| {
+            OciSpecError::from(
+                ValidationError::new(ValidationErrorKind::InvalidFormat, "target", message)
+                    .with_code(FindingCode::PlatformTargetUnknown)
+                    .with_actual(actual),
+            )
+        };
+        if goos.is_empty() || goarch.is_empty() {
+            return Err(unknown("GOOS and GOARCH must not be empty", ""));
+        }
+
+        let architecture = Arch::from(goarch);
+        let variant = match architecture {
+            Arch::ARM => {
+                let goarm = goarm.unwrap_or("7");
+                let version = goarm.split(',').next().unwrap_or_default();
+                if !matches!(version, "5" | "6" | "7") {
+                    return Err(unknown("GOARM must be 5, 6 or 7", goarm));
+                }
+                Some(format!("v{version}"))
+            }
+            _ => None,
+        };
+
+        Ok(Platform {
+            architecture,
+            os: Os::from(goos),
+            variant,
+            ..Default::default()
+        })
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.os, self.architecture)?;
+        if let Some(variant) = &self.variant {
+            write!(f, "/{variant}")?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_urls(urls: &[String]) -> Result<(), OciSpecError> {
+    urls.iter()
+        .enumerate()
+        .try_for_each(|(i, url)| validate_url(&format!("urls[{i}]"), url))
+}
+
+/// Check that `url` is an absolute http or https URL with a host, as the spec
+/// requires for the entries of `urls`.
+fn validate_url(path: &str, url: &str) -> Result<(), OciSpecError> {
+    let rest = ["http://", "https://"].iter().find_map(|scheme| {
+        url.get(..scheme.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+            .map(|_| &url[scheme.len()..])
+    });
+    let host = rest.map(|rest| rest.split(['/', '?', '#']).next().unwrap_or_default());
+    let valid = host.is_some_and(|host| !host.is_empty())
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control());
+    if !valid {
+        return Err(ValidationError::new(
+            ValidationErrorKind::InvalidFormat,
+            path,
+            "must be an absolute http or https URL",
+        )
+        .with_code(FindingCode::DescriptorUrlInvalid)
+        .with_actual(url)
+        .into());
+    }
+    Ok(())
+}
+
+/// Check that `size` is neither negative nor beyond the int64 range of the
+/// spec and return it as unsigned size.
+fn validate_size(size: i128) -> Result<u64, OciSpecError> {
+    let message = match size {
+        ..0 => "size is negative",
+        0..=MAX_SIZE => return Ok(size as u64),
+        _ => "size overflows int64",
+    };
+    Err(
+        ValidationError::new(ValidationErrorKind::OutOfRange, "size", message)
+            .with_code(FindingCode::DescriptorSizeInvalid)
+            .with_expected(format!("0-{MAX_SIZE}"))
+            .with_actual(size.to_string())
+            .into(),
+    )
+}
+
+/// The largest size of the int64 range of the spec.
+const MAX_SIZE: i128 = i64::MAX as i128;
+
+impl Descriptor {
+    /// Construct a new descriptor with the required fields.
+    pub fn new(media_type: MediaType, size: u64, digest: impl Into<Digest>) -> Self {
+        Self {
+            media_type,
+            size: size.into(),
+            digest: digest.into(),
+            urls: Default::default(),
+            annotations: Default::default(),
+            platform: Default::default(),
+            artifact_type: Default::default(),
+            data: Default::default(),
+        }
+    }
+
+    /// The size of the content in bytes.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] with the code
+    /// [FindingCode::DescriptorSizeInvalid] if the size is negative or beyond
+    /// the int64 range of the spec, which non-conformant producers write.
+    /// # Example
+    /// ```
+    /// use oci_spec::image::Descriptor;
+    ///
+    /// let json = r#"{
+    ///     "mediaType": "application/vnd.oci.image.layer.v1.tar",
+    ///     "digest": "sha256:44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a",
+    ///     "size": -1
+    /// }"#;
+    /// let descriptor: Descriptor = serde_json::from_str(json).unwrap();
+    /// assert_eq!(descriptor.raw_size(), -1);
+    /// assert!(descriptor.size().is_err());
+    /// ```
+    pub fn size(&self) -> Result<u64, OciSpecError> {
+        validate_size(self.size)
+    }
+
+    /// The size as serialized, which may be negative or beyond the int64
+    /// range of the spec.
+    pub fn raw_size(&self) -> i128 {
+        self.size
+    }
+
+    /// Set the size of the content in bytes.
+    pub fn set_size(&mut self, size: u64) -> &mut Self {
+        self.size = size.into();
+        self
+    }
+
+    /// Check the descriptor against the rules of the spec which
+    /// deserialization does not enforce: the size must neither be negative
+    /// nor beyond the int64 range, every entry of `urls` must be an absolute
+    /// http or https URL, annotation keys must neither be empty nor use the reserved
+    /// `org.opencontainers` namespace for keys the spec does not define and
+    /// the platform must pass the validation of [PlatformBuilder].
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] for the first
+    /// invalid value.
+    pub fn validate(&self) -> Result<(), OciSpecError> {
+        self.size()?;
+        validate_urls(self.urls.as_deref().unwrap_or_default())?;
+        if let Some(annotations) = &self.annotations {
+            validate_annotations(annotations)?;
+        }
+        if let Some(platform) = &self.platform {
+            platform.validate().map_err(with_parent("platform"))?;
+        }
+        Ok(())
+    }
+
+    /// Append `url` to the URLs the content may be downloaded from, e.g. a
+    /// mirror, unless it is already listed.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] if `url` is
+    /// not an absolute http or https URL.
+    /// # Example
+    /// ```
+    /// use oci_spec::image::{Descriptor, MediaType, Sha256Digest};
+    /// use std::str::FromStr;
+    ///
+    /// let digest = Sha256Digest::from_str(
+    ///     "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a",
+    /// )
+    /// .unwrap();
+    /// let mut descriptor = Descriptor::new(MediaType::ImageLayerGzip, 2, digest);
+    /// descriptor.add_url("https://mirror.example.com/layer").unwrap();
+    /// descriptor.add_url("https://mirror.example.com/layer").unwrap();
+    /// assert_eq!(descriptor.urls().as_ref().unwrap().len(), 1);
+    /// assert!(descriptor.add_url("ftp://mirror.example.com/layer").is_err());
+    /// ```
+    pub fn add_url(&mut self, url: impl Into<String>) -> Result<(), OciSpecError> {
+        let url = url.into();
+        validate_url("urls", &url)?;
+        let urls = self.urls.get_or_insert_with(Vec::new);
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+        Ok(())
+    }
+
+    /// Return a view of [`Self::digest()`] that has been parsed as a valid SHA-256.
+    pub fn as_digest_sha256(&self) -> Option<&str> {
+        match self.digest.algorithm() {
+            super::DigestAlgorithm::Sha256 => Some(self.digest.digest()),
+            _ => None,
+        }
+    }
+
+    /// Return the value of the annotation `key`, if present.
+    pub fn annotation(&self, key: &str) -> Option<&str> {
+        self.annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(key))
+            .map(String::as_str)
+    }
+
+    /// Set the annotation `key` to `value`, replacing any previous value.
+    pub fn set_annotation(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.annotations
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value.into());
+    }
+
+    /// The human-readable title of the content, stored in the
+    /// `org.opencontainers.image.title` annotation.
+    pub fn title(&self) -> Option<&str> {
+        self.annotation(ANNOTATION_TITLE)
+    }
+
+    /// Set the `org.opencontainers.image.title` annotation, e.g. the file name
+    /// of an artifact layer.
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.set_annotation(ANNOTATION_TITLE, title);
+    }
+
+    /// The creation date and time of the content, stored in the
+    /// `org.opencontainers.image.created` annotation as RFC 3339 string.
+    pub fn created(&self) -> Option<&str> {
+        self.annotation(ANNOTATION_CREATED)
+    }
+
+    /// Set the `org.opencontainers.image.created` annotation, which should be
+    /// formatted as defined by RFC 3339.
+    pub fn set_created(&mut self, created: impl Into<String>) {
+        self.set_annotation(ANNOTATION_CREATED, created);
+    }
+
+    /// The name of the reference for the content, stored in the
+    /// `org.opencontainers.image.ref.name` annotation, e.g. a tag in an image
+    /// layout.
+    pub fn ref_name(&self) -> Option<&str> {
+        self.annotation(ANNOTATION_REF_NAME)
+    }
+
+    /// Set the `org.opencontainers.image.ref.name` annotation.
+    pub fn set_ref_name(&mut self, ref_name: impl Into<String>) {
+        self.set_annotation(ANNOTATION_REF_NAME, ref_name);
+    }
+
+    /// Set the platform to the one of binaries built for the Rust target
+    /// `triple`, see [Platform::from_target_triple].
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] if the triple
+    /// has no OCI platform, the descriptor is not changed then.
+    pub fn set_platform_from_target_triple(&mut self, triple: &str) -> Result<(), OciSpecError> {
+        self.platform = Some(Platform::from_target_triple(triple)?);
+        Ok(())
+    }
+
+    /// Convert the `Descriptor` back into a [`DescriptorBuilder`], so that it can be modified and
+    /// validated again when calling `build()`.
+    pub fn into_builder(self) -> DescriptorBuilder {
+        let Descriptor {
+            media_type,
+            digest,
+            size,
+            urls,
+            annotations,
+            platform,
+            artifact_type,
+            data,
+        } = self;
+        DescriptorBuilder {
+            media_type: Some(media_type),
+            digest: Some(digest),
+            size: Some(size),
+            urls: Some(urls),
+            annotations: Some(annotations),
+            platform: Some(platform),
+            artifact_type: Some(artifact_type),
+            data: Some(data),
+        }
+    }
+
+    /// Create a [`DescriptorBuilder`] pre-populated with a copy of this descriptor.
+    pub fn to_builder(&self) -> DescriptorBuilder {
+        self.clone().into_builder()
+    }
+}
+
+impl DescriptorBuilder {
+    /// Append `url` to the URLs set on this builder unless it is already
+    /// listed. The URL is validated when calling `build()`.
+    pub fn add_url(self, url: impl Into<String>) -> Self {
+        self.extend_urls([url.into()])
+    }
+
+    /// Append the provided URLs to the URLs set on this builder, skipping
+    /// URLs which are already listed.
+    pub fn extend_urls<I: IntoIterator<Item = String>>(mut self, urls: I) -> Self {
+        let existing = self
+            .urls
+            .get_or_insert_with(|| Some(Vec::new()))
+            .get_or_insert_with(Vec::new);
+        for url in urls {
+            if !existing.contains(&url) {
+                existing.push(url);
+            }
+        }
+        self
+    }
+
+    fn validate(&self) -> Result<(), OciSpecError> {
+        if let Some(size) = self.size {
+            validate_size(size)?;
+        }
+        validate_urls(
+            self.urls
+                .as_ref()
+                .and_then(Option::as_deref)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Add a single annotation, keeping any annotations set before.
+    pub fn add_annotation(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extend_annotations([(key.into(), value.into())])
+    }
+
+    /// Extend the annotations with the provided key-value pairs, keeping any annotations set
+    /// before.
+    pub fn extend_annotations<I>(mut self, annotations: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        self.annotations
+            .get_or_insert_with(|| Some(HashMap::new()))
+            .get_or_insert_with(HashMap::new)
+            .extend(annotations);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let descriptor_str = r#"{
+            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+            "digest":"sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356",
+            "size":769,
+            "annotations":{"org.opencontainers.image.created": "2023-10-11T22:37:26Z"},
+            "artifactType":"application/spdx+json"}"#;
+        let descriptor: Descriptor = serde_json::from_str(descriptor_str).unwrap();
+        assert_eq!(descriptor.media_type, MediaType::ImageManifest);
+        assert_eq!(
+            descriptor.digest,
+            Digest::from_str(
+                "sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356"
+            )
+            .unwrap()
+        );
+        assert_eq!(descriptor.size, 769);
+        assert_eq!(
+            descriptor
+                .annotations
+                .unwrap()
+                .get("org.opencontainers.image.created"),
+            Some(&"2023-10-11T22:37:26Z".to_string())
+        );
+        assert_eq!(
+            descriptor.artifact_type.unwrap(),
+            MediaType::Other("application/spdx+json".to_string())
+        );
+    }
+
+    #[test]
+    fn test_size() {
+        let json = |size: &str| {
+            format!(
+                r#"{{"mediaType": "application/vnd.oci.image.layer.v1.tar",
+                "digest": "sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356",
+                "size": {size}}}"#
+            )
+        };
+
+        let descriptor: Descriptor = serde_json::from_str(&json("-5")).unwrap();
+        assert_eq!(descriptor.raw_size(), -5);
+        let err = descriptor.size().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("size: size is negative (expected 0-{}, got -5)", i64::MAX)
+        );
+        assert_eq!(
+            descriptor.validate().unwrap_err().to_string(),
+            err.to_string()
+        );
+        let descriptor: Descriptor = serde_json::from_str(&json(&u64::MAX.to_string())).unwrap();
+        assert_eq!(descriptor.raw_size(), u64::MAX.into());
+        assert!(descriptor.validate().is_err());
+        let json_out = serde_json::to_value(&descriptor).unwrap();
+        assert_eq!(json_out["size"], u64::MAX);
+
+        let descriptor: Descriptor = serde_json::from_str(&json("769")).unwrap();
+        assert_eq!(descriptor.size().unwrap(), 769);
+        let err = descriptor.to_builder().size(u64::MAX).build().unwrap_err();
+        assert!(err.to_string().starts_with("size: size overflows int64"));
+        assert!(descriptor.to_builder().size(-1).build().is_err());
+        let rebuilt = descriptor.to_builder().size(42u64).build().unwrap();
+        assert_eq!(rebuilt.size().unwrap(), 42);
+
+        // Sizes set without the builder are checked by size and validate.
+        let mut descriptor = descriptor;
+        descriptor.set_size(u64::MAX);
+        assert_eq!(descriptor.raw_size(), u64::MAX.into());
+        assert!(descriptor.size().is_err());
+        assert!(descriptor.validate().is_err());
+    }
+
+    #[test]
+    fn test_urls() {
+        let digest = Digest::from_str(
+            "sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356",
+        )
+        .unwrap();
+        let builder = || {
+            DescriptorBuilder::default()
+                .media_type(MediaType::ImageLayerGzip)
+                .digest(digest.clone())
+                .size(1u64)
+        };
+
+        let descriptor = builder()
+            .add_url("https://a.example.com/layer")
+            .extend_urls([
+                "HTTP://b.example.com:8080/layer?x=1".to_string(),
+                "https://a.example.com/layer".to_string(),
+            ])
+            .build()
+            .unwrap();
+        assert_eq!(descriptor.urls().as_ref().unwrap().len(), 2);
+
+        for invalid in [
+            "/relative/layer",
+            "ftp://a.example.com/layer",
+            "https:///layer",
+            "https://a.example.com/with space",
+        ] {
+            let err = builder().add_url(invalid).build().unwrap_err();
+            assert!(err.to_string().starts_with("urls[0]: "), "{invalid}");
+        }
+
+        let json = r#"{"mediaType": "application/vnd.oci.image.layer.v1.tar",
+            "digest": "sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356",
+            "size": 1, "urls": ["https://a.example.com/layer", "file:///layer"]}"#;
+        let descriptor: Descriptor = serde_json::from_str(json).unwrap();
+        let err = descriptor.validate().unwrap_err();
+        assert!(err.to_string().starts_with("urls[1]: "));
+    }
+
+    #[test]
+    fn test_malformed_digest() {
+        let descriptor_str = r#"{
+            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+            "digest":"../blah:this-is-an-attack",
+            "size":769,
+            "annotations":{"org.opencontainers.image.created": "2023-10-11T22:37:26Z"},
+            "artifactType":"application/spdx+json"}"#;
+        assert!(serde_json::from_str::<Descriptor>(descriptor_str).is_err());
+    }
+
+    #[test]
+    fn test_platform_from_str() {
+        let platform = Platform::from_str("linux/arm64/v8").unwrap();
+        assert_eq!(platform.os(), &Os::Linux);
+        assert_eq!(platform.architecture(), &Arch::ARM64);
+        assert_eq!(platform.variant().as_deref(), Some("v8"));
+        assert_eq!(platform.to_string(), "linux/arm64/v8");
+
+        for valid in [
+            "linux/amd64",
+            "windows/amd64",
+            "linux/arm/v7",
+            "linux/arm64/v8.2",
+            "wasip1/wasm",
+            "plan10/amd64",
+        ] {
+            assert_eq!(Platform::from_str(valid).unwrap().to_string(), valid);
+        }
+        let wasm = Platform::from_target_triple("wasm32-wasip1").unwrap();
+        assert_eq!(Platform::from_str(&wasm.to_string()).unwrap(), wasm);
+        for invalid in [
+            "linux",
+            "linux/",
+            "linux/amd64/v2/extra",
+            "linux/amd64/v8",
+            "linux/s390x/v1",
+            "linux/arm64/v8.",
+        ] {
+            assert!(Platform::from_str(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_platform_builder_validation() {
+        let builder = || {
+            PlatformBuilder::default()
+                .os(Os::Windows)
+                .architecture(Arch::Amd64)
+        };
+        assert!(builder().build().is_ok());
+        assert!(builder()
+            .os_features(vec!["win32k".to_string()])
+            .build()
+            .is_ok());
+
+        let path = |res: Result<Platform, OciSpecError>| match res {
+            Err(OciSpecError::Validation(err)) => err.path().to_string(),
+            res => panic!("expected a validation error, got {res:?}"),
+        };
+        assert_eq!(path(builder().os("").build()), "os");
+        assert_eq!(path(builder().architecture("").build()), "architecture");
+        assert_eq!(path(builder().variant("v8").build()), "variant");
+        assert_eq!(
+            path(builder().os_features(vec!["gpu".to_string()]).build()),
+            "os.features"
+        );
+        assert!(builder()
+            .os(Os::Linux)
+            .os_features(vec!["gpu".to_string()])
+            .build()
+            .is_ok());
+
+        let mut descriptor = Descriptor::new(
+            MediaType::ImageManifest,
+            7,
+            Digest::from_str(
+                "sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356",
+            )
+            .unwrap(),
+        );
+        let mut platform = builder().build().unwrap();
+        platform.set_variant(Some("v9".to_string()));
+        descriptor.set_platform(Some(platform));
+        let err = descriptor.validate().unwrap_err();
+        assert!(err.to_string().starts_with("platform.variant"));
+    }
+
+    #[test]
+    fn test_platform_from_target() {
+        for (triple, platform) in [
+            ("x86_64-unknown-linux-gnu", "linux/amd64"),
+            ("aarch64-unknown-linux-musl", "linux/arm64"),
+            ("arm-unknown-linux-gnueabihf", "linux/arm/v6"),
+            ("armv5te-unknown-linux-gnueabi", "linux/arm/v5"),
+            ("armv7-unknown-linux-gnueabihf", "linux/arm/v7"),
+            ("thumbv7neon-linux-androideabi", "android/arm/v7"),
+            ("powerpc64le-unknown-linux-gnu", "linux/ppc64le"),
+            ("riscv64gc-unknown-linux-gnu", "linux/riscv64"),
+            ("s390x-unknown-linux-gnu", "linux/s390x"),
+            ("x86_64-pc-windows-msvc", "windows/amd64"),
+            ("aarch64-apple-darwin", "darwin/arm64"),
+            ("x86_64-unknown-freebsd", "freebsd/amd64"),
+            ("wasm32-wasip1", "wasip1/wasm"),
+            ("wasm32-wasi", "wasip1/wasm"),
+            ("wasm32-wasip1-threads", "wasip1/wasm"),
+        ] {
+            let parsed = Platform::from_target_triple(triple).unwrap();
+            assert_eq!(parsed.to_string(), platform, "{triple}");
+            parsed.validate().unwrap();
+        }
+        for invalid in [
+            "",
+            "x86_64-unknown-none",
+            "avr-unknown-gnu-atmega328",
+            "wasm32-wasip2",
+        ] {
+            let err = Platform::from_target_triple(invalid).unwrap_err();
+            match err {
+                OciSpecError::Validation(err) => {
+                    assert_eq!(err.code(), FindingCode::PlatformTargetUnknown)
+                }
+                err => panic!("unexpected error {err}"),
+            }
+        }
+
+        let go = |goarch, goarm| Platform::from_go("linux", goarch, goarm).map(|p| p.to_string());
+        assert_eq!(go("amd64", None).unwrap(), "linux/amd64");
+        assert_eq!(go("arm", None).unwrap(), "linux/arm/v7");
+        assert_eq!(go("arm", Some("5,softfloat")).unwrap(), "linux/arm/v5");
+        assert!(go("arm", Some("8")).is_err());
+        assert!(go("", None).is_err());
+
+        let mut descriptor = Descriptor::new(
+            MediaType::ImageManifest,
+            7,
+            Digest::from_str(
+                "sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356",
+            )
+            .unwrap(),
+        );
+        assert!(descriptor
+            .set_platform_from_target_triple("x86_64-unknown-none")
+            .is_err());
+        assert!(descriptor.platform().is_none());
+        descriptor
+            .set_platform_from_target_triple("armv7-unknown-linux-musleabihf")
+            .unwrap();
+        assert_eq!(
+            descriptor.platform().as_ref().unwrap().variant().as_deref(),
+            Some("v7")
+        );
+        descriptor.validate().unwrap();
+    }
+
+    #[test]
+    fn test_annotation_accessors() {
+        let mut descriptor = Descriptor::new(
+            MediaType::ImageLayerGzip,
+            1024,
+            Digest::from_str(
+                "sha256:c2b8beca588702777e5f35dafdbeae9ec16c2bab802331f81cacd2a92f1d5356",
+            )
+            .unwrap(),
+        );
+        assert_eq!(descriptor.title(), None);
+
+        descriptor.set_title("layer.tar.gz");
+        descriptor.set_created("2023-10-11T22:37:26Z");
+        descriptor.set_ref_name("v1.0");
+        descriptor.set_ref_name("latest");
+        assert_eq!(descriptor.title(), Some("layer.tar.gz"));
+        assert_eq!(descriptor.created(), Some("2023-10-11T22:37:26Z"));
+        assert_eq!(descriptor.ref_name(), Some("latest"));
+        assert_eq!(
+            descriptor.annotation(ANNOTATION_REF_NAME),
+            descriptor.ref_name()
+        );
+        assert_eq!(descriptor.annotations().as_ref().unwrap().len(), 3);
+    }
+}
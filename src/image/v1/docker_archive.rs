@@ -0,0 +1,466 @@
+//! Conversion between `docker save` archives and OCI image layouts.
+
+use super::{
+    blob_path,
+    oci_layout::{create_temp_file, INDEX_FILE, LAYOUT_FILE},
+    tar::{TarEntryKind, TarReader, TarWriter},
+    Descriptor, Digest, DigestAlgorithm, ImageConfiguration, ImageIndex, ImageManifest,
+    ImageManifestBuilder, MediaType, OciLayout, OciLayoutBuilder, PlatformBuilder, Sha256Digest,
+};
+use crate::{
+    error::{file_io_error, oci_error, OciSpecError, Result},
+    sha256::Sha256,
+};
+use derive_builder::Builder;
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+/// The file of a `docker save` archive which lists the images.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// The file of a `docker save` archive which maps repositories and tags to
+/// the top layer of the images, used by old docker versions.
+const REPOSITORIES_FILE: &str = "repositories";
+
+#[derive(
+    Builder, Clone, Debug, Default, Deserialize, Eq, Getters, Setters, PartialEq, Serialize,
+)]
+#[serde(rename_all = "PascalCase")]
+#[builder(
+    default,
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(error = "OciSpecError")
+)]
+#[getset(get = "pub", set = "pub")]
+/// An image of the `manifest.json` file of a `docker save` archive.
+#[non_exhaustive]
+pub struct DockerArchiveManifest {
+    /// The path of the image configuration inside of the archive.
+    config: String,
+
+    /// The tags of the image, e.g. `docker.io/library/alpine:3.20`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repo_tags: Option<Vec<String>>,
+
+    /// The paths of the uncompressed or compressed layer tarballs inside of
+    /// the archive, starting with the base layer.
+    layers: Vec<String>,
+}
+
+/// Import the images of a `docker save` archive into the OCI image layout
+/// directory `layout`, which is created if it does not exist. Each image is
+/// added to `index.json` once per repo tag, using the tag as
+/// `org.opencontainers.image.ref.name`. The images are added to the index
+/// of an existing layout, replacing the manifests of the same tags like
+/// `docker load` does.
+///
+/// Returns the resulting image index.
+/// # Errors
+/// This function will return an [OciSpecError::Io] or
+/// [OciSpecError::FileIo] if the archive cannot be read or the layout cannot
+/// be written, or an [OciSpecError::Other] if `layout` is a non-empty
+/// directory which is not an image layout, or if the archive is missing its
+/// `manifest.json` or references files it does not contain.
+/// # Example
+/// ``` no_run
+/// use oci_spec::image::docker_archive_to_oci_layout;
+/// use std::fs::File;
+///
+/// let archive = File::open("image.tar").unwrap();
+/// let index = docker_archive_to_oci_layout(archive, "layout").unwrap();
+/// ```
+pub fn docker_archive_to_oci_layout<R: Read, P: AsRef<Path>>(
+    archive: R,
+    layout: P,
+) -> Result<ImageIndex> {
+    let layout = layout.as_ref();
+    let is_empty = match fs::read_dir(layout) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => true,
+        Err(err) => return Err(file_io_error(layout)(err)),
+    };
+    if !is_empty && !layout.join(LAYOUT_FILE).exists() {
+        return Err(oci_error(format!(
+            "{} is neither empty nor an image layout",
+            layout.display()
+        )));
+    }
+    let blobs = layout.join("blobs").join(DigestAlgorithm::Sha256.as_ref());
+    fs::create_dir_all(&blobs).map_err(file_io_error(&blobs))?;
+    let lock = OciLayout::lock_exclusive(layout)?;
+
+    let mut files: HashMap<String, Blob> = HashMap::new();
+    let mut links: HashMap<String, String> = HashMap::new();
+    let mut manifest = None;
+    let mut archive = TarReader::new(archive);
+    while let Some(entry) = archive.next_entry()? {
+        let path = normalize(&entry.path);
+        match entry.kind {
+            TarEntryKind::File if path == MANIFEST_FILE => {
+                manifest = Some(serde_json::from_reader::<_, Vec<DockerArchiveManifest>>(
+                    &mut archive,
+                )?);
+            }
+            TarEntryKind::File => {
+                let blob = write_blob(&blobs, &mut archive)?;
+                files.insert(path, blob);
+            }
+            TarEntryKind::Symlink(target) => {
+                let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+                let target = normalize(&format!("{parent}/{target}"));
+                links.insert(path, target);
+            }
+            TarEntryKind::Hardlink(target) => {
+                links.insert(path, normalize(&target));
+            }
+            _ => {}
+        }
+    }
+    let manifest =
+        manifest.ok_or_else(|| oci_error(format!("archive is missing {MANIFEST_FILE}")))?;
+
+    let lookup = |path: &str| {
+        let mut path = normalize(path);
+        for _ in 0..=links.len() {
+            match links.get(&path) {
+                Some(target) => path = target.clone(),
+                None => break,
+            }
+        }
+        files
+            .get(&path)
+            .ok_or_else(|| oci_error(format!("archive is missing {path}")))
+    };
+
+    let mut index = if layout.join(INDEX_FILE).exists() {
+        lock.read_index()?
+    } else {
+        ImageIndex::default()
+    };
+    for image in &manifest {
+        let config = lookup(image.config())?;
+        let mut layers = Vec::new();
+        for layer in image.layers() {
+            let layer = lookup(layer)?;
+            layers.push(layer.descriptor(layer.media_type.clone()));
+        }
+        let image_manifest = ImageManifestBuilder::default()
+            .schema_version(super::SCHEMA_VERSION)
+            .media_type(MediaType::ImageManifest)
+            .config(config.descriptor(MediaType::ImageConfig))
+            .layers(layers)
+            .build()?;
+
+        let content = image_manifest.to_string()?;
+        let blob = write_blob(&blobs, content.as_bytes())?;
+        let mut descriptor = blob.descriptor(MediaType::ImageManifest);
+        let config_path = blobs.join(config.digest.digest());
+        if let Ok(config) = ImageConfiguration::from_file(config_path) {
+            let mut platform = PlatformBuilder::default()
+                .architecture(config.architecture().clone())
+                .os(config.os().clone());
+            if let Some(variant) = config.variant() {
+                platform = platform.variant(variant.clone());
+            }
+            descriptor.set_platform(Some(platform.build()?));
+        }
+
+        match image.repo_tags().as_deref() {
+            Some(tags) if !tags.is_empty() => {
+                for tag in tags {
+                    index.tag(descriptor.clone(), tag)?;
+                }
+            }
+            _ if index.manifests().contains(&descriptor) => {}
+            _ => index.manifests_mut().push(descriptor),
+        }
+    }
+
+    OciLayoutBuilder::default()
+        .image_layout_version("1.0.0")
+        .build()?
+        .to_file(layout.join("oci-layout"))?;
+    lock.write_index(&index)?;
+    Ok(index)
+}
+
+/// Export the images of the `index.json` of the OCI image layout directory
+/// `layout` as `docker save` archive into `writer`.
+///
+/// Only manifests referenced directly by the index are exported. Their
+/// `org.opencontainers.image.ref.name` annotations are used as repo tags if
+/// they are a full reference with a tag like `alpine:3.20`, other manifests
+/// are exported untagged.
+/// # Errors
+/// This function will return an [OciSpecError::Io] or
+/// [OciSpecError::FileIo] if the layout cannot be read or the archive cannot
+/// be written.
+/// # Example
+/// ``` no_run
+/// use oci_spec::image::oci_layout_to_docker_archive;
+/// use std::fs::File;
+///
+/// let archive = File::create("image.tar").unwrap();
+/// oci_layout_to_docker_archive("layout", archive).unwrap();
+/// ```
+pub fn oci_layout_to_docker_archive<P: AsRef<Path>, W: Write>(layout: P, writer: W) -> Result<()> {
+    let layout = layout.as_ref();
+    let lock = OciLayout::lock_shared(layout)?;
+    let index = lock.read_index()?;
+
+    let mut images: Vec<(Digest, DockerArchiveManifest)> = Vec::new();
+    let mut blobs: Vec<Digest> = Vec::new();
+    for descriptor in index.manifests() {
+        if descriptor.media_type() != &MediaType::ImageManifest {
+            continue;
+        }
+        let position = images.iter().position(|(d, _)| d == descriptor.digest());
+        let position = match position {
+            Some(position) => position,
+            None => {
+                let manifest =
+                    ImageManifest::from_file(layout.join(blob_path(descriptor.digest())))?;
+                let layers = manifest.layers().iter().map(Descriptor::digest);
+                for digest in std::iter::once(manifest.config().digest()).chain(layers.clone()) {
+                    if !blobs.contains(digest) {
+                        blobs.push(digest.clone());
+                    }
+                }
+                let image = DockerArchiveManifestBuilder::default()
+                    .config(archive_path(manifest.config().digest()))
+                    .layers(layers.map(archive_path).collect::<Vec<_>>())
+                    .build()?;
+                images.push((descriptor.digest().clone(), image));
+                images.len() - 1
+            }
+        };
+        if let Some(tag) = descriptor
+            .ref_name()
+            .filter(|name| split_repo_tag(name).is_some())
+        {
+            let image = &mut images[position].1;
+            let mut tags = image.repo_tags().clone().unwrap_or_default();
+            tags.push(tag.to_string());
+            image.set_repo_tags(Some(tags));
+        }
+    }
+
+    let mut archive = TarWriter::new(BufWriter::new(writer));
+    let mut directories: Vec<_> = blobs.iter().map(Digest::algorithm).collect();
+    directories.dedup();
+    archive.append_dir("blobs")?;
+    for algorithm in directories {
+        archive.append_dir(&format!("blobs/{algorithm}"))?;
+    }
+    for digest in &blobs {
+        let blob = layout.join(blob_path(digest));
+        let file = File::open(&blob).map_err(file_io_error(&blob))?;
+        let size = file.metadata().map_err(file_io_error(&blob))?.len();
+        archive.append_file(&archive_path(digest), size, BufReader::new(file))?;
+    }
+
+    let mut repositories: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for (_, image) in &images {
+        for tag in image.repo_tags().iter().flatten() {
+            if let (Some((repo, tag)), Some(top)) = (split_repo_tag(tag), image.layers().last()) {
+                let top = top.rsplit('/').next().unwrap_or_default();
+                repositories
+                    .entry(repo.to_string())
+                    .or_default()
+                    .insert(tag.to_string(), top.to_string());
+            }
+        }
+    }
+
+    let manifest: Vec<_> = images.into_iter().map(|(_, image)| image).collect();
+    let manifest = serde_json::to_vec(&manifest)?;
+    archive.append_file(MANIFEST_FILE, manifest.len() as u64, &manifest[..])?;
+    let repositories = serde_json::to_vec(&repositories)?;
+    archive.append_file(
+        REPOSITORIES_FILE,
+        repositories.len() as u64,
+        &repositories[..],
+    )?;
+    archive.finish()?;
+    Ok(())
+}
+
+/// A blob written into the layout while importing an archive.
+struct Blob {
+    digest: Digest,
+    size: u64,
+    media_type: MediaType,
+}
+
+impl Blob {
+    fn descriptor(&self, media_type: MediaType) -> Descriptor {
+        Descriptor::new(media_type, self.size, self.digest.clone())
+    }
+}
+
+/// Copy `content` into the blobs directory while computing its digest and
+/// detecting the compression of layers.
+fn write_blob<R: Read>(blobs: &Path, content: R) -> Result<Blob> {
+    let (file, tmp) = create_temp_file(&blobs.join("blob"))?;
+    let blob = copy_blob(blobs, content, file, &tmp);
+    if blob.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    blob
+}
+
+/// Copy `content` into the temporary `file` at `tmp` and move it to its
+/// digest in `blobs`.
+fn copy_blob<R: Read>(blobs: &Path, mut content: R, file: File, tmp: &Path) -> Result<Blob> {
+    let mut file = BufWriter::new(file);
+    let mut hasher = Sha256::default();
+    let mut magic = Vec::new();
+    let mut size = 0u64;
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = match content.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        if magic.len() < 4 {
+            magic.extend(buf[..n].iter().take(4 - magic.len()));
+        }
+        hasher.update(&buf[..n]);
+        file.write_all(&buf[..n]).map_err(file_io_error(tmp))?;
+        size += n as u64;
+    }
+    file.flush().map_err(file_io_error(tmp))?;
+
+    let hex = hasher.finalize_hex();
+    let path = blobs.join(&hex);
+    fs::rename(tmp, &path).map_err(file_io_error(&path))?;
+    let media_type = match magic.as_slice() {
+        [0x1f, 0x8b, ..] => MediaType::ImageLayerGzip,
+        [0x28, 0xb5, 0x2f, 0xfd] => MediaType::ImageLayerZstd,
+        _ => MediaType::ImageLayer,
+    };
+    Ok(Blob {
+        digest: Sha256Digest::from_str(&hex)?.into(),
+        size,
+        media_type,
+    })
+}
+
+/// Normalize an archive path, resolving `.` and `..` components.
+fn normalize(path: &str) -> String {
+    let mut components = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    components.join("/")
+}
+
+/// The path of a blob inside of an exported archive, which uses the same
+/// structure as an image layout like `docker save` since version 25.
+fn archive_path(digest: &Digest) -> String {
+    format!("blobs/{}/{}", digest.algorithm(), digest.digest())
+}
+
+/// Split a reference like `registry.example/app:v1` into repository and tag.
+fn split_repo_tag(name: &str) -> Option<(&str, &str)> {
+    let (repo, tag) = name.rsplit_once(':')?;
+    (!repo.is_empty() && !tag.is_empty() && !tag.contains('/')).then_some((repo, tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str =
+        r#"{"architecture":"amd64","os":"linux","rootfs":{"type":"layers","diff_ids":[]}}"#;
+
+    fn create_archive() -> Result<Vec<u8>> {
+        let mut archive = TarWriter::new(Vec::new());
+        let config_hex = Sha256::digest_hex(CONFIG.as_bytes());
+        archive.append_file(
+            &format!("{config_hex}.json"),
+            CONFIG.len() as u64,
+            CONFIG.as_bytes(),
+        )?;
+        archive.append_dir("layer1")?;
+        archive.append_file("layer1/layer.tar", 5, &b"layer"[..])?;
+        let manifest = format!(
+            r#"[{{"Config":"{config_hex}.json","RepoTags":["app:v1","app:latest"],"Layers":["layer1/layer.tar"]}}]"#
+        );
+        archive.append_file(MANIFEST_FILE, manifest.len() as u64, manifest.as_bytes())?;
+        Ok(archive.finish()?)
+    }
+
+    #[test]
+    fn docker_archive_round_trip() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let layout = tmp.path().join("layout");
+
+        let index = docker_archive_to_oci_layout(&create_archive()?[..], &layout)?;
+        assert_eq!(index.manifests().len(), 2);
+        let descriptor = index.manifest_by_ref_name("app:latest").expect("tagged");
+        let platform = descriptor.platform().as_ref().expect("platform");
+        assert_eq!(platform.to_string(), "linux/amd64");
+
+        let manifest = ImageManifest::from_file(layout.join(blob_path(descriptor.digest())))?;
+        assert_eq!(manifest.layers()[0].media_type(), &MediaType::ImageLayer);
+        assert_eq!(
+            manifest.layers()[0].digest().digest(),
+            Sha256::digest_hex(b"layer")
+        );
+
+        let mut exported = Vec::new();
+        oci_layout_to_docker_archive(&layout, &mut exported)?;
+        let reimported = docker_archive_to_oci_layout(&exported[..], tmp.path().join("copy"))?;
+        assert_eq!(reimported, index);
+
+        // Importing into an existing layout merges into its index.
+        let other = OciLayout::lock_exclusive(&layout)?;
+        let mut existing = other.read_index()?;
+        let mut base = descriptor.clone();
+        base.set_annotations(None);
+        existing.tag(base, "base:v1")?;
+        other.write_index(&existing)?;
+        drop(other);
+        let merged = docker_archive_to_oci_layout(&create_archive()?[..], &layout)?;
+        assert_eq!(merged.manifests().len(), 3);
+        assert!(merged.manifest_by_ref_name("base:v1").is_some());
+        let names = fs::read_dir(layout.join("blobs/sha256"))?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<_>>>()?;
+        assert!(names.iter().all(|name| !name.starts_with('.')), "{names:?}");
+
+        // Other directories are left alone.
+        let other = tmp.path().join("other");
+        fs::create_dir(&other)?;
+        fs::write(other.join("file"), "content")?;
+        assert!(docker_archive_to_oci_layout(&create_archive()?[..], &other).is_err());
+        assert!(!other.join("blobs").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn docker_archive_without_manifest() {
+        let archive = TarWriter::new(Vec::new()).finish().unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(docker_archive_to_oci_layout(&archive[..], tmp.path()).is_err());
+        // A failed conversion does not leave an empty index behind.
+        assert!(!tmp.path().join("index.json").exists());
+    }
+}
@@ -0,0 +1,857 @@
+use super::{Descriptor, Digest, MediaType, Platform, ANNOTATION_REF_NAME};
+use crate::{
+    annotations::validate_annotations,
+    error::{with_parent, FindingCode, OciSpecError, Result, ValidationError, ValidationErrorKind},
+    from_file, from_reader, to_file, to_string, to_writer, ParseOptions,
+};
+use derive_builder::Builder;
+use getset::{CopyGetters, Getters, MutGetters, Setters};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{Read, Write},
+    path::Path,
+};
+
+/// The expected schema version; equals 2 for compatibility with older versions of Docker.
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(
+    Builder,
+    Clone,
+    CopyGetters,
+    Debug,
+    Deserialize,
+    Eq,
+    Getters,
+    MutGetters,
+    Setters,
+    PartialEq,
+    Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[builder(
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(validate = "Self::validate", error = "OciSpecError")
+)]
+/// The image index is a higher-level manifest which points to specific
+/// image manifests, ideal for one or more platforms. While the use of
+/// an image index is OPTIONAL for image providers, image consumers
+/// SHOULD be prepared to process them.
+#[non_exhaustive]
+pub struct ImageIndex {
+    /// This REQUIRED property specifies the image manifest schema version.
+    /// For this version of the specification, this MUST be 2 to ensure
+    /// backward compatibility with older versions of Docker. The
+    /// value of this field will not change. This field MAY be
+    /// removed in a future version of the specification.
+    #[getset(get_copy = "pub", set = "pub")]
+    schema_version: u32,
+    /// This property is reserved for use, to maintain compatibility. When
+    /// used, this field contains the media type of this document,
+    /// which differs from the descriptor use of mediaType.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    media_type: Option<MediaType>,
+    /// This OPTIONAL property contains the type of an artifact when the manifest is used for an
+    /// artifact. If defined, the value MUST comply with RFC 6838, including the naming
+    /// requirements in its section 4.2, and MAY be registered with IANA.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    artifact_type: Option<MediaType>,
+    /// This REQUIRED property contains a list of manifests for specific
+    /// platforms. While this property MUST be present, the size of
+    /// the array MAY be zero.
+    #[getset(get_mut = "pub", get = "pub", set = "pub")]
+    manifests: Vec<Descriptor>,
+    /// This OPTIONAL property specifies a descriptor of another manifest. This value, used by the
+    /// referrers API, indicates a relationship to the specified manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    subject: Option<Descriptor>,
+    /// This OPTIONAL property contains arbitrary metadata for the image
+    /// index. This OPTIONAL property MUST use the annotation rules.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
+    #[getset(get_mut = "pub", get = "pub", set = "pub")]
+    #[builder(default)]
+    annotations: Option<HashMap<String, String>>,
+}
+
+impl ImageIndex {
+    /// Attempts to load an image index from a file.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// if the file does not exist or an
+    /// [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if the image index
+    /// cannot be deserialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageIndex;
+    ///
+    /// let image_index = ImageIndex::from_file("index.json").unwrap();
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ImageIndex> {
+        from_file(path)
+    }
+
+    /// Attempts to load an image index from a stream.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe)
+    /// if the index cannot be deserialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageIndex;
+    /// use std::fs::File;
+    ///
+    /// let reader = File::open("index.json").unwrap();
+    /// let image_index = ImageIndex::from_reader(reader).unwrap();
+    /// ```
+    pub fn from_reader<R: Read>(reader: R) -> Result<ImageIndex> {
+        from_reader(reader)
+    }
+
+    /// Attempts to load an image index from a file, enforcing the limits of `options`.
+    /// # Errors
+    /// This function will return the errors of [ImageIndex::from_file] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if a limit is exceeded.
+    pub fn from_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ParseOptions,
+    ) -> Result<ImageIndex> {
+        crate::parse::from_file_with_options(path, options)
+    }
+
+    /// Attempts to load an image index from a stream, enforcing the limits of `options`.
+    /// # Errors
+    /// This function will return the errors of [ImageIndex::from_reader] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if a limit is exceeded.
+    pub fn from_reader_with_options<R: Read>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> Result<ImageIndex> {
+        crate::parse::from_reader_with_options(reader, options)
+    }
+
+    /// Attempts to load an image index from a file and validates it, see
+    /// [ImageIndex::validate].
+    /// # Errors
+    /// This function will return the errors of [ImageIndex::from_file] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if the index does not
+    /// pass validation.
+    pub fn from_file_validated<P: AsRef<Path>>(path: P) -> Result<ImageIndex> {
+        let index = Self::from_file(path)?;
+        index.validate()?;
+        Ok(index)
+    }
+
+    /// Attempts to load an image index from a stream and validates it, see
+    /// [ImageIndex::validate].
+    /// # Errors
+    /// This function will return the errors of [ImageIndex::from_reader] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if the index does not
+    /// pass validation.
+    pub fn from_reader_validated<R: Read>(reader: R) -> Result<ImageIndex> {
+        let index = Self::from_reader(reader)?;
+        index.validate()?;
+        Ok(index)
+    }
+
+    /// Validates the index, which deserialization does not do: the manifest
+    /// and subject descriptors, the reference names of the manifests, that
+    /// the subject does not set a platform and the annotation keys.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation](crate::OciSpecError::Validation)
+    /// for the first value which does not pass validation.
+    /// # Example
+    /// ```
+    /// use oci_spec::image::ImageIndex;
+    ///
+    /// let json = r#"{
+    ///     "schemaVersion": 2,
+    ///     "manifests": [{
+    ///         "mediaType": "application/vnd.oci.image.manifest.v1+json",
+    ///         "digest": "sha256:44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a",
+    ///         "size": 2,
+    ///         "annotations": { "org.opencontainers.image.ref.name": "no spaces" }
+    ///     }]
+    /// }"#;
+    /// assert!(ImageIndex::from_reader(json.as_bytes()).is_ok());
+    /// assert!(ImageIndex::from_reader_validated(json.as_bytes()).is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        validate_index(
+            &self.manifests,
+            self.subject.as_ref(),
+            self.annotations.as_ref(),
+        )
+    }
+
+    /// Attempts to write an image index to a file as JSON. If the file already exists, it
+    /// will be overwritten.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
+    /// the image index cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageIndex;
+    ///
+    /// let image_index = ImageIndex::from_file("index.json").unwrap();
+    /// image_index.to_file("my-index.json").unwrap();
+    /// ```
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        to_file(&self, path, false)
+    }
+
+    /// Attempts to write an image index to a file as pretty printed JSON. If the file
+    /// already exists, it will be overwritten.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
+    /// the image index cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageIndex;
+    ///
+    /// let image_index = ImageIndex::from_file("index.json").unwrap();
+    /// image_index.to_file_pretty("my-index.json").unwrap();
+    /// ```
+    pub fn to_file_pretty<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        to_file(&self, path, true)
+    }
+
+    /// Attempts to write an image index to a stream as JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image index cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageIndex;
+    ///
+    /// let image_index = ImageIndex::from_file("index.json").unwrap();
+    /// let mut writer = Vec::new();
+    /// image_index.to_writer(&mut writer);
+    /// ```
+    pub fn to_writer<W: Write>(&self, writer: &mut W) -> Result<()> {
+        to_writer(&self, writer, false)
+    }
+
+    /// Attempts to write an image index to a stream as pretty printed JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image index cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageIndex;
+    ///
+    /// let image_index = ImageIndex::from_file("index.json").unwrap();
+    /// let mut writer = Vec::new();
+    /// image_index.to_writer_pretty(&mut writer);
+    /// ```
+    pub fn to_writer_pretty<W: Write>(&self, writer: &mut W) -> Result<()> {
+        to_writer(&self, writer, true)
+    }
+
+    /// Attempts to write an image index to a string as JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image configuration cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageIndex;
+    ///
+    /// let image_index = ImageIndex::from_file("index.json").unwrap();
+    /// let json_str = image_index.to_string().unwrap();
+    /// ```
+    pub fn to_string(&self) -> Result<String> {
+        to_string(&self, false)
+    }
+
+    /// Attempts to write an image index to a string as pretty printed JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image configuration cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageIndex;
+    ///
+    /// let image_index = ImageIndex::from_file("index.json").unwrap();
+    /// let json_str = image_index.to_string_pretty().unwrap();
+    /// ```
+    pub fn to_string_pretty(&self) -> Result<String> {
+        to_string(&self, true)
+    }
+
+    /// Attempts to write the image index to a string in the style of Go's
+    /// `json.Marshal` of the equivalent Go struct: in Go field order,
+    /// without zero valued `omitempty` fields and with Go string escaping.
+    /// Only the top level objects and the nested structs known to this
+    /// crate are reordered, other objects are written with sorted keys.
+    /// The output is not checked against the Go implementations and is
+    /// not guaranteed to be byte-identical to theirs.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image index cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageIndex;
+    ///
+    /// let item = ImageIndex::from_file("index.json").unwrap();
+    /// let json_str = item.to_string_go().unwrap();
+    /// ```
+    pub fn to_string_go(&self) -> Result<String> {
+        crate::go_compat::to_string_go(self, &crate::go_compat::image::INDEX)
+    }
+
+    /// Attempts to write the image index to a stream in the style of Go's
+    /// `json.Marshal`, see [Self::to_string_go].
+    /// # Errors
+    /// This function will return an [OciSpecError::Io](crate::OciSpecError::Io) or
+    /// [OciSpecError::SerDe](crate::OciSpecError::SerDe) if the image index cannot be
+    /// written.
+    pub fn to_writer_go<W: Write>(&self, writer: &mut W) -> Result<()> {
+        crate::go_compat::to_writer_go(self, writer, &crate::go_compat::image::INDEX)
+    }
+
+    /// Convert the `ImageIndex` back into a [`ImageIndexBuilder`], so that it can be modified and
+    /// validated again when calling `build()`.
+    pub fn into_builder(self) -> ImageIndexBuilder {
+        let ImageIndex {
+            schema_version,
+            media_type,
+            artifact_type,
+            manifests,
+            subject,
+            annotations,
+        } = self;
+        ImageIndexBuilder {
+            schema_version: Some(schema_version),
+            media_type: Some(media_type),
+            artifact_type: Some(artifact_type),
+            manifests: Some(manifests),
+            subject: Some(subject),
+            annotations: Some(annotations),
+        }
+    }
+
+    /// Create a [`ImageIndexBuilder`] pre-populated with a copy of this index.
+    pub fn to_builder(&self) -> ImageIndexBuilder {
+        self.clone().into_builder()
+    }
+
+    /// Consumes the index and returns its manifest descriptors.
+    pub fn into_manifests(self) -> Vec<Descriptor> {
+        self.manifests
+    }
+
+    /// Take the subject descriptor out of the index, leaving `None` in its place.
+    pub fn take_subject(&mut self) -> Option<Descriptor> {
+        self.subject.take()
+    }
+
+    /// Take the annotations out of the index, leaving `None` in its place.
+    pub fn take_annotations(&mut self) -> Option<HashMap<String, String>> {
+        self.annotations.take()
+    }
+
+    /// Remove all manifests with the given digest from the index, e.g. the
+    /// descriptors of every tag of an image, and return them in the order of
+    /// the index. The returned vector is empty if the index does not contain
+    /// the digest.
+    pub fn remove_manifest(&mut self, digest: &Digest) -> Vec<Descriptor> {
+        let (removed, kept) = std::mem::take(&mut self.manifests)
+            .into_iter()
+            .partition(|m| m.digest() == digest);
+        self.manifests = kept;
+        removed
+    }
+
+    /// Replace all manifests with the given digest by a single `manifest` at
+    /// the position of the first of them, and return the replaced
+    /// descriptors in the order of the index. The index is left unchanged and
+    /// the returned vector is empty if the index does not contain the digest.
+    pub fn replace_manifest(&mut self, digest: &Digest, manifest: Descriptor) -> Vec<Descriptor> {
+        let Some(position) = self.manifests.iter().position(|m| m.digest() == digest) else {
+            return Vec::new();
+        };
+        let replaced = self.remove_manifest(digest);
+        self.manifests.insert(position, manifest);
+        replaced
+    }
+
+    /// Retain only the manifests for which `f` returns true when called with
+    /// their platform, e.g. to prune an architecture from a multi-arch image.
+    /// Manifests without a platform are passed as `None`.
+    /// # Example
+    /// ```no_run
+    /// use oci_spec::image::{Arch, ImageIndex};
+    ///
+    /// let mut index = ImageIndex::from_file("index.json").unwrap();
+    /// index.retain_platforms(|platform| {
+    ///     platform.map_or(true, |p| p.architecture() != &Arch::s390x)
+    /// });
+    /// ```
+    pub fn retain_platforms<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Option<&Platform>) -> bool,
+    {
+        self.manifests.retain(|m| f(m.platform().as_ref()));
+    }
+
+    /// Return the manifest whose `org.opencontainers.image.ref.name`
+    /// annotation equals `name`, e.g. the tag of an image in a layout.
+    pub fn manifest_by_ref_name(&self, name: &str) -> Option<&Descriptor> {
+        self.manifests.iter().find(|m| m.ref_name() == Some(name))
+    }
+
+    /// Add `descriptor` to the index under the reference `name`, setting its
+    /// `org.opencontainers.image.ref.name` annotation. Any other manifest
+    /// with the same reference is removed, so that `name` keeps pointing to a
+    /// single manifest like a tag.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] if `name` does
+    /// not match the grammar of the image spec for reference names.
+    pub fn tag(&mut self, mut descriptor: Descriptor, name: &str) -> Result<()> {
+        if !is_valid_ref_name(name) {
+            return Err(ValidationError::new(
+                ValidationErrorKind::InvalidFormat,
+                ANNOTATION_REF_NAME,
+                "invalid reference name",
+            )
+            .with_code(FindingCode::RefNameInvalid)
+            .with_actual(name)
+            .into());
+        }
+        self.manifests.retain(|m| m.ref_name() != Some(name));
+        descriptor.set_ref_name(name);
+        self.manifests.push(descriptor);
+        Ok(())
+    }
+}
+
+/// Check `name` against the grammar of the image spec for reference names:
+/// components of alphanumerics joined by `-._:@+` or `--`, separated by `/`.
+fn is_valid_ref_name(name: &str) -> bool {
+    name.split('/').all(|component| {
+        let bytes = component.as_bytes();
+        let is_separator = |b: u8| matches!(b, b'-' | b'.' | b'_' | b':' | b'@' | b'+');
+        let mut i = 0;
+        let mut expect_alphanum = true;
+        while i < bytes.len() {
+            if bytes[i].is_ascii_alphanumeric() {
+                expect_alphanum = false;
+            } else if expect_alphanum || !is_separator(bytes[i]) {
+                return false;
+            } else {
+                if bytes[i] == b'-' && bytes.get(i + 1) == Some(&b'-') {
+                    i += 1;
+                }
+                expect_alphanum = true;
+            }
+            i += 1;
+        }
+        !expect_alphanum
+    })
+}
+
+impl Default for ImageIndex {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            media_type: Default::default(),
+            manifests: Default::default(),
+            annotations: Default::default(),
+            artifact_type: Default::default(),
+            subject: Default::default(),
+        }
+    }
+}
+
+impl ImageIndexBuilder {
+    fn validate(&self) -> Result<()> {
+        validate_index(
+            self.manifests.as_deref().unwrap_or_default(),
+            self.subject.as_ref().and_then(Option::as_ref),
+            self.annotations.as_ref().and_then(Option::as_ref),
+        )
+    }
+
+    /// Append a single manifest descriptor to the manifests set on this builder.
+    pub fn add_manifest(self, manifest: Descriptor) -> Self {
+        self.extend_manifests([manifest])
+    }
+
+    /// Append the provided manifest descriptors to the manifests set on this builder.
+    pub fn extend_manifests<I: IntoIterator<Item = Descriptor>>(mut self, manifests: I) -> Self {
+        self.manifests
+            .get_or_insert_with(Vec::new)
+            .extend(manifests);
+        self
+    }
+
+    /// Add a single annotation, keeping any annotations set before.
+    pub fn add_annotation(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extend_annotations([(key.into(), value.into())])
+    }
+
+    /// Extend the annotations with the provided key-value pairs, keeping any annotations set
+    /// before.
+    pub fn extend_annotations<I>(mut self, annotations: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        self.annotations
+            .get_or_insert_with(|| Some(HashMap::new()))
+            .get_or_insert_with(HashMap::new)
+            .extend(annotations);
+        self
+    }
+}
+
+fn validate_index(
+    manifests: &[Descriptor],
+    subject: Option<&Descriptor>,
+    annotations: Option<&HashMap<String, String>>,
+) -> Result<()> {
+    let descriptors = manifests
+        .iter()
+        .enumerate()
+        .map(|(i, m)| (format!("manifests[{i}]"), m))
+        .chain(subject.into_iter().map(|s| ("subject".to_string(), s)));
+    for (path, descriptor) in descriptors {
+        descriptor.validate().map_err(with_parent(path))?;
+    }
+
+    for (i, manifest) in manifests.iter().enumerate() {
+        if let Some(name) = manifest.ref_name() {
+            if !is_valid_ref_name(name) {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::InvalidFormat,
+                    format!("manifests[{i}].annotations.{ANNOTATION_REF_NAME}"),
+                    "invalid reference name",
+                )
+                .with_code(FindingCode::RefNameInvalid)
+                .with_actual(name)
+                .into());
+            }
+        }
+    }
+
+    if let Some(subject) = subject {
+        if subject.platform().is_some() {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Conflict,
+                "subject.platform",
+                "subject descriptor must not set a platform",
+            )
+            .with_code(FindingCode::SubjectPlatformSet)
+            .into());
+        }
+    }
+
+    if let Some(annotations) = annotations {
+        validate_annotations(annotations)?;
+    }
+    Ok(())
+}
+
+/// This ToString trait is automatically implemented for any type which implements the Display trait.
+/// As such, ToString shouldn’t be implemented directly: Display should be implemented instead,
+/// and you get the ToString implementation for free.
+impl Display for ImageIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Serde serialization never fails since this is
+        // a combination of String and enums.
+        write!(
+            f,
+            "{}",
+            self.to_string_pretty()
+                .expect("ImageIndex to JSON conversion failed")
+        )
+    }
+}
+
+impl TryFrom<serde_json::Value> for ImageIndex {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a JSON value into an image index.
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        crate::from_value(value)
+    }
+}
+
+impl TryFrom<ImageIndex> for serde_json::Value {
+    type Error = OciSpecError;
+
+    /// Attempts to convert an image index into a JSON value.
+    fn try_from(index: ImageIndex) -> Result<Self> {
+        crate::to_value(&index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+    use crate::image::{Arch, Os, Sha256Digest};
+    use crate::image::{DescriptorBuilder, PlatformBuilder};
+
+    fn create_index() -> ImageIndex {
+        let ppc_manifest = DescriptorBuilder::default()
+            .media_type(MediaType::ImageManifest)
+            .digest(
+                Sha256Digest::from_str(
+                    "e692418e4cbaf90ca69d05a66403747baa33ee08806650b51fab815ad7fc331f",
+                )
+                .unwrap(),
+            )
+            .size(7143u64)
+            .platform(
+                PlatformBuilder::default()
+                    .architecture(Arch::PowerPC64le)
+                    .os(Os::Linux)
+                    .build()
+                    .expect("build ppc64le platform"),
+            )
+            .build()
+            .expect("build ppc manifest descriptor");
+
+        let amd64_manifest = DescriptorBuilder::default()
+            .media_type(MediaType::ImageManifest)
+            .digest(
+                Sha256Digest::from_str(
+                    "5b0bcabd1ed22e9fb1310cf6c2dec7cdef19f0ad69efa1f392e94a4333501270",
+                )
+                .unwrap(),
+            )
+            .size(7682u64)
+            .platform(
+                PlatformBuilder::default()
+                    .architecture(Arch::Amd64)
+                    .os(Os::Linux)
+                    .build()
+                    .expect("build amd64 platform"),
+            )
+            .build()
+            .expect("build amd64 manifest descriptor");
+
+        ImageIndexBuilder::default()
+            .schema_version(SCHEMA_VERSION)
+            .manifests(vec![ppc_manifest, amd64_manifest])
+            .build()
+            .expect("build image index")
+    }
+
+    fn get_index_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/data/index.json")
+    }
+
+    #[test]
+    fn load_index_from_file() {
+        // arrange
+        let index_path = get_index_path();
+
+        // act
+        let actual = ImageIndex::from_file(index_path).expect("from file");
+
+        // assert
+        let expected = create_index();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn load_index_from_reader() {
+        // arrange
+        let reader = fs::read(get_index_path()).expect("read index");
+
+        // act
+        let actual = ImageIndex::from_reader(&*reader).expect("from reader");
+
+        // assert
+        let expected = create_index();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn save_index_to_file() {
+        // arrange
+        let tmp = std::env::temp_dir().join("save_index_to_file");
+        fs::create_dir_all(&tmp).expect("create test directory");
+        let index = create_index();
+        let index_path = tmp.join("index.json");
+
+        // act
+        index
+            .to_file_pretty(&index_path)
+            .expect("write index to file");
+
+        // assert
+        let actual = fs::read_to_string(index_path).expect("read actual");
+        let expected = fs::read_to_string(get_index_path()).expect("read expected");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn save_index_to_writer() {
+        // arrange
+        let mut actual = Vec::new();
+        let index = create_index();
+
+        // act
+        index.to_writer_pretty(&mut actual).expect("to writer");
+
+        // assert
+        let expected = fs::read(get_index_path()).expect("read expected");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn save_index_to_string() {
+        // arrange
+        let index = create_index();
+
+        // act
+        let actual = index.to_string_pretty().expect("to string");
+
+        // assert
+        let expected = fs::read_to_string(get_index_path()).expect("read expected");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn edit_index_manifests() {
+        let mut index = create_index();
+        let ppc_digest = index.manifests()[0].digest().clone();
+        let amd64 = index.manifests()[1].clone();
+        let mut tagged = amd64.clone();
+        tagged.set_ref_name("latest");
+        index.manifests_mut().push(tagged.clone());
+
+        let mut rebuilt = amd64.clone();
+        rebuilt.set_size(1234);
+        let replaced = index.replace_manifest(amd64.digest(), rebuilt);
+        assert_eq!(replaced, vec![amd64.clone(), tagged]);
+        assert_eq!(index.manifests().len(), 2);
+        assert_eq!(index.manifests()[1].size().unwrap(), 1234);
+
+        index.retain_platforms(|p| p.is_some_and(|p| p.architecture() != &Arch::PowerPC64le));
+        assert_eq!(index.manifests().len(), 1);
+        assert!(index.remove_manifest(&ppc_digest).is_empty());
+
+        let removed = index.remove_manifest(amd64.digest());
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].size().unwrap(), 1234);
+        assert!(index.manifests().is_empty());
+        assert!(index
+            .replace_manifest(amd64.digest(), amd64.clone())
+            .is_empty());
+        assert!(index.manifests().is_empty());
+    }
+
+    #[test]
+    fn tag_index_manifests() {
+        let mut index = create_index();
+        let amd64 = index.manifests()[1].clone();
+
+        index.tag(amd64.clone(), "v1.2").expect("tag v1.2");
+        index.tag(amd64.clone(), "latest").expect("tag latest");
+        assert_eq!(index.manifests().len(), 4);
+        let tagged = index.manifest_by_ref_name("v1.2").expect("resolve v1.2");
+        assert_eq!(tagged.digest(), amd64.digest());
+
+        let ppc = index.manifests()[0].clone();
+        index.tag(ppc.clone(), "latest").expect("move latest");
+        assert_eq!(index.manifests().len(), 4);
+        let latest = index
+            .manifest_by_ref_name("latest")
+            .expect("resolve latest");
+        assert_eq!(latest.digest(), ppc.digest());
+        assert!(index.manifest_by_ref_name("v2").is_none());
+
+        for name in ["example.com/app:v1", "a--b", "1.0+build"] {
+            assert!(is_valid_ref_name(name), "{name}");
+        }
+        for name in ["", "v1.", "-v1", "a/", "a---b", "a b"] {
+            assert!(index.tag(amd64.clone(), name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn validate_rebuilt() {
+        let index = create_index();
+        assert_eq!(index.to_builder().build().expect("rebuild index"), index);
+
+        let mut manifest = index.manifests()[0].clone();
+        manifest.set_ref_name("not valid");
+        let err = index
+            .to_builder()
+            .add_manifest(manifest)
+            .build()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with(&format!("manifests[2].annotations.{ANNOTATION_REF_NAME}")));
+
+        let err = index
+            .clone()
+            .into_builder()
+            .subject(index.manifests()[0].clone())
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("subject.platform"));
+
+        let err = index
+            .to_builder()
+            .add_annotation("org.opencontainers.unknown", "value")
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("annotations"));
+    }
+
+    #[test]
+    fn validate_deserialized() {
+        let mut index = create_index();
+        assert!(index.validate().is_ok());
+
+        let path = |index: &ImageIndex| match index.validate() {
+            Err(OciSpecError::Validation(err)) => (err.path().to_string(), err.code()),
+            res => panic!("expected a validation error, got {res:?}"),
+        };
+        index.manifests_mut()[1].set_ref_name("not valid");
+        assert_eq!(
+            path(&index),
+            (
+                format!("manifests[1].annotations.{ANNOTATION_REF_NAME}"),
+                FindingCode::RefNameInvalid
+            )
+        );
+
+        let mut index = create_index();
+        index.set_subject(Some(index.manifests()[0].clone()));
+        assert_eq!(
+            path(&index),
+            (
+                "subject.platform".to_string(),
+                FindingCode::SubjectPlatformSet
+            )
+        );
+
+        let mut index = create_index();
+        index.set_annotations(Some(HashMap::from([(
+            "org.opencontainers.image.unknown".to_string(),
+            "value".to_string(),
+        )])));
+        assert_eq!(path(&index).1, FindingCode::AnnotationKeyReserved);
+
+        let json = serde_json::to_string(&create_index()).unwrap();
+        assert!(ImageIndex::from_reader_validated(json.as_bytes()).is_ok());
+    }
+}
@@ -0,0 +1,968 @@
+use super::{Compression, Descriptor, Digest, MediaType};
+#[cfg(feature = "distribution")]
+use crate::distribution::Reference;
+use crate::{
+    annotations::validate_annotations,
+    error::{with_parent, FindingCode, OciSpecError, Result, ValidationError, ValidationErrorKind},
+    from_file, from_reader, to_file, to_string, to_writer, ParseOptions,
+};
+use derive_builder::Builder;
+use getset::{CopyGetters, Getters, MutGetters, Setters};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{Read, Write},
+    path::Path,
+};
+
+#[derive(
+    Builder,
+    Clone,
+    CopyGetters,
+    Debug,
+    Deserialize,
+    Eq,
+    Getters,
+    MutGetters,
+    Setters,
+    PartialEq,
+    Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[builder(
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(validate = "Self::validate", error = "OciSpecError")
+)]
+/// Unlike the image index, which contains information about a set of images
+/// that can span a variety of architectures and operating systems, an image
+/// manifest provides a configuration and set of layers for a single
+/// container image for a specific architecture and operating system.
+#[non_exhaustive]
+pub struct ImageManifest {
+    /// This REQUIRED property specifies the image manifest schema version.
+    /// For this version of the specification, this MUST be 2 to ensure
+    /// backward compatibility with older versions of Docker. The
+    /// value of this field will not change. This field MAY be
+    /// removed in a future version of the specification.
+    #[getset(get_copy = "pub", set = "pub")]
+    schema_version: u32,
+    /// This property is reserved for use, to maintain compatibility. When
+    /// used, this field contains the media type of this document,
+    /// which differs from the descriptor use of mediaType.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    media_type: Option<MediaType>,
+    /// This OPTIONAL property contains the type of an artifact when the manifest is used for an
+    /// artifact. This MUST be set when config.mediaType is set to the empty value. If defined, the
+    /// value MUST comply with RFC 6838, including the naming requirements in its section 4.2, and
+    /// MAY be registered with IANA. Implementations storing or copying image manifests MUST NOT
+    /// error on encountering an artifactType that is unknown to the implementation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    artifact_type: Option<MediaType>,
+    /// This REQUIRED property references a configuration object for a
+    /// container, by digest. Beyond the descriptor requirements,
+    /// the value has the following additional restrictions:
+    /// The media type descriptor property has additional restrictions for
+    /// config. Implementations MUST support at least the following
+    /// media types:
+    /// - application/vnd.oci.image.config.v1+json
+    ///
+    /// Manifests concerned with portability SHOULD use one of the above
+    /// media types.
+    #[getset(get = "pub", set = "pub")]
+    config: Descriptor,
+    /// Each item in the array MUST be a descriptor. The array MUST have the
+    /// base layer at index 0. Subsequent layers MUST then follow in
+    /// stack order (i.e. from `layers[0]` to `layers[len(layers)-1]`).
+    /// The final filesystem layout MUST match the result of applying
+    /// the layers to an empty directory. The ownership, mode, and other
+    /// attributes of the initial empty directory are unspecified.
+    #[getset(get_mut = "pub", get = "pub", set = "pub")]
+    layers: Vec<Descriptor>,
+    /// This OPTIONAL property specifies a descriptor of another manifest. This value, used by the
+    /// referrers API, indicates a relationship to the specified manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    #[builder(default)]
+    subject: Option<Descriptor>,
+    /// This OPTIONAL property contains arbitrary metadata for the image
+    /// manifest. This OPTIONAL property MUST use the annotation
+    /// rules.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
+    #[getset(get_mut = "pub", get = "pub", set = "pub")]
+    #[builder(default)]
+    annotations: Option<HashMap<String, String>>,
+}
+
+impl ImageManifest {
+    /// Attempts to load an image manifest from a file.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// if the file does not exist or an
+    /// [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if the image manifest
+    /// cannot be deserialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageManifest;
+    ///
+    /// let image_manifest = ImageManifest::from_file("manifest.json").unwrap();
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ImageManifest> {
+        from_file(path)
+    }
+
+    /// Attempts to load an image manifest from a stream.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe)
+    /// if the manifest cannot be deserialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageManifest;
+    /// use std::fs::File;
+    ///
+    /// let reader = File::open("manifest.json").unwrap();
+    /// let image_manifest = ImageManifest::from_reader(reader).unwrap();
+    /// ```
+    pub fn from_reader<R: Read>(reader: R) -> Result<ImageManifest> {
+        from_reader(reader)
+    }
+
+    /// Attempts to load an image manifest from a file, enforcing the limits of `options`.
+    /// # Errors
+    /// This function will return the errors of [ImageManifest::from_file] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if a limit is exceeded.
+    pub fn from_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ParseOptions,
+    ) -> Result<ImageManifest> {
+        crate::parse::from_file_with_options(path, options)
+    }
+
+    /// Attempts to load an image manifest from a stream, enforcing the limits of `options`.
+    /// # Errors
+    /// This function will return the errors of [ImageManifest::from_reader] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if a limit is exceeded.
+    pub fn from_reader_with_options<R: Read>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> Result<ImageManifest> {
+        crate::parse::from_reader_with_options(reader, options)
+    }
+
+    /// Attempts to load an image manifest from a file and runs the validation
+    /// of [ImageManifestBuilder] on it, see [ImageManifest::validate].
+    /// # Errors
+    /// This function will return the errors of [ImageManifest::from_file] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if the manifest does not
+    /// pass validation.
+    pub fn from_file_validated<P: AsRef<Path>>(path: P) -> Result<ImageManifest> {
+        let manifest = Self::from_file(path)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Attempts to load an image manifest from a stream and runs the validation
+    /// of [ImageManifestBuilder] on it, see [ImageManifest::validate].
+    /// # Errors
+    /// This function will return the errors of [ImageManifest::from_reader] or an
+    /// [OciSpecError::Validation](crate::OciSpecError::Validation) if the manifest does not
+    /// pass validation.
+    pub fn from_reader_validated<R: Read>(reader: R) -> Result<ImageManifest> {
+        let manifest = Self::from_reader(reader)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Runs the validation of [ImageManifestBuilder] on the manifest, which
+    /// deserialization does not do, e.g. checking the media types of the
+    /// config and the layers.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation](crate::OciSpecError::Validation)
+    /// for the first value which does not pass validation.
+    /// # Example
+    /// ```
+    /// use oci_spec::image::ImageManifest;
+    ///
+    /// let json = r#"{
+    ///     "schemaVersion": 2,
+    ///     "config": {
+    ///         "mediaType": "application/vnd.oci.image.index.v1+json",
+    ///         "digest": "sha256:44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a",
+    ///         "size": 2
+    ///     },
+    ///     "layers": []
+    /// }"#;
+    /// assert!(ImageManifest::from_reader(json.as_bytes()).is_ok());
+    /// assert!(ImageManifest::from_reader_validated(json.as_bytes()).is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        validate_manifest(
+            Some(&self.config),
+            &self.layers,
+            self.subject.as_ref(),
+            self.annotations.as_ref(),
+        )
+    }
+
+    /// Attempts to write an image manifest to a file as JSON. If the file already exists, it
+    /// will be overwritten.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
+    /// the image manifest cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageManifest;
+    ///
+    /// let image_manifest = ImageManifest::from_file("manifest.json").unwrap();
+    /// image_manifest.to_file("my-manifest.json").unwrap();
+    /// ```
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        to_file(&self, path, false)
+    }
+
+    /// Attempts to write an image manifest to a file as pretty printed JSON. If the file already exists, it
+    /// will be overwritten.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
+    /// the image manifest cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageManifest;
+    ///
+    /// let image_manifest = ImageManifest::from_file("manifest.json").unwrap();
+    /// image_manifest.to_file_pretty("my-manifest.json").unwrap();
+    /// ```
+    pub fn to_file_pretty<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        to_file(&self, path, true)
+    }
+
+    /// Attempts to write an image manifest to a stream as JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image manifest cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageManifest;
+    ///
+    /// let image_manifest = ImageManifest::from_file("manifest.json").unwrap();
+    /// let mut writer = Vec::new();
+    /// image_manifest.to_writer(&mut writer);
+    /// ```
+    pub fn to_writer<W: Write>(&self, writer: &mut W) -> Result<()> {
+        to_writer(&self, writer, false)
+    }
+
+    /// Attempts to write an image manifest to a stream as pretty printed JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image manifest cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageManifest;
+    ///
+    /// let image_manifest = ImageManifest::from_file("manifest.json").unwrap();
+    /// let mut writer = Vec::new();
+    /// image_manifest.to_writer_pretty(&mut writer);
+    /// ```
+    pub fn to_writer_pretty<W: Write>(&self, writer: &mut W) -> Result<()> {
+        to_writer(&self, writer, true)
+    }
+
+    /// Attempts to write an image manifest to a string as JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image configuration cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageManifest;
+    ///
+    /// let image_manifest = ImageManifest::from_file("manifest.json").unwrap();
+    /// let json_str = image_manifest.to_string().unwrap();
+    /// ```
+    pub fn to_string(&self) -> Result<String> {
+        to_string(&self, false)
+    }
+
+    /// Attempts to write an image manifest to a string as pretty printed JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image configuration cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageManifest;
+    ///
+    /// let image_manifest = ImageManifest::from_file("manifest.json").unwrap();
+    /// let json_str = image_manifest.to_string_pretty().unwrap();
+    /// ```
+    pub fn to_string_pretty(&self) -> Result<String> {
+        to_string(&self, true)
+    }
+
+    /// Attempts to write the image manifest to a string in the style of Go's
+    /// `json.Marshal` of the equivalent Go struct: in Go field order,
+    /// without zero valued `omitempty` fields and with Go string escaping.
+    /// Only the top level objects and the nested structs known to this
+    /// crate are reordered, other objects are written with sorted keys.
+    /// The output is not checked against the Go implementations and is
+    /// not guaranteed to be byte-identical to theirs.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the image manifest cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::ImageManifest;
+    ///
+    /// let item = ImageManifest::from_file("manifest.json").unwrap();
+    /// let json_str = item.to_string_go().unwrap();
+    /// ```
+    pub fn to_string_go(&self) -> Result<String> {
+        crate::go_compat::to_string_go(self, &crate::go_compat::image::MANIFEST)
+    }
+
+    /// Attempts to write the image manifest to a stream in the style of Go's
+    /// `json.Marshal`, see [Self::to_string_go].
+    /// # Errors
+    /// This function will return an [OciSpecError::Io](crate::OciSpecError::Io) or
+    /// [OciSpecError::SerDe](crate::OciSpecError::SerDe) if the image manifest cannot be
+    /// written.
+    pub fn to_writer_go<W: Write>(&self, writer: &mut W) -> Result<()> {
+        crate::go_compat::to_writer_go(self, writer, &crate::go_compat::image::MANIFEST)
+    }
+
+    /// Convert the `ImageManifest` back into a [`ImageManifestBuilder`], so that it can be modified and
+    /// validated again when calling `build()`.
+    pub fn into_builder(self) -> ImageManifestBuilder {
+        let ImageManifest {
+            schema_version,
+            media_type,
+            artifact_type,
+            config,
+            layers,
+            subject,
+            annotations,
+        } = self;
+        ImageManifestBuilder {
+            schema_version: Some(schema_version),
+            media_type: Some(media_type),
+            artifact_type: Some(artifact_type),
+            config: Some(config),
+            layers: Some(layers),
+            subject: Some(subject),
+            annotations: Some(annotations),
+        }
+    }
+
+    /// Create a [`ImageManifestBuilder`] pre-populated with a copy of this manifest.
+    pub fn to_builder(&self) -> ImageManifestBuilder {
+        self.clone().into_builder()
+    }
+
+    /// Consumes the manifest and returns its config descriptor.
+    pub fn into_config(self) -> Descriptor {
+        self.config
+    }
+
+    /// Consumes the manifest and returns its layer descriptors.
+    pub fn into_layers(self) -> Vec<Descriptor> {
+        self.layers
+    }
+
+    /// Take the subject descriptor out of the manifest, leaving `None` in its place.
+    pub fn take_subject(&mut self) -> Option<Descriptor> {
+        self.subject.take()
+    }
+
+    /// Take the annotations out of the manifest, leaving `None` in its place.
+    pub fn take_annotations(&mut self) -> Option<HashMap<String, String>> {
+        self.annotations.take()
+    }
+
+    /// Returns the size of all blobs referenced by the manifest, the config
+    /// and all layers, as stored in a registry. Invalid sizes, see
+    /// [Descriptor::size], are counted as 0.
+    /// # Example
+    /// ```
+    /// use oci_spec::image::{DescriptorBuilder, ImageManifestBuilder, MediaType, Sha256Digest};
+    /// use std::str::FromStr;
+    ///
+    /// let digest = Sha256Digest::from_str(
+    ///     "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a",
+    /// )
+    /// .unwrap();
+    /// let manifest = ImageManifestBuilder::default()
+    ///     .schema_version(2u32)
+    ///     .config(
+    ///         DescriptorBuilder::default()
+    ///             .media_type(MediaType::ImageConfig)
+    ///             .size(100u64)
+    ///             .digest(digest.clone())
+    ///             .build()
+    ///             .unwrap(),
+    ///     )
+    ///     .layers(vec![DescriptorBuilder::default()
+    ///         .media_type(MediaType::ImageLayerGzip)
+    ///         .size(1000u64)
+    ///         .digest(digest)
+    ///         .build()
+    ///         .unwrap()])
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(manifest.total_size(), 1100);
+    /// assert_eq!(manifest.compressed_size(), 1000);
+    /// ```
+    pub fn total_size(&self) -> u64 {
+        self.config
+            .size()
+            .unwrap_or_default()
+            .saturating_add(self.compressed_size())
+    }
+
+    /// Returns the size of all layers as stored in a registry, which is the
+    /// compressed size for compressed layers and the amount of data pulled
+    /// for the filesystem of the image. Invalid sizes are counted as 0.
+    pub fn compressed_size(&self) -> u64 {
+        self.layers.iter().fold(0, |size, layer| {
+            size.saturating_add(layer.size().unwrap_or_default())
+        })
+    }
+
+    /// Returns an iterator over the layers along with the compression
+    /// detected from their media type, see [MediaType::compression], and
+    /// their title annotation, e.g. the file name of an artifact blob.
+    pub fn layers_detailed(
+        &self,
+    ) -> impl Iterator<Item = (&Descriptor, Option<Compression>, Option<&str>)> {
+        self.layers
+            .iter()
+            .map(|layer| (layer, layer.media_type().compression(), layer.title()))
+    }
+
+    /// The reference of the image this image is based on, parsed from the
+    /// `org.opencontainers.image.base.name` annotation.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if the annotation is not a valid reference.
+    #[cfg(feature = "distribution")]
+    pub fn base_image_name(&self) -> Result<Option<Reference>> {
+        super::annotations::base_image_name(self.annotations.as_ref())
+    }
+
+    /// The manifest digest of the image this image is based on, parsed from
+    /// the `org.opencontainers.image.base.digest` annotation.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if the annotation is not a valid digest.
+    pub fn base_image_digest(&self) -> Result<Option<Digest>> {
+        super::annotations::base_image_digest(self.annotations.as_ref())
+    }
+
+    /// Record the image this image is based on in the
+    /// `org.opencontainers.image.base.name` and `base.digest` annotations.
+    /// # Example
+    /// ```
+    /// use oci_spec::distribution::Reference;
+    /// use oci_spec::image::{DescriptorBuilder, Digest, ImageManifestBuilder, MediaType};
+    ///
+    /// let name: Reference = "docker.io/library/alpine:3.20".parse().unwrap();
+    /// let digest: Digest =
+    ///     "sha256:beefdbd8a1da6d2915566fde36db9db0b524eb737fc57cd1367effd16dc0d06d"
+    ///         .parse()
+    ///         .unwrap();
+    /// let mut manifest = ImageManifestBuilder::default()
+    ///     .schema_version(2u32)
+    ///     .config(
+    ///         DescriptorBuilder::default()
+    ///             .media_type(MediaType::ImageConfig)
+    ///             .size(100u64)
+    ///             .digest(digest.clone())
+    ///             .build()
+    ///             .unwrap(),
+    ///     )
+    ///     .layers(vec![])
+    ///     .build()
+    ///     .unwrap();
+    /// manifest.set_base_image(&name, &digest);
+    /// assert_eq!(manifest.base_image_name().unwrap(), Some(name));
+    /// assert_eq!(manifest.base_image_digest().unwrap(), Some(digest));
+    /// ```
+    #[cfg(feature = "distribution")]
+    pub fn set_base_image(&mut self, name: &Reference, digest: &Digest) {
+        super::annotations::set_base_image(
+            self.annotations.get_or_insert_with(HashMap::new),
+            name,
+            digest,
+        );
+    }
+}
+
+impl ImageManifestBuilder {
+    fn validate(&self) -> Result<()> {
+        validate_manifest(
+            self.config.as_ref(),
+            self.layers.as_deref().unwrap_or_default(),
+            self.subject.as_ref().and_then(Option::as_ref),
+            self.annotations.as_ref().and_then(Option::as_ref),
+        )
+    }
+
+    /// Append a single layer descriptor to the layers set on this builder.
+    pub fn add_layer(self, layer: Descriptor) -> Self {
+        self.extend_layers([layer])
+    }
+
+    /// Append the provided layer descriptors to the layers set on this builder.
+    pub fn extend_layers<I: IntoIterator<Item = Descriptor>>(mut self, layers: I) -> Self {
+        self.layers.get_or_insert_with(Vec::new).extend(layers);
+        self
+    }
+
+    /// Add a single annotation, keeping any annotations set before.
+    pub fn add_annotation(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extend_annotations([(key.into(), value.into())])
+    }
+
+    /// Extend the annotations with the provided key-value pairs, keeping any annotations set
+    /// before.
+    pub fn extend_annotations<I>(mut self, annotations: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        self.annotations
+            .get_or_insert_with(|| Some(HashMap::new()))
+            .get_or_insert_with(HashMap::new)
+            .extend(annotations);
+        self
+    }
+}
+
+fn validate_manifest(
+    config: Option<&Descriptor>,
+    layers: &[Descriptor],
+    subject: Option<&Descriptor>,
+    annotations: Option<&HashMap<String, String>>,
+) -> Result<()> {
+    let invalid_media_type = |path: String, message: &str, media_type: &MediaType| {
+        ValidationError::new(ValidationErrorKind::InvalidFormat, path, message)
+            .with_code(FindingCode::MediaTypeInvalid)
+            .with_actual(media_type.to_string())
+            .into()
+    };
+
+    if let Some(config) = config {
+        if !is_config_media_type(config.media_type()) {
+            return Err(invalid_media_type(
+                "config.mediaType".to_string(),
+                "config media type must be an image or artifact config type",
+                config.media_type(),
+            ));
+        }
+    }
+
+    let descriptors = config
+        .map(|config| ("config".to_string(), config))
+        .into_iter()
+        .chain(
+            layers
+                .iter()
+                .enumerate()
+                .map(|(i, l)| (format!("layers[{i}]"), l)),
+        )
+        .chain(subject.map(|subject| ("subject".to_string(), subject)));
+    for (path, descriptor) in descriptors {
+        descriptor.validate().map_err(with_parent(path))?;
+    }
+
+    for (i, layer) in layers.iter().enumerate() {
+        if !is_layer_media_type(layer.media_type()) {
+            return Err(invalid_media_type(
+                format!("layers[{i}].mediaType"),
+                "layer media type must be a layer or artifact blob type",
+                layer.media_type(),
+            ));
+        }
+    }
+
+    if let Some(subject) = subject {
+        if subject.platform().is_some() {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Conflict,
+                "subject.platform",
+                "subject descriptor must not set a platform",
+            )
+            .with_code(FindingCode::SubjectPlatformSet)
+            .into());
+        }
+    }
+
+    if let Some(annotations) = annotations {
+        validate_annotations(annotations)?;
+    }
+
+    Ok(())
+}
+
+/// Media types of other documents of the image spec, which are neither a
+/// config, a layer nor an artifact blob.
+fn is_document_media_type(media_type: &MediaType) -> bool {
+    matches!(
+        media_type,
+        MediaType::Descriptor
+            | MediaType::LayoutHeader
+            | MediaType::ImageManifest
+            | MediaType::ImageIndex
+            | MediaType::ArtifactManifest
+    )
+}
+
+/// Returns true if `media_type` can be used for the config of a manifest. Any
+/// media type not defined by the image spec is accepted as artifact config.
+fn is_config_media_type(media_type: &MediaType) -> bool {
+    !is_document_media_type(media_type)
+        && !matches!(
+            media_type,
+            MediaType::ImageLayer
+                | MediaType::ImageLayerGzip
+                | MediaType::ImageLayerZstd
+                | MediaType::ImageLayerNonDistributable
+                | MediaType::ImageLayerNonDistributableGzip
+                | MediaType::ImageLayerNonDistributableZstd
+        )
+}
+
+/// Returns true if `media_type` can be used for a layer of a manifest. Any
+/// media type not defined by the image spec is accepted as artifact blob.
+fn is_layer_media_type(media_type: &MediaType) -> bool {
+    !is_document_media_type(media_type) && media_type != &MediaType::ImageConfig
+}
+
+/// This ToString trait is automatically implemented for any type which implements the Display trait.
+/// As such, ToString shouldn’t be implemented directly: Display should be implemented instead,
+/// and you get the ToString implementation for free.
+impl Display for ImageManifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Serde serialization never fails since this is
+        // a combination of String and enums.
+        write!(
+            f,
+            "{}",
+            self.to_string_pretty()
+                .expect("ImageManifest to JSON conversion failed")
+        )
+    }
+}
+
+impl TryFrom<serde_json::Value> for ImageManifest {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a JSON value into an image manifest.
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        crate::from_value(value)
+    }
+}
+
+impl TryFrom<ImageManifest> for serde_json::Value {
+    type Error = OciSpecError;
+
+    /// Attempts to convert an image manifest into a JSON value.
+    fn try_from(manifest: ImageManifest) -> Result<Self> {
+        crate::to_value(&manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf, str::FromStr};
+
+    use super::*;
+    use crate::image::{DescriptorBuilder, Platform, Sha256Digest, ANNOTATION_CREATED};
+
+    fn create_manifest() -> ImageManifest {
+        use crate::image::SCHEMA_VERSION;
+
+        let config = DescriptorBuilder::default()
+            .media_type(MediaType::ImageConfig)
+            .size(7023u64)
+            .digest(
+                "b5b2b2c507a0944348e0303114d8d93aaaa081732b86451d9bce1f432a537bc7"
+                    .parse::<Sha256Digest>()
+                    .unwrap(),
+            )
+            .build()
+            .expect("build config descriptor");
+
+        let layers: Vec<Descriptor> = [
+            (
+                32654u64,
+                "9834876dcfb05cb167a5c24953eba58c4ac89b1adf57f28f2f9d09af107ee8f0",
+            ),
+            (
+                16724,
+                "3c3a4604a545cdc127456d94e421cd355bca5b528f4a9c1905b15da2eb4a4c6b",
+            ),
+            (
+                73109,
+                "ec4b8955958665577945c89419d1af06b5f7636b4ac3da7f12184802ad867736",
+            ),
+        ]
+        .iter()
+        .map(|l| {
+            DescriptorBuilder::default()
+                .media_type(MediaType::ImageLayerGzip)
+                .size(l.0)
+                .digest(Sha256Digest::from_str(l.1).unwrap())
+                .build()
+                .expect("build layer")
+        })
+        .collect();
+
+        ImageManifestBuilder::default()
+            .schema_version(SCHEMA_VERSION)
+            .config(config)
+            .layers(layers)
+            .build()
+            .expect("build image manifest")
+    }
+
+    fn get_manifest_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/data/manifest.json")
+    }
+
+    #[test]
+    fn load_manifest_from_file() {
+        // arrange
+        let manifest_path = get_manifest_path();
+        let expected = create_manifest();
+
+        // act
+        let actual = ImageManifest::from_file(manifest_path).expect("from file");
+
+        // assert
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn getset() {
+        let mut manifest = create_manifest();
+        assert_eq!(manifest.layers().len(), 3);
+        let layer_copy = manifest.layers()[0].clone();
+        manifest.layers_mut().push(layer_copy);
+        assert_eq!(manifest.layers().len(), 4);
+    }
+
+    #[test]
+    fn into_layers() {
+        let manifest = create_manifest();
+        let expected = manifest.layers().clone();
+        assert_eq!(manifest.into_layers(), expected);
+    }
+
+    #[test]
+    fn sizes_and_layers() {
+        let mut manifest = create_manifest();
+        assert_eq!(manifest.compressed_size(), 32654 + 16724 + 73109);
+        assert_eq!(manifest.total_size(), 7023 + 32654 + 16724 + 73109);
+
+        manifest.layers_mut()[1].set_title("rootfs.tar");
+        manifest.layers_mut()[2].set_media_type(MediaType::Other(
+            "application/vnd.docker.image.rootfs.diff.tar.zstd".to_string(),
+        ));
+        let layers: Vec<_> = manifest
+            .layers_detailed()
+            .map(|(layer, compression, title)| (layer.size().unwrap(), compression, title))
+            .collect();
+        assert_eq!(
+            layers,
+            [
+                (32654, Some(Compression::Gzip), None),
+                (16724, Some(Compression::Gzip), Some("rootfs.tar")),
+                (73109, Some(Compression::Zstd), None),
+            ]
+        );
+        assert_eq!(
+            MediaType::ImageLayer.compression(),
+            Some(Compression::Uncompressed)
+        );
+        assert_eq!(MediaType::ImageConfig.compression(), None);
+    }
+
+    #[test]
+    fn builder_add_layer() {
+        let expected = create_manifest();
+        let (first, rest) = expected.layers().split_first().unwrap();
+        let manifest = ImageManifestBuilder::default()
+            .schema_version(expected.schema_version())
+            .config(expected.config().clone())
+            .add_layer(first.clone())
+            .extend_layers(rest.iter().cloned())
+            .build()
+            .expect("build image manifest");
+        assert_eq!(manifest, expected);
+    }
+
+    #[test]
+    fn json_value_round_trip() {
+        let manifest = create_manifest();
+        let value = serde_json::Value::try_from(manifest.clone()).expect("to value");
+        assert_eq!(value["schemaVersion"], 2);
+        let actual = ImageManifest::try_from(value).expect("from value");
+        assert_eq!(actual, manifest);
+        assert!(ImageManifest::try_from(serde_json::json!({"layers": 1})).is_err());
+    }
+
+    #[test]
+    fn load_manifest_from_reader() {
+        // arrange
+        let reader = fs::read(get_manifest_path()).expect("read manifest");
+
+        // act
+        let actual = ImageManifest::from_reader(&*reader).expect("from reader");
+
+        // assert
+        let expected = create_manifest();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn save_manifest_to_file() {
+        // arrange
+        let tmp = std::env::temp_dir().join("save_manifest_to_file");
+        fs::create_dir_all(&tmp).expect("create test directory");
+        let manifest = create_manifest();
+        let manifest_path = tmp.join("manifest.json");
+
+        // act
+        manifest
+            .to_file_pretty(&manifest_path)
+            .expect("write manifest to file");
+
+        // assert
+        let actual = fs::read_to_string(manifest_path).expect("read actual");
+        let expected = fs::read_to_string(get_manifest_path()).expect("read expected");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn save_manifest_to_writer() {
+        // arrange
+        let manifest = create_manifest();
+        let mut actual = Vec::new();
+
+        // act
+        manifest.to_writer_pretty(&mut actual).expect("to writer");
+
+        // assert
+        let expected = fs::read(get_manifest_path()).expect("read expected");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn save_manifest_to_string() {
+        // arrange
+        let manifest = create_manifest();
+
+        // act
+        let actual = manifest.to_string_pretty().expect("to string");
+
+        // assert
+        let expected = fs::read_to_string(get_manifest_path()).expect("read expected");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn builder_validation() {
+        let manifest = create_manifest();
+        let artifact = manifest
+            .to_builder()
+            .config(Descriptor::new(
+                MediaType::Other("application/vnd.example.config+json".to_string()),
+                2,
+                manifest.config().digest().clone(),
+            ))
+            .add_layer(Descriptor::new(
+                MediaType::Other("application/vnd.example.blob".to_string()),
+                2,
+                manifest.config().digest().clone(),
+            ))
+            .add_annotation(ANNOTATION_CREATED, "2024-01-01T00:00:00Z")
+            .add_annotation("com.example.key", "value")
+            .build();
+        assert!(artifact.is_ok());
+
+        let mut config = manifest.config().clone();
+        config.set_media_type(MediaType::ImageLayerGzip);
+        assert!(manifest.to_builder().config(config).build().is_err());
+
+        let layer = manifest.config().clone();
+        assert!(manifest.to_builder().add_layer(layer).build().is_err());
+
+        let mut subject = manifest.config().clone();
+        subject.set_media_type(MediaType::ImageManifest);
+        subject.set_platform(Some(Platform::default()));
+        assert!(manifest.to_builder().subject(subject).build().is_err());
+
+        let err = manifest
+            .to_builder()
+            .add_annotation("org.opencontainers.image.unknown", "value")
+            .build()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("annotations.org.opencontainers.image.unknown"));
+
+        let err = manifest
+            .to_builder()
+            .add_annotation("org.opencontainers.artifact.created", "value")
+            .build()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("annotations.org.opencontainers.artifact.created"));
+
+        let err = manifest
+            .to_builder()
+            .add_annotation("", "value")
+            .build()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("annotations: annotation key must not be empty"));
+        assert!(manifest
+            .to_builder()
+            .add_annotation(ANNOTATION_CREATED, "2024-01-01T00:00:00Z")
+            .add_annotation("com.example.key", "value")
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn validate_deserialized() {
+        let manifest = create_manifest();
+        assert!(manifest.validate().is_ok());
+
+        let mut invalid = manifest.clone();
+        invalid.layers_mut().push(manifest.config().clone());
+        let json = invalid.to_string().unwrap();
+        assert!(ImageManifest::from_reader(json.as_bytes()).is_ok());
+        let err = ImageManifest::from_reader_validated(json.as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("layers[3].mediaType"));
+
+        let mut invalid = manifest.clone();
+        let mut config = manifest.config().clone();
+        config.set_size(u64::MAX);
+        invalid.set_config(config);
+        let json = invalid.to_string().unwrap();
+        assert!(ImageManifest::from_reader(json.as_bytes()).is_ok());
+        let err = ImageManifest::from_reader_validated(json.as_bytes()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("config.size: size overflows int64"));
+    }
+}
@@ -0,0 +1,1209 @@
+use super::{Descriptor, Digest, ImageIndex, ImageManifest, MediaType};
+use crate::{
+    error::{
+        file_io_error, file_serde_error, oci_error, with_parent, FindingCode, OciSpecError, Result,
+        ValidationError, ValidationErrorKind,
+    },
+    from_file, from_reader, to_file, to_string, to_writer,
+};
+use derive_builder::Builder;
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Read, Write},
+    path::{Component, Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The name of the file marking the root of a layout directory.
+pub(crate) const LAYOUT_FILE: &str = "oci-layout";
+
+/// The name of the file of a layout directory which contains the image index.
+pub(crate) const INDEX_FILE: &str = "index.json";
+
+/// The name of the file of a layout directory which is locked instead of
+/// `index.json`, which is replaced when it is written.
+const LOCK_FILE: &str = "index.json.lock";
+
+/// The name of the directory of a layout which contains the blobs.
+const BLOBS_DIR: &str = "blobs";
+
+/// The maximum depth of nested indexes, which protects against cycles and
+/// stack overflows.
+pub(crate) const MAX_INDEX_DEPTH: usize = 8;
+
+/// Return the path of the blob with `digest` relative to the root of an
+/// image layout, `blobs/<alg>/<encoded>`.
+///
+/// The path is always safe to join to the layout root, as the digest grammar
+/// does not allow path separators or `..` components.
+/// # Example
+/// ```
+/// use oci_spec::image::{blob_path, Digest};
+/// use std::{path::Path, str::FromStr};
+///
+/// let digest = Digest::from_str(
+///     "sha256:6c3c624b58dbbcd3c0dd82b4c53f04194d1247c6eebdaab7c610cf7d66709b3b",
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     blob_path(&digest),
+///     Path::new("blobs/sha256/6c3c624b58dbbcd3c0dd82b4c53f04194d1247c6eebdaab7c610cf7d66709b3b"),
+/// );
+/// ```
+pub fn blob_path(digest: &Digest) -> PathBuf {
+    [BLOBS_DIR, digest.algorithm().as_ref(), digest.digest()]
+        .iter()
+        .collect()
+}
+
+/// Return the digest of the blob at `path`, the inverse of [blob_path].
+/// `path` may be prefixed by the path of the layout root, but must not
+/// contain `..` components. Uppercase hex digits of registered algorithms are
+/// normalized to lowercase.
+/// # Errors
+/// This function will return an [OciSpecError::Validation] if `path` does not
+/// end with `blobs/<alg>/<encoded>`, contains `..` components or the digest
+/// is invalid.
+pub fn digest_from_blob_path<P: AsRef<Path>>(path: P) -> Result<Digest> {
+    let path = path.as_ref();
+    let invalid = |message: &str| -> OciSpecError {
+        ValidationError::new(ValidationErrorKind::InvalidFormat, "blob path", message)
+            .with_code(FindingCode::BlobPathInvalid)
+            .with_expected("blobs/<alg>/<encoded>")
+            .with_actual(path.display().to_string())
+            .into()
+    };
+
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(component) => components.push(
+                component
+                    .to_str()
+                    .ok_or_else(|| invalid("blob path is not valid UTF-8"))?,
+            ),
+            Component::ParentDir => return Err(invalid("blob path must not contain '..'")),
+            _ => {}
+        }
+    }
+    let [.., BLOBS_DIR, algorithm, encoded] = components.as_slice() else {
+        return Err(invalid("blob path is not inside of a blobs directory"));
+    };
+
+    let algorithm = super::DigestAlgorithm::from(*algorithm);
+    let encoded = match algorithm.digest_hexlen() {
+        Some(_) => encoded.to_ascii_lowercase(),
+        None => encoded.to_string(),
+    };
+    Digest::try_from(format!("{algorithm}:{encoded}"))
+        .map_err(|err| invalid(&format!("invalid digest: {err}")))
+}
+
+#[derive(Builder, Clone, Debug, Deserialize, Eq, Getters, Setters, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[builder(
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(error = "OciSpecError")
+)]
+/// The oci layout JSON object serves as a marker for the base of an Open Container Image Layout
+/// and to provide the version of the image-layout in use. The imageLayoutVersion value will align
+/// with the OCI Image Specification version at the time changes to the layout are made, and will
+/// pin a given version until changes to the image layout are required.
+#[non_exhaustive]
+pub struct OciLayout {
+    /// This REQUIRED property specifies the image layout version.
+    #[getset(get = "pub", set = "pub")]
+    image_layout_version: String,
+}
+
+impl OciLayout {
+    /// Attempts to load an oci layout from a file.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// if the file does not exist or an
+    /// [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if the oci layout
+    /// cannot be deserialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let oci_layout = OciLayout::from_file("oci-layout").unwrap();
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<OciLayout> {
+        from_file(path)
+    }
+
+    /// Attempts to load an oci layout from a stream.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe)
+    /// if the oci layout cannot be deserialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    /// use std::fs::File;
+    ///
+    /// let reader = File::open("oci-layout").unwrap();
+    /// let oci_layout = OciLayout::from_reader(reader).unwrap();
+    /// ```
+    pub fn from_reader<R: Read>(reader: R) -> Result<OciLayout> {
+        from_reader(reader)
+    }
+
+    /// Attempts to write an oci layout to a file as JSON. If the file already exists, it
+    /// will be overwritten.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
+    /// the oci layout cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let oci_layout = OciLayout::from_file("oci-layout").unwrap();
+    /// oci_layout.to_file("oci-layout").unwrap();
+    /// ```
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        to_file(&self, path, false)
+    }
+
+    /// Attempts to write an oci layout to a file as pretty printed JSON. If the file
+    /// already exists, it will be overwritten.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
+    /// the oci layout cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let oci_layout = OciLayout::from_file("oci-layout").unwrap();
+    /// oci_layout.to_file_pretty("my-oci-layout").unwrap();
+    /// ```
+    pub fn to_file_pretty<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        to_file(&self, path, true)
+    }
+
+    /// Attempts to write an oci layout to a stream as JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the oci layout cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let oci_layout = OciLayout::from_file("oci-layout").unwrap();
+    /// let mut writer = Vec::new();
+    /// oci_layout.to_writer(&mut writer);
+    /// ```
+    pub fn to_writer<W: Write>(&self, writer: &mut W) -> Result<()> {
+        to_writer(&self, writer, false)
+    }
+
+    /// Attempts to write an oci layout to a stream as pretty printed JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the oci layout cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let oci_layout = OciLayout::from_file("oci-layout").unwrap();
+    /// let mut writer = Vec::new();
+    /// oci_layout.to_writer_pretty(&mut writer);
+    /// ```
+    pub fn to_writer_pretty<W: Write>(&self, writer: &mut W) -> Result<()> {
+        to_writer(&self, writer, true)
+    }
+
+    /// Attempts to write an oci layout to a string as JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the oci layout configuration cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let oci_layout = OciLayout::from_file("oci-layout").unwrap();
+    /// let json_str = oci_layout.to_string().unwrap();
+    /// ```
+    pub fn to_string(&self) -> Result<String> {
+        to_string(&self, false)
+    }
+
+    /// Attempts to write an oci layout to a string as pretty printed JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe) if
+    /// the oci layout configuration cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let oci_layout = OciLayout::from_file("oci-layout").unwrap();
+    /// let json_str = oci_layout.to_string_pretty().unwrap();
+    /// ```
+    pub fn to_string_pretty(&self) -> Result<String> {
+        to_string(&self, true)
+    }
+
+    /// Resolve a reference in the image layout directory `layout`, like a
+    /// tag, to the descriptor in `index.json` whose
+    /// `org.opencontainers.image.ref.name` annotation equals `name`.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// or [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
+    /// `index.json` cannot be read.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let descriptor = OciLayout::resolve("layout", "v1.2").unwrap();
+    /// ```
+    pub fn resolve<P: AsRef<Path>>(layout: P, name: &str) -> Result<Option<Descriptor>> {
+        let index = OciLayout::lock_shared(layout)?.read_index()?;
+        Ok(index.manifest_by_ref_name(name).cloned())
+    }
+
+    /// Take a shared lock on the `index.json` of the image layout directory
+    /// `layout` for reading it, which waits for writers holding an exclusive
+    /// lock. The lock is taken on the `index.json.lock` file of the layout,
+    /// which is created if it does not exist. It is advisory, so only
+    /// processes which lock the layout as well are excluded, and it is
+    /// released when the returned guard is dropped. A layout whose lock file
+    /// does not exist and cannot be created, like a layout on a read-only
+    /// file system, is read without a lock, as it cannot be written either.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// if the lock file cannot be opened or locked.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let lock = OciLayout::lock_shared("layout").unwrap();
+    /// let index = lock.read_index().unwrap();
+    /// ```
+    pub fn lock_shared<P: AsRef<Path>>(layout: P) -> Result<LayoutLock> {
+        let layout = layout.as_ref();
+        let lock_path = layout.join(LOCK_FILE);
+        // Open an existing lock file read-only, so that read-only layouts
+        // can be read.
+        let file = match File::open(&lock_path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => match open_lock_file(&lock_path) {
+                Err(err) if is_read_only(&err) => None,
+                file => Some(file.map_err(file_io_error(&lock_path))?),
+            },
+            file => Some(file.map_err(file_io_error(&lock_path))?),
+        };
+        if let Some(file) = &file {
+            file.lock_shared().map_err(file_io_error(&lock_path))?;
+        }
+        Ok(LayoutLock {
+            file,
+            path: layout.join(INDEX_FILE),
+            exclusive: false,
+        })
+    }
+
+    /// Take an exclusive lock on the `index.json` of the image layout
+    /// directory `layout` for updating it, which waits for all readers and
+    /// writers holding a lock. The lock is taken on the `index.json.lock`
+    /// file of the layout, which is created if it does not exist, so that a
+    /// new layout can be locked before its `index.json` is written. The lock
+    /// is advisory and released when the returned guard is dropped.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// if the lock file cannot be opened or locked.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let lock = OciLayout::lock_exclusive("layout").unwrap();
+    /// let mut index = lock.read_index().unwrap();
+    /// index.manifests_mut().retain(|m| m.ref_name().is_some());
+    /// lock.write_index(&index).unwrap();
+    /// ```
+    pub fn lock_exclusive<P: AsRef<Path>>(layout: P) -> Result<LayoutLock> {
+        let layout = layout.as_ref();
+        let lock_path = layout.join(LOCK_FILE);
+        let file = open_lock_file(&lock_path).map_err(file_io_error(&lock_path))?;
+        file.lock().map_err(file_io_error(&lock_path))?;
+        Ok(LayoutLock {
+            file: Some(file),
+            path: layout.join(INDEX_FILE),
+            exclusive: true,
+        })
+    }
+
+    /// Tag `descriptor` as `name` in the `index.json` of the image layout
+    /// directory `layout`, see [ImageIndex::tag].
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] if `name` is
+    /// not a valid reference name, or an
+    /// [OciSpecError::FileIo](crate::OciSpecError::FileIo) or
+    /// [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if
+    /// `index.json` cannot be read or written.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let descriptor = OciLayout::resolve("layout", "v1.2").unwrap().unwrap();
+    /// OciLayout::tag("layout", descriptor, "latest").unwrap();
+    /// ```
+    pub fn tag<P: AsRef<Path>>(layout: P, descriptor: Descriptor, name: &str) -> Result<()> {
+        let lock = OciLayout::lock_exclusive(layout)?;
+        let mut index = lock.read_index()?;
+        index.tag(descriptor, name)?;
+        lock.write_index(&index)
+    }
+
+    /// Validate the image layout directory `layout`: the `oci-layout` file
+    /// and `index.json` must be readable, every blob referenced by the
+    /// index, by nested indexes and by image manifests must exist with the
+    /// size of its descriptor, and every image manifest must pass
+    /// [ImageManifest::validate]. Layers with `urls` may be missing, as
+    /// non-distributable layers are not required to be part of the layout.
+    ///
+    /// The path of a returned [ValidationError] starts at `index.json`, e.g.
+    /// `manifests[0].layers[1]`. Digests of the blobs are not verified.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] for the first
+    /// invalid blob, or an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// or [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if a
+    /// document cannot be read.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// OciLayout::validate("layout").unwrap();
+    /// ```
+    pub fn validate<P: AsRef<Path>>(layout: P) -> Result<()> {
+        let layout = layout.as_ref();
+        let marker = OciLayout::from_file(layout.join(LAYOUT_FILE))?;
+        if marker.image_layout_version().is_empty() {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Missing,
+                "imageLayoutVersion",
+                "image layout version must be set",
+            )
+            .with_code(FindingCode::LayoutVersionMissing)
+            .into());
+        }
+
+        let index = OciLayout::lock_shared(layout)?.read_index()?;
+        validate_manifests(&[layout], index.manifests(), "manifests")
+    }
+
+    /// Merge the image layout directory `other` into the image layout
+    /// directory `layout`, which is created if it does not exist, and return
+    /// the merged index, e.g. to consolidate layouts built per architecture
+    /// into one multi-arch layout.
+    ///
+    /// All blobs of `other` which are not in `layout` yet are copied, blobs
+    /// are deduplicated by their digest. The manifests of the `index.json`
+    /// of `other` are added unless `layout` contains them with the same
+    /// reference name already. If a reference name points to different
+    /// manifests in both layouts, `conflict` decides which one is kept. The
+    /// merged index is validated like [OciLayout::validate] before any blob
+    /// is copied, and `layout` is removed again if it was created by a
+    /// failed merge. Both layouts are locked while they are merged, merging
+    /// a layout into itself returns its index unchanged.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] if a
+    /// reference name conflicts and `conflict` is [RefNameConflict::Error]
+    /// or the merged layout is invalid, or an
+    /// [OciSpecError::FileIo](crate::OciSpecError::FileIo) or
+    /// [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if a layout
+    /// cannot be read or written.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::{OciLayout, RefNameConflict};
+    ///
+    /// for arch in ["amd64", "arm64"] {
+    ///     OciLayout::merge_from("multi-arch", arch, RefNameConflict::Error).unwrap();
+    /// }
+    /// ```
+    pub fn merge_from<P: AsRef<Path>, Q: AsRef<Path>>(
+        layout: P,
+        other: Q,
+        conflict: RefNameConflict,
+    ) -> Result<ImageIndex> {
+        let (layout, other) = (layout.as_ref(), other.as_ref());
+        let other_root = fs::canonicalize(other).map_err(file_io_error(other))?;
+        if fs::canonicalize(layout).is_ok_and(|root| root == other_root) {
+            // Merging a layout into itself changes nothing, locking it twice
+            // would deadlock.
+            return OciLayout::lock_shared(layout)?.read_index();
+        }
+
+        let created = !layout.exists();
+        fs::create_dir_all(layout).map_err(file_io_error(layout))?;
+        let merged = merge_layouts(layout, other, &other_root, conflict);
+        if merged.is_err() && created {
+            let _ = fs::remove_dir_all(layout);
+        }
+        merged
+    }
+}
+
+/// Merge `other` into `layout` for [OciLayout::merge_from], the index of
+/// `layout` is only written if the merged layout is valid.
+fn merge_layouts(
+    layout: &Path,
+    other: &Path,
+    other_root: &Path,
+    conflict: RefNameConflict,
+) -> Result<ImageIndex> {
+    // Lock in the order of the paths, so that concurrent merges of two
+    // layouts in opposite directions cannot deadlock.
+    let root = fs::canonicalize(layout).map_err(file_io_error(layout))?;
+    let (lock, other_lock) = if root.as_path() < other_root {
+        let lock = OciLayout::lock_exclusive(layout)?;
+        (lock, OciLayout::lock_shared(other)?)
+    } else {
+        let other_lock = OciLayout::lock_shared(other)?;
+        (OciLayout::lock_exclusive(layout)?, other_lock)
+    };
+    let other_index = other_lock.read_index()?;
+    let mut index = if layout.join(INDEX_FILE).exists() {
+        lock.read_index()?
+    } else {
+        ImageIndex::default()
+    };
+
+    for (i, descriptor) in other_index.manifests().iter().enumerate() {
+        let Some(name) = descriptor.ref_name() else {
+            let merged = index
+                .manifests()
+                .iter()
+                .any(|m| m.digest() == descriptor.digest() && m.ref_name().is_none());
+            if !merged {
+                index.manifests_mut().push(descriptor.clone());
+            }
+            continue;
+        };
+        match index.manifest_by_ref_name(name) {
+            None => index.manifests_mut().push(descriptor.clone()),
+            Some(existing) if existing.digest() == descriptor.digest() => {}
+            Some(existing) => match conflict {
+                RefNameConflict::Error => {
+                    return Err(ValidationError::new(
+                        ValidationErrorKind::Conflict,
+                        format!("manifests[{i}]"),
+                        format!("reference name {name} points to a different manifest"),
+                    )
+                    .with_code(FindingCode::RefNameConflict)
+                    .with_expected(existing.digest().to_string())
+                    .with_actual(descriptor.digest().to_string())
+                    .into());
+                }
+                RefNameConflict::Keep => {}
+                RefNameConflict::Replace => index.tag(descriptor.clone(), name)?,
+            },
+        }
+    }
+
+    // Validate with the blobs of both layouts before anything is copied.
+    validate_manifests(&[layout, other], index.manifests(), "manifests")?;
+    copy_blobs(&other.join(BLOBS_DIR), &layout.join(BLOBS_DIR))?;
+    let marker = layout.join(LAYOUT_FILE);
+    if !marker.exists() {
+        OciLayoutBuilder::default()
+            .image_layout_version("1.0.0")
+            .build()?
+            .to_file(&marker)?;
+    }
+    lock.write_index(&index)?;
+    Ok(index)
+}
+
+/// How [OciLayout::merge_from] resolves a reference name which points to
+/// different manifests in the merged layouts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RefNameConflict {
+    /// Fail the merge without changing the index.
+    #[default]
+    Error,
+    /// Keep the manifest of the layout merged into.
+    Keep,
+    /// Point the name to the manifest of the merged layout.
+    Replace,
+}
+
+/// Copy the blobs of the `blobs` directory `from` which do not exist in the
+/// `blobs` directory `to`.
+fn copy_blobs(from: &Path, to: &Path) -> Result<()> {
+    for algorithm in fs::read_dir(from).map_err(file_io_error(from))? {
+        let algorithm = algorithm.map_err(file_io_error(from))?.path();
+        if !algorithm.is_dir() {
+            continue;
+        }
+        let target_dir = to.join(algorithm.file_name().unwrap_or_default());
+        fs::create_dir_all(&target_dir).map_err(file_io_error(&target_dir))?;
+        for blob in fs::read_dir(&algorithm).map_err(file_io_error(&algorithm))? {
+            let blob = blob.map_err(file_io_error(&algorithm))?;
+            let name = blob.file_name();
+            let target = target_dir.join(&name);
+            // Skip temporary files of unfinished writes.
+            if name.to_string_lossy().starts_with('.') || target.exists() {
+                continue;
+            }
+            // Copy to a temporary file first, so that the blob appears
+            // completely or not at all.
+            let source = blob.path();
+            write_file_atomic(&target, |writer| {
+                let mut file = File::open(&source).map_err(file_io_error(&source))?;
+                io::copy(&mut file, writer).map_err(file_io_error(&source))?;
+                Ok(())
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// A lock on the `index.json` of an image layout directory, taken by
+/// [OciLayout::lock_shared] or [OciLayout::lock_exclusive] and released
+/// when dropped.
+#[derive(Debug)]
+pub struct LayoutLock {
+    /// The locked lock file, `None` for a read-only layout read without a
+    /// lock.
+    file: Option<File>,
+    path: PathBuf,
+    exclusive: bool,
+}
+
+impl LayoutLock {
+    /// Returns true if the lock is exclusive and allows to write the index.
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive
+    }
+
+    /// Read the `index.json` of the locked layout.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// or [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if the
+    /// index cannot be read, e.g. because the layout is new.
+    pub fn read_index(&self) -> Result<ImageIndex> {
+        ImageIndex::from_file(&self.path)
+    }
+
+    /// Replace the `index.json` of the locked layout with `index`. The index
+    /// is written to a temporary file which is renamed to `index.json`, so
+    /// that readers never see a partially written index.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if the lock is shared, or an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// or [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if the
+    /// index cannot be written.
+    pub fn write_index(&self, index: &ImageIndex) -> Result<()> {
+        if !self.exclusive {
+            return Err(oci_error(format!(
+                "cannot write {} with a shared lock",
+                self.path.display()
+            )));
+        }
+        write_file_atomic(&self.path, |writer| {
+            serde_json::to_writer_pretty(writer, index).map_err(file_serde_error(&self.path))
+        })
+    }
+}
+
+impl Drop for LayoutLock {
+    fn drop(&mut self) {
+        if let Some(file) = &self.file {
+            let _ = file.unlock();
+        }
+    }
+}
+
+/// Open the lock file at `path`, creating it if it does not exist.
+fn open_lock_file(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+}
+
+/// Returns true if `err` is caused by a file system or directory which does
+/// not allow to create files.
+fn is_read_only(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::ReadOnlyFilesystem | io::ErrorKind::PermissionDenied
+    )
+}
+
+/// Create a new temporary file in the directory of `path` with a unique
+/// name starting with a dot, which is renamed to `path` once it is complete.
+pub(crate) fn create_temp_file(path: &Path) -> Result<(File, PathBuf)> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    loop {
+        let tmp = dir.join(format!(
+            ".{name}.tmp-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        match OpenOptions::new().write(true).create_new(true).open(&tmp) {
+            Ok(file) => return Ok((file, tmp)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(file_io_error(&tmp)(err)),
+        }
+    }
+}
+
+/// Write the file at `path` with `write` into a temporary file, which is
+/// renamed to `path` if writing succeeds and removed otherwise.
+pub(crate) fn write_file_atomic<F>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    let (file, tmp) = create_temp_file(path)?;
+    let result = (|| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        let file = writer
+            .into_inner()
+            .map_err(|err| file_io_error(&tmp)(err.into_error()))?;
+        file.sync_all().map_err(file_io_error(&tmp))?;
+        fs::rename(&tmp, path).map_err(file_io_error(path))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Validate the blobs of `manifests`, which are looked up in all `layouts`.
+fn validate_manifests(layouts: &[&Path], manifests: &[Descriptor], parent: &str) -> Result<()> {
+    validate_nested_manifests(layouts, manifests, parent, &mut Vec::new())
+}
+
+/// Validate `manifests` of an index nested in the indexes with the digests
+/// `ancestors`.
+fn validate_nested_manifests(
+    layouts: &[&Path],
+    manifests: &[Descriptor],
+    parent: &str,
+    ancestors: &mut Vec<Digest>,
+) -> Result<()> {
+    for (i, descriptor) in manifests.iter().enumerate() {
+        let path = format!("{parent}[{i}]");
+        let blob = validate_blob(layouts, descriptor, &path)?;
+        match descriptor.media_type() {
+            MediaType::ImageManifest => {
+                let manifest = ImageManifest::from_file(&blob)?;
+                manifest.validate().map_err(with_parent(&path))?;
+                validate_blob(layouts, manifest.config(), &format!("{path}.config"))?;
+                for (j, layer) in manifest.layers().iter().enumerate() {
+                    let non_distributable = layer.urls().as_ref().is_some_and(|u| !u.is_empty());
+                    let relative = blob_path(layer.digest());
+                    if !non_distributable || layouts.iter().any(|l| l.join(&relative).exists()) {
+                        validate_blob(layouts, layer, &format!("{path}.layers[{j}]"))?;
+                    }
+                }
+            }
+            MediaType::ImageIndex => {
+                let nesting_error = |message: &str| {
+                    ValidationError::new(ValidationErrorKind::Conflict, &path, message)
+                        .with_code(FindingCode::IndexNestingInvalid)
+                };
+                if ancestors.contains(descriptor.digest()) {
+                    return Err(nesting_error("index references itself").into());
+                }
+                if ancestors.len() >= MAX_INDEX_DEPTH {
+                    return Err(nesting_error("indexes are nested too deeply")
+                        .with_expected(format!("at most {MAX_INDEX_DEPTH} levels"))
+                        .into());
+                }
+                let index = ImageIndex::from_file(&blob)?;
+                ancestors.push(descriptor.digest().clone());
+                validate_nested_manifests(
+                    layouts,
+                    index.manifests(),
+                    &format!("{path}.manifests"),
+                    ancestors,
+                )?;
+                ancestors.pop();
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check that the blob of `descriptor` exists in one of `layouts` with the
+/// size of the descriptor and return its path.
+fn validate_blob(layouts: &[&Path], descriptor: &Descriptor, path: &str) -> Result<PathBuf> {
+    let relative = blob_path(descriptor.digest());
+    let mut found = None;
+    for layout in layouts {
+        let blob = layout.join(&relative);
+        match fs::metadata(&blob) {
+            Ok(metadata) => {
+                found = Some((blob, metadata));
+                break;
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(file_io_error(&blob)(err)),
+        }
+    }
+    let (blob, metadata) = found.ok_or_else(|| {
+        ValidationError::new(ValidationErrorKind::Missing, path, "blob does not exist")
+            .with_code(FindingCode::BlobMissing)
+            .with_expected(relative.display().to_string())
+    })?;
+    descriptor.validate().map_err(with_parent(path))?;
+    let size = descriptor.size().map_err(with_parent(path))?;
+    if metadata.len() != size {
+        return Err(ValidationError::new(
+            ValidationErrorKind::Conflict,
+            path,
+            "size of the blob does not match the descriptor",
+        )
+        .with_code(FindingCode::BlobSizeMismatch)
+        .with_expected(size.to_string())
+        .with_actual(metadata.len().to_string())
+        .into());
+    }
+    Ok(blob)
+}
+
+impl TryFrom<serde_json::Value> for OciLayout {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a JSON value into an oci layout.
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        crate::from_value(value)
+    }
+}
+
+impl TryFrom<OciLayout> for serde_json::Value {
+    type Error = OciSpecError;
+
+    /// Attempts to convert an oci layout into a JSON value.
+    fn try_from(layout: OciLayout) -> Result<Self> {
+        crate::to_value(&layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    fn create_oci_layout() -> OciLayout {
+        OciLayoutBuilder::default()
+            .image_layout_version("lorem ipsum")
+            .build()
+            .expect("build oci layout")
+    }
+
+    fn get_oci_layout_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/data/oci-layout")
+    }
+
+    #[test]
+    fn load_oci_layout_from_file() {
+        // arrange
+        let oci_layout_path = get_oci_layout_path();
+
+        // act
+        let actual = OciLayout::from_file(oci_layout_path).expect("from file");
+
+        // assert
+        let expected = create_oci_layout();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn load_oci_layout_from_reader() {
+        // arrange
+        let reader = fs::read(get_oci_layout_path()).expect("read oci-layout");
+
+        // act
+        let actual = OciLayout::from_reader(&*reader).expect("from reader");
+
+        // assert
+        let expected = create_oci_layout();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn save_oci_layout_to_file() {
+        // arrange
+        let tmp = std::env::temp_dir().join("save_oci_layout_to_file");
+        fs::create_dir_all(&tmp).expect("create test directory");
+        let oci_layout = create_oci_layout();
+        let oci_layout_path = tmp.join("oci-layout");
+
+        // act
+        oci_layout
+            .to_file_pretty(&oci_layout_path)
+            .expect("write oci-layout to file");
+
+        // assert
+        let actual = fs::read_to_string(oci_layout_path).expect("read actual");
+        let expected = fs::read_to_string(get_oci_layout_path()).expect("read expected");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn save_oci_layout_to_writer() {
+        // arrange
+        let mut actual = Vec::new();
+        let oci_layout = create_oci_layout();
+
+        // act
+        oci_layout.to_writer_pretty(&mut actual).expect("to writer");
+
+        // assert
+        let expected = fs::read(get_oci_layout_path()).expect("read expected");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn save_oci_layout_to_string() {
+        // arrange
+        let oci_layout = create_oci_layout();
+
+        // act
+        let actual = oci_layout.to_string_pretty().expect("to string");
+
+        // assert
+        let expected = fs::read_to_string(get_oci_layout_path()).expect("read expected");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn tag_and_resolve_in_layout() {
+        let layout = std::env::temp_dir().join("tag_and_resolve_in_layout");
+        fs::create_dir_all(&layout).expect("create test directory");
+        let index_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/data/index.json");
+        fs::copy(index_path, layout.join(INDEX_FILE)).expect("copy index");
+
+        assert_eq!(OciLayout::resolve(&layout, "v1.2").expect("resolve"), None);
+        let index = ImageIndex::from_file(layout.join(INDEX_FILE)).expect("load index");
+        let descriptor = index.manifests()[0].clone();
+        OciLayout::tag(&layout, descriptor.clone(), "v1.2").expect("tag");
+
+        let resolved = OciLayout::resolve(&layout, "v1.2")
+            .expect("resolve")
+            .expect("tagged descriptor");
+        assert_eq!(resolved.digest(), descriptor.digest());
+        assert_eq!(resolved.ref_name(), Some("v1.2"));
+    }
+
+    #[test]
+    fn validate_layout() {
+        use crate::image::{
+            v1::testing::{digest, manifest_blob, write_blob},
+            ImageIndexBuilder, SCHEMA_VERSION,
+        };
+
+        let tmp = tempfile::tempdir().expect("create temp dir");
+        let layout = tmp.path();
+
+        let config = Descriptor::new(MediaType::ImageConfig, 2, digest('a'));
+        write_blob(layout, config.digest(), b"{}");
+        let layer = Descriptor::new(MediaType::ImageLayerGzip, 5, digest('b'));
+        write_blob(layout, layer.digest(), b"layer");
+        let (descriptor, manifest) = manifest_blob('c', config.clone(), vec![layer.clone()]);
+        write_blob(layout, descriptor.digest(), &manifest);
+        ImageIndexBuilder::default()
+            .schema_version(SCHEMA_VERSION)
+            .manifests(vec![descriptor])
+            .build()
+            .expect("build index")
+            .to_file(layout.join(INDEX_FILE))
+            .expect("write index");
+
+        // The oci-layout file is missing.
+        assert!(OciLayout::validate(layout).is_err());
+        OciLayoutBuilder::default()
+            .image_layout_version("1.0.0")
+            .build()
+            .expect("build oci layout")
+            .to_file(layout.join(LAYOUT_FILE))
+            .expect("write oci layout");
+        OciLayout::validate(layout).expect("valid layout");
+
+        fs::remove_file(layout.join(blob_path(layer.digest()))).expect("remove layer");
+        let err = OciLayout::validate(layout).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("manifests[0].layers[0]: blob does not exist"));
+
+        write_blob(layout, layer.digest(), b"layer");
+        write_blob(layout, config.digest(), b"{ }");
+        let err = OciLayout::validate(layout).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "manifests[0].config: size of the blob does not match the descriptor \
+             (expected 2, got 3)"
+        );
+    }
+
+    #[test]
+    fn validate_cyclic_layout() {
+        use crate::image::{v1::testing::digest, ImageIndexBuilder, SCHEMA_VERSION};
+
+        let tmp = tempfile::tempdir().expect("create temp dir");
+        let layout = tmp.path();
+        OciLayoutBuilder::default()
+            .image_layout_version("1.0.0")
+            .build()
+            .expect("build oci layout")
+            .to_file(layout.join(LAYOUT_FILE))
+            .expect("write oci layout");
+
+        // An index blob which lists itself, the size is part of the content.
+        let digest = digest('c');
+        let mut size = 0;
+        let content = loop {
+            let content = ImageIndexBuilder::default()
+                .schema_version(SCHEMA_VERSION)
+                .manifests(vec![Descriptor::new(
+                    MediaType::ImageIndex,
+                    size,
+                    digest.clone(),
+                )])
+                .build()
+                .expect("build index")
+                .to_string()
+                .expect("serialize index");
+            if content.len() as u64 == size {
+                break content;
+            }
+            size = content.len() as u64;
+        };
+        let blob = layout.join(blob_path(&digest));
+        fs::create_dir_all(blob.parent().unwrap()).expect("create blobs dir");
+        fs::write(&blob, &content).expect("write blob");
+        fs::write(layout.join(INDEX_FILE), &content).expect("write index");
+
+        let err = OciLayout::validate(layout).unwrap_err();
+        match err {
+            OciSpecError::Validation(err) => {
+                assert_eq!(err.code(), FindingCode::IndexNestingInvalid);
+                assert_eq!(err.path(), "manifests[0].manifests[0]");
+            }
+            err => panic!("unexpected error {err}"),
+        }
+
+        // Errors other than a missing blob are not reported as missing.
+        fs::remove_dir_all(layout.join(BLOBS_DIR)).expect("remove blobs");
+        fs::create_dir(layout.join(BLOBS_DIR)).expect("create blobs dir");
+        fs::write(layout.join(BLOBS_DIR).join("sha256"), "").expect("write file");
+        let err = OciLayout::validate(layout).unwrap_err();
+        assert!(matches!(err, OciSpecError::FileIo { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn blob_path_round_trip() {
+        let hex = "6c3c624b58dbbcd3c0dd82b4c53f04194d1247c6eebdaab7c610cf7d66709b3b";
+        let digest = Digest::try_from(format!("sha256:{hex}")).expect("digest");
+        let path = blob_path(&digest);
+        assert_eq!(digest_from_blob_path(&path).expect("inverse"), digest);
+
+        let upper = PathBuf::from("/layout/blobs/sha256").join(hex.to_uppercase());
+        assert_eq!(digest_from_blob_path(upper).expect("normalized"), digest);
+
+        for invalid in [
+            format!("blobs/sha256/../sha256/{hex}"),
+            format!("sha256/{hex}"),
+            format!("blobs/sha256/{}", &hex[1..]),
+            "blobs/sha256".to_string(),
+        ] {
+            assert!(digest_from_blob_path(&invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn lock_layout() {
+        use crate::image::{ImageIndexBuilder, SCHEMA_VERSION};
+
+        let tmp = tempfile::tempdir().expect("create temp dir");
+        let layout = tmp.path();
+        let index = ImageIndexBuilder::default()
+            .schema_version(SCHEMA_VERSION)
+            .manifests(vec![])
+            .build()
+            .expect("build index");
+
+        let lock = OciLayout::lock_exclusive(layout).expect("lock new layout");
+        assert!(lock.is_exclusive());
+        assert!(lock.read_index().is_err());
+        assert!(!layout.join(INDEX_FILE).exists());
+        lock.write_index(&index).expect("write index");
+        assert_eq!(lock.read_index().expect("read index"), index);
+        // Only the index and the lock file are left, no temporary files.
+        assert_eq!(fs::read_dir(layout).expect("read layout").count(), 2);
+
+        let other = File::open(layout.join(LOCK_FILE)).expect("open lock file");
+        assert!(other.try_lock_shared().is_err());
+        drop(lock);
+        other.try_lock_shared().expect("lock released on drop");
+        other.unlock().expect("unlock");
+
+        let lock = OciLayout::lock_shared(layout).expect("lock layout");
+        assert!(!lock.is_exclusive());
+        assert_eq!(lock.read_index().expect("read index"), index);
+        assert!(lock.write_index(&index).is_err());
+        assert!(other.try_lock().is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn lock_read_only_layout() {
+        use crate::image::{ImageIndexBuilder, SCHEMA_VERSION};
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().expect("create temp dir");
+        let layout = tmp.path();
+        let index = ImageIndexBuilder::default()
+            .schema_version(SCHEMA_VERSION)
+            .manifests(vec![])
+            .build()
+            .expect("build index");
+        index.to_file(layout.join(INDEX_FILE)).expect("write index");
+        fs::set_permissions(layout, fs::Permissions::from_mode(0o555)).expect("make read-only");
+        // Privileged users can create files in read-only directories.
+        if File::create(layout.join(LOCK_FILE)).is_ok() {
+            return;
+        }
+
+        let lock = OciLayout::lock_shared(layout).expect("read without lock");
+        assert_eq!(lock.read_index().expect("read index"), index);
+        assert!(!layout.join(LOCK_FILE).exists());
+        assert!(OciLayout::lock_exclusive(layout).is_err());
+        drop(lock);
+        fs::set_permissions(layout, fs::Permissions::from_mode(0o755)).expect("restore");
+    }
+
+    #[test]
+    fn merge_layouts() {
+        use crate::image::{
+            v1::testing::{digest, manifest_blob, write_blob},
+            ImageIndexBuilder, SCHEMA_VERSION,
+        };
+
+        let tmp = tempfile::tempdir().expect("create temp dir");
+        // Create a layout with a manifest tagged `name`, which shares its
+        // layer with all other layouts.
+        let create_layout = |dir: &str, name: &str, config: char, manifest: char| {
+            let layout = tmp.path().join(dir);
+            let config = Descriptor::new(MediaType::ImageConfig, 2, digest(config));
+            write_blob(&layout, config.digest(), b"{}");
+            let layer = Descriptor::new(MediaType::ImageLayerGzip, 5, digest('b'));
+            write_blob(&layout, layer.digest(), b"layer");
+            let (mut descriptor, content) = manifest_blob(manifest, config, vec![layer]);
+            descriptor.set_ref_name(name);
+            write_blob(&layout, descriptor.digest(), &content);
+            ImageIndexBuilder::default()
+                .schema_version(SCHEMA_VERSION)
+                .manifests(vec![descriptor])
+                .build()
+                .expect("build index")
+                .to_file(layout.join(INDEX_FILE))
+                .expect("write index");
+            layout
+        };
+        let amd64 = create_layout("amd64", "amd64", 'a', 'c');
+        let arm64 = create_layout("arm64", "arm64", 'd', 'e');
+        let rebuilt = create_layout("rebuilt", "amd64", 'f', '1');
+        let merged = tmp.path().join("merged");
+        let ref_names = |index: &ImageIndex| -> Vec<(String, Digest)> {
+            index
+                .manifests()
+                .iter()
+                .map(|m| (m.ref_name().unwrap().to_string(), m.digest().clone()))
+                .collect()
+        };
+
+        OciLayout::merge_from(&merged, &amd64, RefNameConflict::Error).expect("merge amd64");
+        let index =
+            OciLayout::merge_from(&merged, &arm64, RefNameConflict::Error).expect("merge arm64");
+        assert_eq!(
+            ref_names(&index),
+            [
+                ("amd64".to_string(), digest('c')),
+                ("arm64".to_string(), digest('e'))
+            ]
+        );
+        OciLayout::validate(&merged).expect("valid merged layout");
+        assert_eq!(
+            fs::read_dir(merged.join("blobs/sha256")).unwrap().count(),
+            5
+        );
+
+        let again =
+            OciLayout::merge_from(&merged, &arm64, RefNameConflict::Error).expect("merge again");
+        assert_eq!(again, index);
+
+        let err = OciLayout::merge_from(&merged, &rebuilt, RefNameConflict::Error).unwrap_err();
+        match err {
+            OciSpecError::Validation(err) => assert_eq!(err.code(), FindingCode::RefNameConflict),
+            err => panic!("unexpected error {err}"),
+        }
+        assert_eq!(
+            OciLayout::lock_shared(&merged)
+                .unwrap()
+                .read_index()
+                .unwrap(),
+            index
+        );
+
+        let kept =
+            OciLayout::merge_from(&merged, &rebuilt, RefNameConflict::Keep).expect("keep amd64");
+        assert_eq!(kept, index);
+        let replaced = OciLayout::merge_from(&merged, &rebuilt, RefNameConflict::Replace)
+            .expect("replace amd64");
+        assert_eq!(
+            ref_names(&replaced),
+            [
+                ("arm64".to_string(), digest('e')),
+                ("amd64".to_string(), digest('1'))
+            ]
+        );
+
+        // Merging a layout into itself, also through a symbolic link, is a
+        // no-op instead of a deadlock.
+        let same = OciLayout::merge_from(&merged, &merged, RefNameConflict::Error)
+            .expect("merge into itself");
+        assert_eq!(same, replaced);
+        #[cfg(unix)]
+        {
+            let link = tmp.path().join("link");
+            std::os::unix::fs::symlink(&merged, &link).expect("create symlink");
+            OciLayout::merge_from(&link, &merged, RefNameConflict::Error)
+                .expect("merge through symlink");
+        }
+
+        // A failed merge leaves neither a new layout nor copied blobs.
+        let broken = create_layout("broken", "broken", '6', '5');
+        fs::remove_file(broken.join(blob_path(&digest('6')))).expect("remove config");
+        let fresh = tmp.path().join("fresh");
+        assert!(OciLayout::merge_from(&fresh, &broken, RefNameConflict::Error).is_err());
+        assert!(!fresh.exists());
+        let blobs = fs::read_dir(merged.join("blobs/sha256")).unwrap().count();
+        assert!(OciLayout::merge_from(&merged, &broken, RefNameConflict::Error).is_err());
+        assert_eq!(
+            fs::read_dir(merged.join("blobs/sha256")).unwrap().count(),
+            blobs
+        );
+        assert_eq!(
+            OciLayout::lock_shared(&merged)
+                .unwrap()
+                .read_index()
+                .unwrap(),
+            replaced
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use super::{
+    blob_path, oci_layout::MAX_INDEX_DEPTH, Descriptor, Digest, ImageIndex, ImageManifest,
+    MediaType, OciLayout, Platform,
+};
+use crate::error::{file_io_error, oci_error, Result};
+use getset::{CopyGetters, Getters};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+/// The media type of Docker image manifests, which are reported like OCI
+/// image manifests.
+const DOCKER_MANIFEST: &str = "application/vnd.docker.distribution.manifest.v2+json";
+
+/// The media type of Docker manifest lists, which are reported like OCI
+/// image indexes.
+const DOCKER_MANIFEST_LIST: &str = "application/vnd.docker.distribution.manifest.list.v2+json";
+
+#[derive(Clone, Debug, Default, Getters, CopyGetters, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The storage used by the images of an [ImageIndex], created by
+/// [ImageIndex::storage_report] or [OciLayout::storage_report].
+///
+/// Sizes are the sums of the descriptor sizes of the manifests, configs and
+/// layers, blobs referenced by several images are counted for each of them
+/// in [StorageReport::total_size] and once in [StorageReport::unique_size].
+#[non_exhaustive]
+pub struct StorageReport {
+    /// The images of the index in the order of the index, nested indexes are
+    /// flattened.
+    #[getset(get = "pub")]
+    images: Vec<ImageUsage>,
+    /// The size of all images.
+    #[getset(get_copy = "pub")]
+    total_size: u64,
+    /// The size of all distinct blobs, the storage used by the index in a
+    /// registry or layout.
+    #[getset(get_copy = "pub")]
+    unique_size: u64,
+    /// The blobs referenced more than once, sorted by the wasted size in
+    /// descending order.
+    #[getset(get = "pub")]
+    duplicate_blobs: Vec<DuplicateBlob>,
+}
+
+#[derive(Clone, Debug, Getters, CopyGetters, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The storage used by a single image of a [StorageReport].
+#[non_exhaustive]
+pub struct ImageUsage {
+    /// The digest of the image manifest.
+    #[getset(get = "pub")]
+    manifest: Digest,
+    /// The platform of the manifest descriptor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub")]
+    platform: Option<Platform>,
+    /// The size of the manifest, its config and its layers.
+    #[getset(get_copy = "pub")]
+    size: u64,
+    /// The part of the size used by blobs which other images of the report
+    /// reference as well.
+    #[getset(get_copy = "pub")]
+    shared_size: u64,
+    /// The number of layers.
+    #[getset(get_copy = "pub")]
+    layers: usize,
+}
+
+#[derive(Clone, Debug, Getters, CopyGetters, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A blob referenced more than once in a [StorageReport].
+#[non_exhaustive]
+pub struct DuplicateBlob {
+    /// The digest of the blob.
+    #[getset(get = "pub")]
+    digest: Digest,
+    /// The size of the blob.
+    #[getset(get_copy = "pub")]
+    size: u64,
+    /// The number of references to the blob.
+    #[getset(get_copy = "pub")]
+    references: usize,
+}
+
+impl DuplicateBlob {
+    /// The size which storing the blob for every reference would waste.
+    pub fn wasted_size(&self) -> u64 {
+        self.size
+            .saturating_mul((self.references as u64).saturating_sub(1))
+    }
+}
+
+impl StorageReport {
+    /// The size saved by storing shared blobs only once, the difference of
+    /// [StorageReport::total_size] and [StorageReport::unique_size].
+    pub fn savings(&self) -> u64 {
+        self.total_size.saturating_sub(self.unique_size)
+    }
+
+    /// The share of the total size saved by deduplication, from 0 to 1.
+    pub fn savings_ratio(&self) -> f64 {
+        if self.total_size == 0 {
+            return 0.0;
+        }
+        self.savings() as f64 / self.total_size as f64
+    }
+
+    /// The size of the images with `platform`, e.g. to compare the
+    /// architectures of an index.
+    pub fn platform_size(&self, platform: &Platform) -> u64 {
+        self.images
+            .iter()
+            .filter(|image| image.platform.as_ref() == Some(platform))
+            .map(|image| image.size)
+            .fold(0, u64::saturating_add)
+    }
+}
+
+impl ImageIndex {
+    /// Create a [StorageReport] of the images of the index, reading the
+    /// manifests and nested indexes with `fetch`, e.g. from a registry.
+    ///
+    /// Only the manifests and indexes are fetched, the sizes of configs and
+    /// layers are taken from their descriptors. Descriptors of other media
+    /// types, e.g. artifacts, are skipped.
+    /// # Errors
+    /// This function will return the errors of `fetch`, an
+    /// [OciSpecError::SerDe](crate::OciSpecError::SerDe) if a fetched blob
+    /// cannot be deserialized, an [OciSpecError::Validation](crate::OciSpecError::Validation)
+    /// if a size is invalid, see [Descriptor::size], or an
+    /// [OciSpecError::Other](crate::OciSpecError::Other) if indexes are nested too deep.
+    /// # Example
+    /// ```
+    /// use oci_spec::image::{ImageIndex, Descriptor};
+    ///
+    /// let report = ImageIndex::default()
+    ///     .storage_report(|_: &Descriptor| -> oci_spec::Result<Vec<u8>> {
+    ///         unreachable!("no manifests to fetch")
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(report.total_size(), 0);
+    /// ```
+    pub fn storage_report<F>(&self, mut fetch: F) -> Result<StorageReport>
+    where
+        F: FnMut(&Descriptor) -> Result<Vec<u8>>,
+    {
+        let mut images = Vec::new();
+        collect_images(
+            self.manifests(),
+            &mut fetch,
+            &mut images,
+            &mut HashSet::new(),
+            0,
+        )?;
+
+        let mut references: HashMap<&Digest, (u64, usize)> = HashMap::new();
+        for (_, blobs) in &images {
+            for (digest, size) in blobs {
+                let entry = references.entry(digest).or_insert((*size, 0));
+                entry.1 += 1;
+            }
+        }
+
+        let mut report = StorageReport::default();
+        for (manifest, blobs) in &images {
+            let size = blobs
+                .iter()
+                .map(|(_, size)| *size)
+                .fold(0, u64::saturating_add);
+            let shared_size = blobs
+                .iter()
+                .filter(|(digest, _)| references[digest].1 > 1)
+                .map(|(_, size)| *size)
+                .fold(0, u64::saturating_add);
+            report.total_size = report.total_size.saturating_add(size);
+            report.images.push(ImageUsage {
+                manifest: manifest.digest().clone(),
+                platform: manifest.platform().clone(),
+                size,
+                shared_size,
+                layers: blobs.len() - 2,
+            });
+        }
+        report.unique_size = references
+            .values()
+            .map(|(size, _)| *size)
+            .fold(0, u64::saturating_add);
+        report.duplicate_blobs = references
+            .into_iter()
+            .filter(|(_, (_, count))| *count > 1)
+            .map(|(digest, (size, references))| DuplicateBlob {
+                digest: digest.clone(),
+                size,
+                references,
+            })
+            .collect();
+        report.duplicate_blobs.sort_by(|a, b| {
+            b.wasted_size()
+                .cmp(&a.wasted_size())
+                .then_with(|| a.digest.to_string().cmp(&b.digest.to_string()))
+        });
+        Ok(report)
+    }
+}
+
+impl OciLayout {
+    /// Create a [StorageReport] of the images of the `index.json` of the
+    /// image layout directory `layout`, see [ImageIndex::storage_report].
+    /// The layout is locked while the report is created.
+    /// # Errors
+    /// This function will return the errors of [ImageIndex::storage_report],
+    /// or an [OciSpecError::FileIo](crate::OciSpecError::FileIo) if a blob
+    /// cannot be read.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::image::OciLayout;
+    ///
+    /// let report = OciLayout::storage_report("layout").unwrap();
+    /// println!("{} bytes saved by shared layers", report.savings());
+    /// ```
+    pub fn storage_report<P: AsRef<Path>>(layout: P) -> Result<StorageReport> {
+        let layout = layout.as_ref();
+        let lock = OciLayout::lock_shared(layout)?;
+        lock.read_index()?.storage_report(|descriptor| {
+            let blob = layout.join(blob_path(descriptor.digest()));
+            fs::read(&blob).map_err(file_io_error(&blob))
+        })
+    }
+}
+
+/// An image manifest descriptor with the digests and sizes of its manifest,
+/// config and layers.
+type ImageBlobs = (Descriptor, Vec<(Digest, u64)>);
+
+fn collect_images<F>(
+    manifests: &[Descriptor],
+    fetch: &mut F,
+    images: &mut Vec<ImageBlobs>,
+    seen: &mut HashSet<Digest>,
+    depth: usize,
+) -> Result<()>
+where
+    F: FnMut(&Descriptor) -> Result<Vec<u8>>,
+{
+    if depth > MAX_INDEX_DEPTH {
+        return Err(oci_error(format!(
+            "indexes are nested deeper than {MAX_INDEX_DEPTH} levels"
+        )));
+    }
+    for descriptor in manifests {
+        // Tags of the same manifest and manifests listed by several indexes
+        // would count it twice.
+        if !seen.insert(descriptor.digest().clone()) {
+            continue;
+        }
+        match descriptor.media_type() {
+            MediaType::ImageManifest => {}
+            MediaType::ImageIndex => {
+                let index = ImageIndex::from_reader(&fetch(descriptor)?[..])?;
+                collect_images(index.manifests(), fetch, images, seen, depth + 1)?;
+                continue;
+            }
+            MediaType::Other(media_type) if media_type == DOCKER_MANIFEST => {}
+            MediaType::Other(media_type) if media_type == DOCKER_MANIFEST_LIST => {
+                let index = ImageIndex::from_reader(&fetch(descriptor)?[..])?;
+                collect_images(index.manifests(), fetch, images, seen, depth + 1)?;
+                continue;
+            }
+            _ => continue,
+        }
+
+        let manifest = ImageManifest::from_reader(&fetch(descriptor)?[..])?;
+        let blobs = [descriptor, manifest.config()]
+            .into_iter()
+            .chain(manifest.layers())
+            .map(|blob| Ok((blob.digest().clone(), blob.size()?)))
+            .collect::<Result<_>>()?;
+        images.push((descriptor.clone(), blobs));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::{
+        v1::testing::{digest, manifest_blob},
+        Arch, ImageIndexBuilder, Os, SCHEMA_VERSION,
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn storage_report() -> Result<()> {
+        let mut blobs: HashMap<Digest, Vec<u8>> = HashMap::new();
+        let mut add_manifest = |manifest: char, config: char, layers: &[(char, u64)]| {
+            let (descriptor, content) = manifest_blob(
+                manifest,
+                Descriptor::new(MediaType::ImageConfig, 10, digest(config)),
+                layers
+                    .iter()
+                    .map(|(c, size)| Descriptor::new(MediaType::ImageLayerGzip, *size, digest(*c)))
+                    .collect(),
+            );
+            blobs.insert(descriptor.digest().clone(), content);
+            descriptor
+        };
+
+        let mut amd64 = add_manifest('1', 'a', &[('b', 1000), ('c', 200)]);
+        amd64.set_platform(Some(Platform::from_str("linux/amd64")?));
+        let mut arm64 = add_manifest('2', 'd', &[('b', 1000), ('e', 300)]);
+        arm64.set_platform(Some(Platform::from_str("linux/arm64/v8")?));
+        let (amd64_size, arm64_size) = (amd64.size()?, arm64.size()?);
+        let nested = ImageIndexBuilder::default()
+            .schema_version(SCHEMA_VERSION)
+            .manifests(vec![arm64])
+            .build()?
+            .to_string()?;
+        let nested_descriptor =
+            Descriptor::new(MediaType::ImageIndex, nested.len() as u64, digest('3'));
+        blobs.insert(nested_descriptor.digest().clone(), nested.into_bytes());
+        let mut tagged = amd64.clone();
+        tagged.set_ref_name("latest");
+        let artifact = Descriptor::new(
+            MediaType::Other("application/x-sbom".into()),
+            5,
+            digest('4'),
+        );
+
+        let index = ImageIndexBuilder::default()
+            .schema_version(SCHEMA_VERSION)
+            .manifests(vec![amd64, tagged, nested_descriptor, artifact])
+            .build()?;
+        let report = index.storage_report(|descriptor| {
+            blobs
+                .get(descriptor.digest())
+                .cloned()
+                .ok_or_else(|| oci_error("blob not found"))
+        })?;
+
+        assert_eq!(report.images().len(), 2);
+        let amd64 = &report.images()[0];
+        assert_eq!(amd64.manifest(), &digest('1'));
+        assert_eq!(amd64.size(), amd64_size + 10 + 1200);
+        assert_eq!(amd64.shared_size(), 1000);
+        assert_eq!(amd64.layers(), 2);
+        assert_eq!(
+            report.images()[1]
+                .platform()
+                .as_ref()
+                .map(|p| p.architecture()),
+            Some(&Arch::ARM64)
+        );
+        assert_eq!(report.total_size(), amd64_size + arm64_size + 2520);
+        assert_eq!(report.unique_size(), report.total_size() - 1000);
+        assert_eq!(report.savings(), 1000);
+        assert_eq!(
+            report.platform_size(&Platform::from_str("linux/arm64/v8")?),
+            arm64_size + 1310
+        );
+        assert_eq!(report.duplicate_blobs().len(), 1);
+        let duplicate = &report.duplicate_blobs()[0];
+        assert_eq!(duplicate.digest(), &digest('b'));
+        assert_eq!((duplicate.references(), duplicate.wasted_size()), (2, 1000));
+        assert_eq!(
+            report.images()[0].platform().as_ref().unwrap().os(),
+            &Os::Linux
+        );
+
+        let failing = index.storage_report(|_| Err(oci_error("unavailable")));
+        assert!(failing.is_err());
+
+        let huge = DuplicateBlob {
+            digest: digest('b'),
+            size: u64::MAX,
+            references: 3,
+        };
+        assert_eq!(huge.wasted_size(), u64::MAX);
+        Ok(())
+    }
+}
@@ -0,0 +1,432 @@
+//! A minimal reader and writer of the tar archives used for image layers and
+//! `docker save` archives.
+
+#[cfg(any(feature = "docker-archive", test))]
+use std::io::Write;
+use std::io::{self, Read};
+
+const BLOCK_SIZE: usize = 512;
+
+/// The maximum size of the content of a PAX or GNU long name header, which
+/// is read into memory.
+const MAX_EXTENDED_HEADER_SIZE: u64 = 1 << 20;
+
+/// The type of a [TarEntry].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TarEntryKind {
+    /// A regular file, its content can be read from the [TarReader].
+    File,
+    /// A directory.
+    Directory,
+    /// A symbolic link to the contained target.
+    Symlink(String),
+    /// A hard link to the contained path of an earlier entry.
+    Hardlink(String),
+    /// Any other entry, like device nodes or fifos.
+    Other(u8),
+}
+
+/// The header of an entry of a tar archive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TarEntry {
+    /// The path of the entry, long paths of PAX and GNU headers are resolved.
+    pub(crate) path: String,
+    /// The type of the entry.
+    pub(crate) kind: TarEntryKind,
+    /// The permission bits of the entry.
+    pub(crate) mode: u32,
+    /// The size of the content of the entry.
+    pub(crate) size: u64,
+}
+
+/// Reads the entries of a tar archive. The content of the current entry is
+/// read through the [Read] implementation of the reader.
+pub(crate) struct TarReader<R> {
+    reader: R,
+    remaining: u64,
+    padding: u64,
+}
+
+impl<R: Read> TarReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            remaining: 0,
+            padding: 0,
+        }
+    }
+
+    /// Advance to the next entry, skipping any unread content of the current
+    /// one. Returns `None` at the end of the archive.
+    pub(crate) fn next_entry(&mut self) -> io::Result<Option<TarEntry>> {
+        let mut long_path = None;
+        let mut long_link = None;
+        loop {
+            self.skip_remaining()?;
+            let mut header = [0u8; BLOCK_SIZE];
+            if !read_block(&mut self.reader, &mut header)? || header.iter().all(|b| *b == 0) {
+                return Ok(None);
+            }
+
+            let size = parse_number(&header[124..136])?;
+            self.remaining = size;
+            self.padding = padding(size);
+
+            let typeflag = header[156];
+            match typeflag {
+                b'x' => {
+                    for (key, value) in parse_pax(&self.read_content()?) {
+                        match key.as_str() {
+                            "path" => long_path = Some(value),
+                            "linkpath" => long_link = Some(value),
+                            _ => {}
+                        }
+                    }
+                    continue;
+                }
+                b'L' => {
+                    long_path = Some(c_string(&self.read_content()?));
+                    continue;
+                }
+                b'K' => {
+                    long_link = Some(c_string(&self.read_content()?));
+                    continue;
+                }
+                b'g' => continue,
+                _ => {}
+            }
+
+            let path = long_path.take().unwrap_or_else(|| {
+                let name = c_string(&header[0..100]);
+                let prefix = c_string(&header[345..500]);
+                if &header[257..263] == b"ustar\0" && !prefix.is_empty() {
+                    format!("{prefix}/{name}")
+                } else {
+                    name
+                }
+            });
+            let link = long_link
+                .take()
+                .unwrap_or_else(|| c_string(&header[157..257]));
+            let kind = match typeflag {
+                b'0' | 0 | b'7' => TarEntryKind::File,
+                b'5' => TarEntryKind::Directory,
+                b'2' => TarEntryKind::Symlink(link),
+                b'1' => TarEntryKind::Hardlink(link),
+                other => TarEntryKind::Other(other),
+            };
+            if kind != TarEntryKind::File {
+                self.skip_remaining()?;
+            }
+
+            return Ok(Some(TarEntry {
+                path,
+                kind,
+                mode: parse_number(&header[100..108])? as u32,
+                size,
+            }));
+        }
+    }
+
+    fn read_content(&mut self) -> io::Result<Vec<u8>> {
+        if self.remaining > MAX_EXTENDED_HEADER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "extended header of {} bytes exceeds the limit of {MAX_EXTENDED_HEADER_SIZE} bytes",
+                    self.remaining
+                ),
+            ));
+        }
+        let mut content = Vec::new();
+        self.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    fn skip_remaining(&mut self) -> io::Result<()> {
+        let skip = self.remaining.checked_add(self.padding).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("entry size {} exceeds the tar limits", self.remaining),
+            )
+        })?;
+        io::copy(&mut (&mut self.reader).take(skip), &mut io::sink())?;
+        self.remaining = 0;
+        self.padding = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for TarReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let max = buf
+            .len()
+            .min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        if max == 0 {
+            return Ok(0);
+        }
+        let n = self.reader.read(&mut buf[..max])?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Writes regular files and directories into a tar archive.
+#[cfg(any(feature = "docker-archive", test))]
+pub(crate) struct TarWriter<W> {
+    writer: W,
+}
+
+#[cfg(any(feature = "docker-archive", test))]
+impl<W: Write> TarWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Append a regular file with `size` bytes read from `content`.
+    pub(crate) fn append_file<R: Read>(
+        &mut self,
+        path: &str,
+        size: u64,
+        content: R,
+    ) -> io::Result<()> {
+        self.write_header(path, b'0', 0o644, size)?;
+        let copied = io::copy(&mut content.take(size), &mut self.writer)?;
+        if copied != size {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.writer
+            .write_all(&[0; BLOCK_SIZE][..padding(size) as usize])
+    }
+
+    /// Append a directory.
+    pub(crate) fn append_dir(&mut self, path: &str) -> io::Result<()> {
+        self.write_header(&format!("{}/", path.trim_end_matches('/')), b'5', 0o755, 0)
+    }
+
+    /// Append a symbolic link to `target`.
+    #[cfg(all(test, feature = "layer-unpack"))]
+    pub(crate) fn append_symlink(&mut self, path: &str, target: &str) -> io::Result<()> {
+        self.write_raw_header(path, b'2', 0o777, 0, target)
+    }
+
+    /// Write the end of archive marker and return the inner writer.
+    pub(crate) fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(&[0; 2 * BLOCK_SIZE])?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_header(&mut self, path: &str, typeflag: u8, mode: u32, size: u64) -> io::Result<()> {
+        if path.len() > 100 {
+            let mut long_path = path.as_bytes().to_vec();
+            long_path.push(0);
+            self.write_raw_header("././@LongLink", b'L', 0, long_path.len() as u64, "")?;
+            self.writer.write_all(&long_path)?;
+            self.writer
+                .write_all(&[0; BLOCK_SIZE][..padding(long_path.len() as u64) as usize])?;
+        }
+        self.write_raw_header(path, typeflag, mode, size, "")
+    }
+
+    fn write_raw_header(
+        &mut self,
+        path: &str,
+        typeflag: u8,
+        mode: u32,
+        size: u64,
+        link: &str,
+    ) -> io::Result<()> {
+        let mut header = [0u8; BLOCK_SIZE];
+        let name = path.as_bytes();
+        let name = &name[..name.len().min(100)];
+        header[..name.len()].copy_from_slice(name);
+        let link = link.as_bytes();
+        let link = &link[..link.len().min(100)];
+        header[157..157 + link.len()].copy_from_slice(link);
+        write_number(&mut header[100..108], u64::from(mode));
+        write_number(&mut header[108..116], 0);
+        write_number(&mut header[116..124], 0);
+        write_number(&mut header[124..136], size);
+        write_number(&mut header[136..148], 0);
+        header[156] = typeflag;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+
+        header[148..156].fill(b' ');
+        let checksum: u64 = header.iter().map(|b| u64::from(*b)).sum();
+        write_number(&mut header[148..155], checksum);
+        self.writer.write_all(&header)
+    }
+}
+
+/// Read a full block, returns false if the reader is at its end.
+fn read_block<R: Read>(reader: &mut R, block: &mut [u8; BLOCK_SIZE]) -> io::Result<bool> {
+    let mut read = 0;
+    while read < BLOCK_SIZE {
+        match reader.read(&mut block[read..])? {
+            0 if read == 0 => return Ok(false),
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            n => read += n,
+        }
+    }
+    Ok(true)
+}
+
+fn padding(size: u64) -> u64 {
+    (BLOCK_SIZE as u64 - size % BLOCK_SIZE as u64) % BLOCK_SIZE as u64
+}
+
+fn c_string(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Parse a numeric header field, either octal or in the base-256 encoding
+/// used for large values.
+fn parse_number(field: &[u8]) -> io::Result<u64> {
+    if field[0] & 0x80 != 0 {
+        let value = field[1..]
+            .iter()
+            .fold(u64::from(field[0] & 0x7f), |acc, b| {
+                (acc << 8) | u64::from(*b)
+            });
+        return Ok(value);
+    }
+    let text = c_string(field);
+    let text = text.trim_matches(|c: char| c == ' ' || c == '\0');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Write a numeric header field as NUL terminated octal number, or in the
+/// base-256 encoding if `value` does not fit, like sizes of 8 GiB or more.
+#[cfg(any(feature = "docker-archive", test))]
+fn write_number(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let text = format!("{value:0digits$o}");
+    if text.len() > digits {
+        field.fill(0);
+        let bytes = value.to_be_bytes();
+        let start = field.len() - bytes.len();
+        field[start..].copy_from_slice(&bytes);
+        field[0] |= 0x80;
+        return;
+    }
+    field[..digits].copy_from_slice(text.as_bytes());
+    field[digits] = 0;
+}
+
+/// Parse the `<length> <key>=<value>\n` records of a PAX extended header.
+fn parse_pax(content: &[u8]) -> Vec<(String, String)> {
+    let mut records = Vec::new();
+    let mut rest = content;
+    while let Some(space) = rest.iter().position(|b| *b == b' ') {
+        let Some(len) = std::str::from_utf8(&rest[..space])
+            .ok()
+            .and_then(|len| len.parse::<usize>().ok())
+            .filter(|len| *len > space && *len <= rest.len())
+        else {
+            break;
+        };
+        let record = String::from_utf8_lossy(&rest[space + 1..len]);
+        if let Some((key, value)) = record.trim_end_matches('\n').split_once('=') {
+            records.push((key.to_string(), value.to_string()));
+        }
+        rest = &rest[len..];
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tar_round_trip() -> io::Result<()> {
+        let long_path = format!("{}/file", "a".repeat(120));
+        let mut writer = TarWriter::new(Vec::new());
+        writer.append_dir("dir")?;
+        writer.append_file("dir/file", 5, &b"hello"[..])?;
+        writer.append_file(&long_path, 0, io::empty())?;
+        let archive = writer.finish()?;
+
+        let mut reader = TarReader::new(&archive[..]);
+        let dir = reader.next_entry()?.expect("directory entry");
+        assert_eq!(dir.path, "dir/");
+        assert_eq!(dir.kind, TarEntryKind::Directory);
+
+        let file = reader.next_entry()?.expect("file entry");
+        assert_eq!(
+            (file.path.as_str(), file.size, file.mode),
+            ("dir/file", 5, 0o644)
+        );
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        assert_eq!(content, "hello");
+
+        assert_eq!(reader.next_entry()?.expect("long entry").path, long_path);
+        assert!(reader.next_entry()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn tar_extended_header_limit() -> io::Result<()> {
+        for typeflag in [b'x', b'L', b'K'] {
+            let mut writer = TarWriter::new(Vec::new());
+            writer.write_raw_header("header", typeflag, 0, MAX_EXTENDED_HEADER_SIZE + 1, "")?;
+            let archive = writer.finish()?;
+            let err = TarReader::new(&archive[..]).next_entry().unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn tar_pax_headers() {
+        let records = parse_pax(b"30 path=a/very/long/path/name\n19 linkpath=target\n");
+        assert_eq!(
+            records,
+            [
+                ("path".to_string(), "a/very/long/path/name".to_string()),
+                ("linkpath".to_string(), "target".to_string())
+            ]
+        );
+        assert_eq!(parse_number(b"0000644\0").unwrap(), 0o644);
+        assert_eq!(
+            parse_number(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0]).unwrap(),
+            256
+        );
+    }
+
+    #[test]
+    fn tar_large_sizes() -> io::Result<()> {
+        for size in [0o77777777777, 8 << 30, u64::MAX] {
+            let mut field = [0u8; 12];
+            write_number(&mut field, size);
+            assert_eq!(parse_number(&field)?, size);
+        }
+
+        let size = 8 << 30;
+        let mut writer = TarWriter::new(Vec::new());
+        writer.write_raw_header("large", b'0', 0o644, size, "")?;
+        let archive = writer.finish()?;
+        let mut reader = TarReader::new(&archive[..]);
+        assert_eq!(reader.next_entry()?.expect("large entry").size, size);
+
+        // The padding of a crafted maximal size overflows the entry size.
+        let mut writer = TarWriter::new(Vec::new());
+        writer.write_raw_header("crafted", b'0', 0o644, u64::MAX, "")?;
+        let archive = writer.finish()?;
+        let mut reader = TarReader::new(&archive[..]);
+        assert_eq!(reader.next_entry()?.expect("crafted entry").size, u64::MAX);
+        let err = reader.next_entry().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+}
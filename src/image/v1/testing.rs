@@ -0,0 +1,39 @@
+//! Helpers shared by the tests of the image modules.
+
+use super::{blob_path, Descriptor, Digest, ImageManifestBuilder, MediaType, SCHEMA_VERSION};
+use std::{fs, path::Path};
+
+/// Returns the sha256 digest whose encoded part repeats `c`.
+pub(crate) fn digest(c: char) -> Digest {
+    Digest::try_from(format!("sha256:{}", c.to_string().repeat(64))).expect("valid digest")
+}
+
+/// Returns the content of an image manifest with `config` and `layers` and
+/// its descriptor with the digest [digest]`(manifest)`.
+pub(crate) fn manifest_blob(
+    manifest: char,
+    config: Descriptor,
+    layers: Vec<Descriptor>,
+) -> (Descriptor, Vec<u8>) {
+    let content = ImageManifestBuilder::default()
+        .schema_version(SCHEMA_VERSION)
+        .config(config)
+        .layers(layers)
+        .build()
+        .expect("build manifest")
+        .to_string()
+        .expect("serialize manifest");
+    let descriptor = Descriptor::new(
+        MediaType::ImageManifest,
+        content.len() as u64,
+        digest(manifest),
+    );
+    (descriptor, content.into_bytes())
+}
+
+/// Writes the blob `content` with `digest` to the image layout `layout`.
+pub(crate) fn write_blob(layout: &Path, digest: &Digest, content: &[u8]) {
+    let path = layout.join(blob_path(digest));
+    fs::create_dir_all(path.parent().unwrap()).expect("create blobs dir");
+    fs::write(path, content).expect("write blob");
+}
@@ -0,0 +1,305 @@
+//! Applying layer tarballs onto a root filesystem directory.
+
+use super::{
+    tar::{TarEntryKind, TarReader},
+    Digest, Sha256Digest,
+};
+use crate::{
+    error::{file_io_error, oci_error, Result},
+    rootfs::resolve,
+    sha256::Sha256,
+};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+    str::FromStr,
+};
+
+/// The prefix of the name of a whiteout file, which marks the file without
+/// the prefix as removed.
+const WHITEOUT_PREFIX: &str = ".wh.";
+
+/// The name of the whiteout file which marks its directory as opaque, hiding
+/// all content of the lower layers.
+const WHITEOUT_OPAQUE: &str = ".wh..wh..opq";
+
+/// Apply the uncompressed layer tarball `layer` onto the root filesystem
+/// directory `rootfs`, which is created if it does not exist, and return the
+/// DiffID of the layer, the digest of the uncompressed tarball.
+///
+/// Whiteout files remove the named file of a lower layer and opaque
+/// directories remove all content of lower layers. Paths are resolved inside
+/// of `rootfs`, so that neither `..` components nor symbolic links of the
+/// layer can write outside of it. File contents, directories, symbolic and
+/// hard links and permission bits are applied, while ownership, timestamps,
+/// extended attributes, device nodes and fifos are not.
+///
+/// Compressed layers have to be decompressed by the caller, e.g. by wrapping
+/// `layer` in a gzip or zstd decoder.
+/// # Errors
+/// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+/// if the layer is compressed, or an [OciSpecError::Io](crate::OciSpecError::Io)
+/// or [OciSpecError::FileIo](crate::OciSpecError::FileIo) if the layer cannot
+/// be read or applied.
+/// # Example
+/// ``` no_run
+/// use oci_spec::image::apply_layer;
+/// use std::fs::File;
+///
+/// let layer = File::open("layer.tar").unwrap();
+/// let diff_id = apply_layer(layer, "rootfs").unwrap();
+/// ```
+pub fn apply_layer<R: Read, P: AsRef<Path>>(layer: R, rootfs: P) -> Result<Digest> {
+    let rootfs = rootfs.as_ref();
+    fs::create_dir_all(rootfs).map_err(file_io_error(rootfs))?;
+
+    let mut reader = HashingReader {
+        reader: layer,
+        hasher: Sha256::default(),
+    };
+    let mut magic = Vec::new();
+    (&mut reader).take(4).read_to_end(&mut magic)?;
+    if magic.starts_with(&[0x1f, 0x8b]) || magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        return Err(oci_error(
+            "layer is compressed, decompress it before applying",
+        ));
+    }
+
+    let mut stream = io::Cursor::new(magic).chain(&mut reader);
+    let mut archive = TarReader::new(&mut stream);
+    let mut applied = HashSet::new();
+    while let Some(entry) = archive.next_entry()? {
+        let Some(path) = clean(&entry.path) else {
+            continue;
+        };
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", &path));
+        let dir = resolve(rootfs, parent)?;
+        if name == WHITEOUT_OPAQUE {
+            clear_opaque(&dir, parent, &applied)?;
+            continue;
+        }
+        if let Some(hidden) = name.strip_prefix(WHITEOUT_PREFIX) {
+            // The hidden name must be a single component of `dir`, `.wh..`
+            // would remove `dir` itself and `.wh...` its parent.
+            if matches!(hidden, "" | "." | "..") || hidden.contains('/') {
+                return Err(oci_error(format!("invalid whiteout file {path}")));
+            }
+            remove(&dir.join(hidden))?;
+            continue;
+        }
+
+        fs::create_dir_all(&dir).map_err(file_io_error(&dir))?;
+        let target = dir.join(name);
+        match entry.kind {
+            TarEntryKind::Directory => {
+                replace(&target, true)?;
+                if !target.is_dir() {
+                    fs::create_dir(&target).map_err(file_io_error(&target))?;
+                }
+                set_mode(&target, entry.mode)?;
+            }
+            TarEntryKind::File => {
+                replace(&target, false)?;
+                let mut file = File::create(&target).map_err(file_io_error(&target))?;
+                io::copy(&mut archive, &mut file).map_err(file_io_error(&target))?;
+                set_mode(&target, entry.mode)?;
+            }
+            TarEntryKind::Symlink(link) => {
+                replace(&target, false)?;
+                symlink(&link, &target)?;
+            }
+            TarEntryKind::Hardlink(link) => {
+                let link = clean(&link).ok_or_else(|| oci_error("hard link to the root"))?;
+                let (link_parent, link_name) = link.rsplit_once('/').unwrap_or(("", &link));
+                let source = resolve(rootfs, link_parent)?.join(link_name);
+                replace(&target, false)?;
+                fs::hard_link(&source, &target).map_err(file_io_error(&target))?;
+            }
+            TarEntryKind::Other(_) => continue,
+        }
+        applied.insert(path);
+    }
+
+    // Hash the end of archive marker and any trailing padding.
+    io::copy(&mut stream, &mut io::sink())?;
+    let diff_id = reader.hasher.finalize_hex();
+    Ok(Sha256Digest::from_str(&diff_id)?.into())
+}
+
+/// Passes the read data through to a [Sha256] hasher.
+struct HashingReader<R> {
+    reader: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Normalize the path of an entry relative to the root of the layer, `None`
+/// for the root itself. `..` components cannot leave the root.
+fn clean(path: &str) -> Option<String> {
+    let mut components = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    (!components.is_empty()).then(|| components.join("/"))
+}
+
+/// Remove all entries of the directory `dir` at the layer path `parent`
+/// which were not applied by the current layer.
+fn clear_opaque(dir: &Path, parent: &str, applied: &HashSet<String>) -> Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(file_io_error(dir)(err)),
+    };
+    for entry in entries {
+        let entry = entry.map_err(file_io_error(dir))?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let path = match parent {
+            "" => name.into_owned(),
+            parent => format!("{parent}/{name}"),
+        };
+        if !applied.contains(&path) {
+            remove(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove `path` and its content if it is a directory, without following
+/// symbolic links.
+fn remove(path: &Path) -> Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+    .map_err(file_io_error(path))
+}
+
+/// Remove an existing entry at `path` which the new entry replaces, existing
+/// directories are kept if the new entry is a directory as well.
+fn replace(path: &Path, dir: bool) -> Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if dir && metadata.is_dir() => Ok(()),
+        _ => remove(path),
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777))
+        .map_err(file_io_error(path))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn symlink(link: &str, path: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(link, path).map_err(file_io_error(path))
+}
+
+#[cfg(not(unix))]
+fn symlink(_link: &str, path: &Path) -> Result<()> {
+    Err(oci_error(format!(
+        "cannot create symbolic link {}: not supported on this platform",
+        path.display()
+    )))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::image::v1::tar::TarWriter;
+
+    fn layer(build: impl FnOnce(&mut TarWriter<Vec<u8>>) -> io::Result<()>) -> Vec<u8> {
+        let mut writer = TarWriter::new(Vec::new());
+        build(&mut writer).expect("write layer");
+        writer.finish().expect("finish layer")
+    }
+
+    #[test]
+    fn apply_layers() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let rootfs = tmp.path().join("rootfs");
+
+        let base = layer(|w| {
+            w.append_dir("etc")?;
+            w.append_file("etc/a", 1, &b"a"[..])?;
+            w.append_file("etc/b", 1, &b"b"[..])?;
+            w.append_file("var/x", 1, &b"x"[..])?;
+            w.append_dir("usr/lib")?;
+            w.append_symlink("lib", "usr/lib")?;
+            w.append_symlink("escape", "/../../")
+        });
+        let diff_id = apply_layer(&base[..], &rootfs)?;
+        assert_eq!(diff_id.digest(), Sha256::digest_hex(&base));
+        assert_eq!(fs::read_to_string(rootfs.join("etc/a"))?, "a");
+        assert_eq!(fs::read_to_string(rootfs.join("var/x"))?, "x");
+
+        let upper = layer(|w| {
+            w.append_file("etc/.wh.a", 0, io::empty())?;
+            w.append_file("var/y", 1, &b"y"[..])?;
+            w.append_file("var/.wh..wh..opq", 0, io::empty())?;
+            w.append_file("lib/libc.so", 4, &b"libc"[..])?;
+            w.append_file("../../outside", 1, &b"o"[..])?;
+            w.append_file("escape/etc/passwd", 4, &b"root"[..])
+        });
+        apply_layer(&upper[..], &rootfs)?;
+        assert!(!rootfs.join("etc/a").exists());
+        assert!(rootfs.join("etc/b").exists());
+        assert!(!rootfs.join("var/x").exists());
+        assert!(rootfs.join("var/y").exists());
+        assert_eq!(fs::read_to_string(rootfs.join("usr/lib/libc.so"))?, "libc");
+        assert!(rootfs.join("outside").exists());
+        assert!(!tmp.path().join("outside").exists());
+        assert_eq!(fs::read_to_string(rootfs.join("etc/passwd"))?, "root");
+
+        let compressed = [0x1f, 0x8b, 0x08, 0x00];
+        assert!(apply_layer(&compressed[..], &rootfs).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reject_invalid_whiteouts() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let rootfs = tmp.path().join("rootfs");
+        let base = layer(|w| w.append_file("etc/a", 1, &b"a"[..]));
+        apply_layer(&base[..], &rootfs)?;
+
+        for whiteout in [
+            ".wh..",
+            ".wh...",
+            ".wh.",
+            "etc/.wh..",
+            "etc/.wh...",
+            "etc/.wh.",
+        ] {
+            let invalid = layer(|w| w.append_file(whiteout, 0, io::empty()));
+            assert!(apply_layer(&invalid[..], &rootfs).is_err(), "{whiteout}");
+            assert!(rootfs.join("etc/a").exists(), "{whiteout}");
+        }
+        Ok(())
+    }
+}
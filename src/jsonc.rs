@@ -0,0 +1,100 @@
+//! Support for JSON with comments and trailing commas.
+
+use crate::error::Result;
+use serde::de::DeserializeOwned;
+use std::io::Read;
+
+/// Deserialize a JSON document from `reader` which may contain `//` and
+/// `/* */` comments and trailing commas in objects and arrays.
+pub(crate) fn from_reader<R: Read, T: DeserializeOwned>(mut reader: R) -> Result<T> {
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    Ok(serde_json::from_slice(&strip(input))?)
+}
+
+/// Replace comments and trailing commas of `input` by whitespace, keeping
+/// line breaks so that the positions of serde errors stay accurate.
+fn strip(mut input: Vec<u8>) -> Vec<u8> {
+    let mut i = 0;
+    let mut in_string = false;
+    let mut trailing_comma = None;
+    while i < input.len() {
+        let next = input.get(i + 1).copied();
+        match (input[i], next) {
+            (b'\\', _) if in_string => i += 1,
+            (b'"', _) => {
+                in_string = !in_string;
+                trailing_comma = None;
+            }
+            _ if in_string => {}
+            (b'/', Some(b'/')) => {
+                let end = input[i..]
+                    .iter()
+                    .position(|b| *b == b'\n')
+                    .map_or(input.len(), |len| i + len);
+                blank(&mut input[i..end]);
+                i = end;
+                continue;
+            }
+            (b'/', Some(b'*')) => {
+                let end = input[i + 2..]
+                    .windows(2)
+                    .position(|w| w == b"*/")
+                    .map_or(input.len(), |len| i + 2 + len + 2);
+                blank(&mut input[i..end]);
+                i = end;
+                continue;
+            }
+            (b',', _) => trailing_comma = Some(i),
+            (b'}' | b']', _) => {
+                if let Some(comma) = trailing_comma.take() {
+                    input[comma] = b' ';
+                }
+            }
+            (b, _) if b.is_ascii_whitespace() => {}
+            _ => trailing_comma = None,
+        }
+        i += 1;
+    }
+    input
+}
+
+fn blank(bytes: &mut [u8]) {
+    bytes
+        .iter_mut()
+        .filter(|b| **b != b'\n')
+        .for_each(|b| *b = b' ');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn strip_comments_and_trailing_commas() -> Result<()> {
+        let input = r#"{
+            // the version
+            "ociVersion": "1.0.2", /* inline */
+            "args": ["sh", "-c", "echo // not a comment, ]",],
+            "escaped": "\" /* still a string */",
+        }"#;
+        let value: Value = from_reader(input.as_bytes())?;
+        assert_eq!(
+            value,
+            json!({
+                "ociVersion": "1.0.2",
+                "args": ["sh", "-c", "echo // not a comment, ]"],
+                "escaped": "\" /* still a string */",
+            })
+        );
+
+        let err = from_reader::<_, Value>("{\n/* a\nb */ \"a\": }".as_bytes()).unwrap_err();
+        match err {
+            crate::OciSpecError::SerDe(err) => assert_eq!(err.line(), 3),
+            err => panic!("unexpected error {err}"),
+        }
+        assert!(from_reader::<_, Value>("[1,,]".as_bytes()).is_err());
+        Ok(())
+    }
+}
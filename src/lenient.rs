@@ -0,0 +1,373 @@
+//! Opt-in lenient deserialization for documents produced by non-conformant tools.
+//!
+//! Some producers deviate from the specifications in ways which are easy to
+//! recover from, for example by encoding numbers as strings. The functions of
+//! this module accept such documents by coercing the offending values into the
+//! expected types and report every coercion they made, so callers can decide
+//! whether to surface them.
+//!
+//! The following deviations are tolerated:
+//! - numbers encoded as strings, e.g. `"1024"`
+//! - booleans encoded as strings in any case, e.g. `"True"` or `"FALSE"`
+//! - `null` where an array is expected
+//!
+//! # Example
+//!
+//! ```
+//! use oci_spec::lenient::{self, CoercionKind};
+//! use oci_spec::runtime::LinuxPids;
+//!
+//! let (pids, coercions) = lenient::from_str::<LinuxPids>(r#"{"limit": "128"}"#).unwrap();
+//! assert_eq!(pids.limit(), 128);
+//! assert_eq!(coercions[0].path(), "limit");
+//! assert_eq!(coercions[0].kind(), CoercionKind::StringToNumber);
+//! ```
+
+use crate::error::Result;
+use serde::{
+    de::{
+        self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+use serde_json::{Map, Value};
+use std::{cell::RefCell, fmt, io::Read, path::Path};
+
+/// The kind of a [Coercion].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CoercionKind {
+    /// A string was converted into a number.
+    StringToNumber,
+    /// A string was converted into a boolean.
+    StringToBool,
+    /// A `null` was converted into an empty array.
+    NullToArray,
+}
+
+/// A value which had to be coerced into the expected type during lenient
+/// deserialization.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Coercion {
+    path: String,
+    kind: CoercionKind,
+}
+
+impl Coercion {
+    /// The path of the coerced value, using the serialized field names, e.g.
+    /// `linux.resources.memory.limit`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The kind of the coercion.
+    pub fn kind(&self) -> CoercionKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for Coercion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            CoercionKind::StringToNumber => "string converted to number",
+            CoercionKind::StringToBool => "string converted to boolean",
+            CoercionKind::NullToArray => "null converted to empty array",
+        };
+        write!(f, "{}: {what}", self.path)
+    }
+}
+
+/// Leniently deserialize a JSON value, returning the coercions which were made.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<(T, Vec<Coercion>)> {
+    let coercions = RefCell::new(Vec::new());
+    let item = T::deserialize(Lenient {
+        value,
+        path: String::new(),
+        coercions: &coercions,
+    })?;
+    Ok((item, coercions.into_inner()))
+}
+
+/// Leniently deserialize a JSON string, returning the coercions which were made.
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<(T, Vec<Coercion>)> {
+    from_value(serde_json::from_str(s)?)
+}
+
+/// Leniently deserialize JSON from a stream, returning the coercions which were made.
+pub fn from_reader<R: Read, T: DeserializeOwned>(reader: R) -> Result<(T, Vec<Coercion>)> {
+    from_value(serde_json::from_reader(reader)?)
+}
+
+/// Leniently deserialize a JSON file, returning the coercions which were made.
+pub fn from_file<P: AsRef<Path>, T: DeserializeOwned>(path: P) -> Result<(T, Vec<Coercion>)> {
+    from_value(crate::from_file(path)?)
+}
+
+/// Deserializer over a [Value] which coerces values into the requested type
+/// where possible.
+struct Lenient<'a> {
+    value: Value,
+    path: String,
+    coercions: &'a RefCell<Vec<Coercion>>,
+}
+
+impl<'a> Lenient<'a> {
+    fn child(&self, value: Value, path: String) -> Self {
+        Lenient {
+            value,
+            path,
+            coercions: self.coercions,
+        }
+    }
+
+    fn record(&self, kind: CoercionKind) {
+        self.coercions.borrow_mut().push(Coercion {
+            path: self.path.clone(),
+            kind,
+        });
+    }
+
+    fn deserialize_number<'de, V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        if let Value::String(s) = &self.value {
+            let s = s.trim();
+            if let Ok(n) = s.parse::<u64>() {
+                self.record(CoercionKind::StringToNumber);
+                return visitor.visit_u64(n);
+            }
+            if let Ok(n) = s.parse::<i64>() {
+                self.record(CoercionKind::StringToNumber);
+                return visitor.visit_i64(n);
+            }
+            if let Ok(n) = s.parse::<f64>() {
+                self.record(CoercionKind::StringToNumber);
+                return visitor.visit_f64(n);
+            }
+        }
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+                self.deserialize_number(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Lenient<'_> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        match self.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Number(n) => n.deserialize_any(visitor),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Array(items) => visitor.visit_seq(LenientSeq {
+                items: items.into_iter().enumerate(),
+                parent: Lenient {
+                    value: Value::Null,
+                    path: self.path,
+                    coercions: self.coercions,
+                },
+            }),
+            Value::Object(map) => visitor.visit_map(LenientMap {
+                entries: map.into_iter(),
+                value: None,
+                parent: Lenient {
+                    value: Value::Null,
+                    path: self.path,
+                    coercions: self.coercions,
+                },
+            }),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        if let Value::String(s) = &self.value {
+            for (text, b) in [("true", true), ("false", false)] {
+                if s.trim().eq_ignore_ascii_case(text) {
+                    self.record(CoercionKind::StringToBool);
+                    return visitor.visit_bool(b);
+                }
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
+    deserialize_number! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        if self.value.is_null() {
+            self.record(CoercionKind::NullToArray);
+            return visitor.visit_seq(LenientSeq {
+                items: Vec::new().into_iter().enumerate(),
+                parent: self,
+            });
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        match self.value {
+            Value::String(s) => visitor.visit_enum(s.into_deserializer()),
+            value => value.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct LenientSeq<'a> {
+    items: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+    parent: Lenient<'a>,
+}
+
+impl<'de> SeqAccess<'de> for LenientSeq<'_> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> serde_json::Result<Option<T::Value>> {
+        match self.items.next() {
+            Some((i, value)) => {
+                let path = format!("{}[{i}]", self.parent.path);
+                seed.deserialize(self.parent.child(value, path)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct LenientMap<'a> {
+    entries: <Map<String, Value> as IntoIterator>::IntoIter,
+    value: Option<(String, Value)>,
+    parent: Lenient<'a>,
+}
+
+impl<'de> MapAccess<'de> for LenientMap<'_> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> serde_json::Result<Option<K::Value>> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                let result = seed.deserialize(key.as_str().into_deserializer()).map(Some);
+                self.value = Some((key, value));
+                result
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> serde_json::Result<V::Value> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value is missing"))?;
+        let path = if self.parent.path.is_empty() {
+            key
+        } else {
+            format!("{}.{key}", self.parent.path)
+        };
+        seed.deserialize(self.parent.child(value, path))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+#[cfg(all(test, feature = "runtime"))]
+mod tests {
+    use super::*;
+    use crate::runtime::{LinuxMemory, Spec};
+
+    #[test]
+    fn lenient_coercions() {
+        let json = r#"{
+            "limit": "1024",
+            "swap": -1,
+            "disableOOMKiller": "True",
+            "useHierarchy": "FALSE"
+        }"#;
+        let (memory, coercions): (LinuxMemory, _) = from_str(json).unwrap();
+        assert_eq!(memory.limit(), Some(1024));
+        assert_eq!(memory.swap(), Some(-1));
+        assert_eq!(memory.disable_oom_killer(), Some(true));
+        assert_eq!(memory.use_hierarchy(), Some(false));
+        let paths: Vec<_> = coercions.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            paths,
+            [
+                "limit: string converted to number",
+                "disableOOMKiller: string converted to boolean",
+                "useHierarchy: string converted to boolean",
+            ]
+        );
+    }
+
+    #[test]
+    fn lenient_spec() {
+        let mut value = serde_json::to_value(Spec::default()).unwrap();
+        value["process"]["user"]["uid"] = "1000".into();
+        value["process"]["args"] = Value::Null;
+        let (spec, coercions): (Spec, _) = from_value(value).unwrap();
+        let process = spec.process().as_ref().unwrap();
+        assert_eq!(process.user().uid(), 1000);
+        assert_eq!(coercions.len(), 1);
+        assert_eq!(coercions[0].path(), "process.user.uid");
+    }
+
+    #[test]
+    fn lenient_null_array() {
+        let json = r#"{"names": null, "action": "SCMP_ACT_ALLOW"}"#;
+        let (syscall, coercions): (crate::runtime::LinuxSyscall, _) = from_str(json).unwrap();
+        assert!(syscall.names().is_empty());
+        assert_eq!(coercions[0].kind(), CoercionKind::NullToArray);
+    }
+
+    #[test]
+    fn lenient_still_rejects_invalid() {
+        assert!(from_str::<LinuxMemory>(r#"{"limit": "lots"}"#).is_err());
+    }
+}
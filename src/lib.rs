@@ -2,53 +2,92 @@
 #![doc = include_str!("../README.md")]
 #![allow(clippy::too_long_first_doc_paragraph)]
 
+#[cfg(any(feature = "image", feature = "runtime"))]
+mod annotations;
 #[cfg(feature = "distribution")]
 pub mod distribution;
 mod error;
+#[cfg(any(feature = "image", feature = "runtime"))]
+mod go_compat;
 #[cfg(feature = "image")]
 pub mod image;
+#[cfg(feature = "jsonc")]
+mod jsonc;
+pub mod lenient;
+#[cfg(any(feature = "image", feature = "runtime"))]
+mod parse;
+#[cfg(any(feature = "layer-unpack", feature = "user-lookup"))]
+mod rootfs;
 #[cfg(feature = "runtime")]
 pub mod runtime;
+#[cfg(any(
+    feature = "docker-archive",
+    feature = "layer-unpack",
+    feature = "runtime"
+))]
+mod sha256;
 
+use serde::de::DeserializeOwned;
+#[cfg(any(feature = "image", feature = "runtime"))]
+use serde::{Serialize, Serializer};
+#[cfg(any(feature = "image", feature = "runtime"))]
+use std::collections::{BTreeMap, HashMap};
+use std::{fs, path::Path};
+#[cfg(feature = "image")]
 use std::{
-    fs::{self, OpenOptions},
+    fs::OpenOptions,
     io::{Read, Write},
-    path::Path,
 };
 
-use serde::{de::DeserializeOwned, Serialize};
-
 pub use error::*;
+#[cfg(any(feature = "image", feature = "runtime"))]
+pub use parse::*;
 
 fn from_file<P: AsRef<Path>, T: DeserializeOwned>(path: P) -> Result<T> {
     let path = path.as_ref();
-    let manifest_file = std::io::BufReader::new(fs::File::open(path)?);
-    let manifest = serde_json::from_reader(manifest_file)?;
+    let manifest_file = std::io::BufReader::new(fs::File::open(path).map_err(file_io_error(path))?);
+    let manifest = serde_json::from_reader(manifest_file).map_err(file_serde_error(path))?;
     Ok(manifest)
 }
 
+#[cfg(feature = "image")]
 fn from_reader<R: Read, T: DeserializeOwned>(reader: R) -> Result<T> {
     let manifest = serde_json::from_reader(reader)?;
     Ok(manifest)
 }
 
+#[cfg(feature = "image")]
 fn to_file<P: AsRef<Path>, T: Serialize>(item: &T, path: P, pretty: bool) -> Result<()> {
     let path = path.as_ref();
     let file = OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
-        .open(path)?;
-    let file = std::io::BufWriter::new(file);
+        .open(path)
+        .map_err(file_io_error(path))?;
+    let mut file = std::io::BufWriter::new(file);
 
     match pretty {
-        true => serde_json::to_writer_pretty(file, item)?,
-        false => serde_json::to_writer(file, item)?,
+        true => serde_json::to_writer_pretty(&mut file, item),
+        false => serde_json::to_writer(&mut file, item),
     }
+    .map_err(file_serde_error(path))?;
+    file.flush().map_err(file_io_error(path))?;
 
     Ok(())
 }
 
+#[cfg(any(feature = "image", feature = "runtime"))]
+fn from_value<T: DeserializeOwned>(value: serde_json::Value) -> Result<T> {
+    Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(any(feature = "image", feature = "runtime"))]
+fn to_value<T: Serialize>(item: &T) -> Result<serde_json::Value> {
+    Ok(serde_json::to_value(item)?)
+}
+
+#[cfg(feature = "image")]
 fn to_writer<W: Write, T: Serialize>(item: &T, writer: &mut W, pretty: bool) -> Result<()> {
     match pretty {
         true => serde_json::to_writer_pretty(writer, item)?,
@@ -58,6 +97,7 @@ fn to_writer<W: Write, T: Serialize>(item: &T, writer: &mut W, pretty: bool) ->
     Ok(())
 }
 
+#[cfg(feature = "image")]
 fn to_string<T: Serialize>(item: &T, pretty: bool) -> Result<String> {
     Ok(match pretty {
         true => serde_json::to_string_pretty(item)?,
@@ -65,7 +105,25 @@ fn to_string<T: Serialize>(item: &T, pretty: bool) -> Result<String> {
     })
 }
 
+// Serializes an optional map with its keys in sorted order, so that the output is deterministic
+// across runs regardless of the iteration order of the map.
+#[cfg(any(feature = "image", feature = "runtime"))]
+fn serialize_sorted<S, K, V>(
+    map: &Option<HashMap<K, V>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Serialize + Ord,
+    V: Serialize,
+{
+    map.as_ref()
+        .map(|m| m.iter().collect::<BTreeMap<_, _>>())
+        .serialize(serializer)
+}
+
 // A generic helper for any Option containing a collection whose reference implements `IntoIterator` (e.g., Vec, HashMap).
+#[cfg(feature = "runtime")]
 fn is_none_or_empty<C>(opt: &Option<C>) -> bool
 where
     for<'a> &'a C: IntoIterator,
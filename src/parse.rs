@@ -0,0 +1,304 @@
+//! Limits for parsing untrusted documents.
+
+use crate::error::{
+    file_io_error, file_serde_error, FindingCode, OciSpecError, Result, ValidationError,
+    ValidationErrorKind,
+};
+use derive_builder::Builder;
+use getset::CopyGetters;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor,
+};
+use std::{borrow::Cow, cell::RefCell, fmt, fs, io::Read, path::Path};
+
+#[derive(Builder, Clone, Copy, CopyGetters, Debug, Default, Eq, PartialEq)]
+#[builder(
+    default,
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(error = "OciSpecError")
+)]
+#[getset(get_copy = "pub")]
+/// Limits applied when parsing a document, so that services handling untrusted
+/// configs and manifests can bound the memory used. Limits which are not set
+/// are not enforced. The nesting and length limits are checked while the
+/// input is read, before any part of the document is built in memory.
+///
+/// # Example
+/// ```
+/// use oci_spec::ParseOptionsBuilder;
+/// use oci_spec::image::ImageManifest;
+///
+/// let options = ParseOptionsBuilder::default()
+///     .max_bytes(4 * 1024 * 1024u64)
+///     .max_depth(2usize)
+///     .build()
+///     .unwrap();
+/// let json = r#"{"schemaVersion": 2, "layers": [[[]]]}"#;
+/// assert!(ImageManifest::from_reader_with_options(json.as_bytes(), &options).is_err());
+/// ```
+pub struct ParseOptions {
+    /// The maximum size of the input in bytes.
+    max_bytes: Option<u64>,
+
+    /// The maximum nesting depth of objects and arrays, the top-level value
+    /// has a depth of 1.
+    max_depth: Option<usize>,
+
+    /// The maximum number of elements of any array and of entries of any
+    /// object.
+    max_array_len: Option<usize>,
+}
+
+impl ParseOptions {
+    /// Returns true if no limit is set.
+    fn is_unlimited(&self) -> bool {
+        self.max_bytes.is_none() && self.max_depth.is_none() && self.max_array_len.is_none()
+    }
+}
+
+/// Checks the nesting depth and the lengths of the arrays and objects of a
+/// document while it is read, without building it in memory. The error of
+/// an exceeded limit is stored in `error`, as serde errors cannot carry it.
+struct LimitCheck<'a> {
+    options: &'a ParseOptions,
+    path: String,
+    depth: usize,
+    error: &'a RefCell<Option<OciSpecError>>,
+}
+
+impl<'a> LimitCheck<'a> {
+    fn nested(&self, path: String) -> Self {
+        Self {
+            options: self.options,
+            path,
+            depth: self.depth + 1,
+            error: self.error,
+        }
+    }
+
+    fn exceeded<E: de::Error>(&self, err: OciSpecError) -> E {
+        let message = err.to_string();
+        *self.error.borrow_mut() = Some(err);
+        E::custom(message)
+    }
+
+    fn check_depth<E: de::Error>(&self) -> std::result::Result<(), E> {
+        match self.options.max_depth.filter(|max| self.depth > *max) {
+            Some(max_depth) => Err(self.exceeded(limit_exceeded(
+                &self.path,
+                "nesting depth",
+                max_depth,
+                self.depth,
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn check_len<E: de::Error>(&self, len: usize) -> std::result::Result<(), E> {
+        match self.options.max_array_len.filter(|max| len > *max) {
+            Some(max_len) => Err(self.exceeded(limit_exceeded(
+                &self.path,
+                "number of elements",
+                max_len,
+                format!("more than {max_len}"),
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for LimitCheck<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for LimitCheck<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        self.check_depth()?;
+        let mut len = 0;
+        while seq
+            .next_element_seed(self.nested(format!("{}[{len}]", self.path)))?
+            .is_some()
+        {
+            len += 1;
+            self.check_len(len)?;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        self.check_depth()?;
+        let mut len = 0;
+        while let Some(key) = map.next_key::<Cow<'de, str>>()? {
+            let path = match self.path.as_str() {
+                "" => key.into_owned(),
+                path => format!("{path}.{key}"),
+            };
+            map.next_value_seed(self.nested(path))?;
+            len += 1;
+            self.check_len(len)?;
+        }
+        Ok(())
+    }
+}
+
+fn limit_exceeded(
+    path: &str,
+    limit: &str,
+    max: impl ToString,
+    actual: impl ToString,
+) -> OciSpecError {
+    let path = if path.is_empty() { "$" } else { path };
+    ValidationError::new(
+        ValidationErrorKind::OutOfRange,
+        path,
+        format!("{limit} exceeds the parse limit"),
+    )
+    .with_code(FindingCode::ParseLimitExceeded)
+    .with_expected(format!("at most {}", max.to_string()))
+    .with_actual(actual.to_string())
+    .into()
+}
+
+/// Parse `reader` as JSON document while enforcing the limits of `options`.
+pub(crate) fn from_reader_with_options<R: Read, T: DeserializeOwned>(
+    reader: R,
+    options: &ParseOptions,
+) -> Result<T> {
+    if options.is_unlimited() {
+        return Ok(serde_json::from_reader(reader)?);
+    }
+
+    let mut input = Vec::new();
+    match options.max_bytes {
+        Some(max_bytes) => {
+            reader
+                .take(max_bytes.saturating_add(1))
+                .read_to_end(&mut input)?;
+            if input.len() as u64 > max_bytes {
+                return Err(limit_exceeded(
+                    "",
+                    "input size",
+                    max_bytes,
+                    format!("more than {max_bytes}"),
+                ));
+            }
+        }
+        None => {
+            let mut reader = reader;
+            reader.read_to_end(&mut input)?;
+        }
+    }
+
+    // The limits are checked in a first pass over the input, so that no part
+    // of a document exceeding them is built in memory.
+    let error = RefCell::new(None);
+    let check = LimitCheck {
+        options,
+        path: String::new(),
+        depth: 1,
+        error: &error,
+    };
+    let mut deserializer = serde_json::Deserializer::from_slice(&input);
+    if let Err(err) = check.deserialize(&mut deserializer) {
+        return Err(error.into_inner().unwrap_or_else(|| err.into()));
+    }
+    Ok(serde_json::from_slice(&input)?)
+}
+
+/// Parse the JSON file at `path` while enforcing the limits of `options`.
+pub(crate) fn from_file_with_options<P: AsRef<Path>, T: DeserializeOwned>(
+    path: P,
+    options: &ParseOptions,
+) -> Result<T> {
+    let path = path.as_ref();
+    let file = std::io::BufReader::new(fs::File::open(path).map_err(file_io_error(path))?);
+    from_reader_with_options(file, options).map_err(|err| match err {
+        OciSpecError::SerDe(err) => file_serde_error(path)(err),
+        OciSpecError::Io(err) => file_io_error(path)(err),
+        err => err,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn parse(json: &str, options: ParseOptions) -> Result<Value> {
+        from_reader_with_options(json.as_bytes(), &options)
+    }
+
+    #[test]
+    fn parse_limits() -> Result<()> {
+        let json = r#"{"a": [1, 2, 3], "b": {"c": {}}}"#;
+        assert!(parse(json, ParseOptions::default()).is_ok());
+
+        let options = ParseOptionsBuilder::default().max_bytes(10u64).build()?;
+        assert!(parse(json, options).is_err());
+
+        let options = ParseOptionsBuilder::default().max_depth(3usize).build()?;
+        assert!(parse(json, options).is_ok());
+        let options = ParseOptionsBuilder::default().max_depth(2usize).build()?;
+        let err = parse(json, options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "b.c: nesting depth exceeds the parse limit (expected at most 2, got 3)"
+        );
+
+        let options = ParseOptionsBuilder::default()
+            .max_array_len(2usize)
+            .build()?;
+        let err = parse(json, options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a: number of elements exceeds the parse limit (expected at most 2, got more than 2)"
+        );
+
+        // The limits are enforced while reading, before the nesting exceeds
+        // the recursion limit of serde_json.
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        let options = ParseOptionsBuilder::default().max_depth(3usize).build()?;
+        let err = parse(&deep, options).unwrap_err();
+        assert!(err.to_string().starts_with("[0][0][0]: nesting depth"));
+        assert!(parse("[1, 2", options).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+//! Access to the files of a root filesystem which never leaves it.
+
+use crate::error::{file_io_error, oci_error, Result};
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// The maximum number of symbolic links followed while resolving a path.
+const MAX_SYMLINKS: usize = 255;
+
+/// Resolve `path` relative to `root` like `chroot` would, following symbolic
+/// links without ever leaving `root`.
+pub(crate) fn resolve(root: &Path, path: &str) -> Result<PathBuf> {
+    let mut resolved = PathBuf::new();
+    let mut pending: Vec<OsString> = path.split('/').rev().map(OsString::from).collect();
+    let mut links = 0;
+    while let Some(component) = pending.pop() {
+        if component.is_empty() || component == "." {
+            continue;
+        }
+        if component == ".." {
+            resolved.pop();
+            continue;
+        }
+
+        let candidate = resolved.join(&component);
+        let full = root.join(&candidate);
+        match fs::symlink_metadata(&full) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                links += 1;
+                if links > MAX_SYMLINKS {
+                    return Err(oci_error(format!(
+                        "too many levels of symbolic links resolving {path}"
+                    )));
+                }
+                let target = fs::read_link(&full).map_err(file_io_error(&full))?;
+                if target.is_absolute() {
+                    resolved = PathBuf::new();
+                }
+                for component in target.components().rev() {
+                    match component {
+                        Component::Normal(name) => pending.push(name.to_os_string()),
+                        Component::ParentDir => pending.push("..".into()),
+                        _ => {}
+                    }
+                }
+            }
+            _ => resolved = candidate,
+        }
+    }
+    Ok(root.join(resolved))
+}
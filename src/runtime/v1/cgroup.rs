@@ -0,0 +1,530 @@
+use super::{
+    CgroupVersion, LinuxBlockIo, LinuxCpu, LinuxDeviceCgroup, LinuxDeviceType, LinuxMemory,
+    LinuxResources,
+};
+use crate::error::{FindingCode, OciSpecError, ValidationError, ValidationErrorKind};
+
+/// The default CFS period in microseconds used if only a quota is set.
+const DEFAULT_CPU_PERIOD: u64 = 100_000;
+
+/// The device cgroup access bits for read, write and mknod.
+const DEVICE_READ: u8 = 1;
+const DEVICE_WRITE: u8 = 2;
+const DEVICE_MKNOD: u8 = 4;
+const DEVICE_ALL: u8 = DEVICE_READ | DEVICE_WRITE | DEVICE_MKNOD;
+
+/// Convert cgroup v1 `cpu.shares` (2-262144) into cgroup v2 `cpu.weight`
+/// (1-10000), using the formula documented by the runtime spec.
+pub fn convert_cpu_shares_to_cgroup_v2(shares: u64) -> u64 {
+    if shares == 0 {
+        return 0;
+    }
+    1 + (shares.saturating_sub(2) * 9999) / 262142
+}
+
+/// Convert a cgroup v1 `blkio.weight` (10-1000) into cgroup v2 `io.weight`
+/// (1-10000), using the formula documented by the runtime spec.
+pub fn convert_blkio_weight_to_cgroup_v2(weight: u16) -> u64 {
+    if weight == 0 {
+        return 0;
+    }
+    1 + (u64::from(weight).saturating_sub(10) * 9999) / 990
+}
+
+/// Convert the memory+swap limit of the spec into the swap only limit of the
+/// cgroup v2 `memory.swap.max` file.
+fn convert_memory_swap(limit: Option<i64>, swap: i64) -> Result<String, OciSpecError> {
+    let conflict = |message: &str| {
+        ValidationError::new(ValidationErrorKind::Conflict, "memory.swap", message)
+            .with_code(FindingCode::MemorySwapBelowLimit)
+            .with_actual(swap.to_string())
+    };
+    if swap == -1 {
+        return Ok("max".to_string());
+    }
+    match limit {
+        None | Some(0) | Some(-1) => {
+            Err(conflict("swap limit cannot be set without a memory limit").into())
+        }
+        Some(limit) if limit > swap => {
+            Err(conflict("memory+swap limit is lower than the memory limit")
+                .with_expected(format!("at least {limit}"))
+                .into())
+        }
+        Some(limit) => Ok((swap - limit).to_string()),
+    }
+}
+
+fn limit_value(limit: i64) -> String {
+    if limit < 0 {
+        "max".to_string()
+    } else {
+        limit.to_string()
+    }
+}
+
+impl LinuxResources {
+    /// Translate the resources into the cgroup v2 interface files and the
+    /// values to write into them, e.g. `("memory.max", "1048576")`.
+    ///
+    /// Settings without a cgroup v2 equivalent, like the swappiness or the
+    /// kernel memory limit, are skipped. Files which take one line per
+    /// device, like `io.max`, are returned once per device. The entries of
+    /// [unified](LinuxResources::unified) are returned last and take
+    /// precedence over the translated values.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] if the swap
+    /// limit cannot be translated because of the memory limit.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{LinuxCpuBuilder, LinuxResourcesBuilder};
+    ///
+    /// let resources = LinuxResourcesBuilder::default()
+    ///     .cpu(LinuxCpuBuilder::default().quota(50000i64).build().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// let files = resources.to_cgroup_v2().unwrap();
+    /// assert_eq!(files, [("cpu.max".to_string(), "50000 100000".to_string())]);
+    /// ```
+    pub fn to_cgroup_v2(&self) -> Result<Vec<(String, String)>, OciSpecError> {
+        let mut files = Vec::new();
+        let mut add = |name: &str, value: String| files.push((name.to_string(), value));
+
+        if let Some(memory) = self.memory() {
+            memory_to_cgroup_v2(memory, &mut add)?;
+        }
+        if let Some(cpu) = self.cpu() {
+            cpu_to_cgroup_v2(cpu, &mut add);
+        }
+        if let Some(pids) = self.pids() {
+            add("pids.max", limit_value(pids.limit()));
+        }
+        if let Some(block_io) = self.block_io() {
+            block_io_to_cgroup_v2(block_io, &mut add);
+        }
+        for limit in self.hugepage_limits().iter().flatten() {
+            add(
+                &format!("hugetlb.{}.max", limit.page_size()),
+                limit_value(limit.limit()),
+            );
+        }
+        let mut rdma: Vec<_> = self.rdma().iter().flatten().collect();
+        rdma.sort_by_key(|(device, _)| device.as_str());
+        for (device, limits) in rdma {
+            let mut value = device.clone();
+            if let Some(handles) = limits.hca_handles() {
+                value.push_str(&format!(" hca_handle={handles}"));
+            }
+            if let Some(objects) = limits.hca_objects() {
+                value.push_str(&format!(" hca_object={objects}"));
+            }
+            add("rdma.max", value);
+        }
+
+        let mut unified: Vec<_> = self.unified().iter().flatten().collect();
+        unified.sort();
+        for (name, value) in unified {
+            files.retain(|(file, _)| file != name);
+            files.push((name.clone(), value.clone()));
+        }
+
+        Ok(files)
+    }
+}
+
+fn memory_to_cgroup_v2(
+    memory: &LinuxMemory,
+    add: &mut impl FnMut(&str, String),
+) -> Result<(), OciSpecError> {
+    if let Some(limit) = memory.limit() {
+        add("memory.max", limit_value(limit));
+    }
+    if let Some(reservation) = memory.reservation() {
+        add("memory.low", limit_value(reservation));
+    }
+    if let Some(swap) = memory.swap().filter(|swap| *swap != 0) {
+        add(
+            "memory.swap.max",
+            convert_memory_swap(memory.limit(), swap)?,
+        );
+    }
+    Ok(())
+}
+
+fn cpu_to_cgroup_v2(cpu: &LinuxCpu, add: &mut impl FnMut(&str, String)) {
+    if let Some(shares) = cpu.shares().filter(|shares| *shares != 0) {
+        add(
+            "cpu.weight",
+            convert_cpu_shares_to_cgroup_v2(shares).to_string(),
+        );
+    }
+    if cpu.quota().is_some() || cpu.period().is_some() {
+        let quota = match cpu.quota() {
+            Some(quota) if quota > 0 => quota.to_string(),
+            _ => "max".to_string(),
+        };
+        let period = cpu.period().unwrap_or(DEFAULT_CPU_PERIOD);
+        add("cpu.max", format!("{quota} {period}"));
+    }
+    if let Some(burst) = cpu.burst() {
+        add("cpu.max.burst", burst.to_string());
+    }
+    if let Some(idle) = cpu.idle() {
+        add("cpu.idle", idle.to_string());
+    }
+    if let Some(cpus) = cpu.cpus().as_ref().filter(|cpus| !cpus.is_empty()) {
+        add("cpuset.cpus", cpus.clone());
+    }
+    if let Some(mems) = cpu.mems().as_ref().filter(|mems| !mems.is_empty()) {
+        add("cpuset.mems", mems.clone());
+    }
+}
+
+fn block_io_to_cgroup_v2(block_io: &LinuxBlockIo, add: &mut impl FnMut(&str, String)) {
+    if let Some(weight) = block_io.weight().filter(|weight| *weight != 0) {
+        add(
+            "io.weight",
+            format!("default {}", convert_blkio_weight_to_cgroup_v2(weight)),
+        );
+    }
+    for device in block_io.weight_device().iter().flatten() {
+        if let Some(weight) = device.weight() {
+            add(
+                "io.weight",
+                format!(
+                    "{}:{} {}",
+                    device.major(),
+                    device.minor(),
+                    convert_blkio_weight_to_cgroup_v2(weight)
+                ),
+            );
+        }
+    }
+
+    let throttles = [
+        ("rbps", block_io.throttle_read_bps_device()),
+        ("wbps", block_io.throttle_write_bps_device()),
+        ("riops", block_io.throttle_read_iops_device()),
+        ("wiops", block_io.throttle_write_iops_device()),
+    ];
+    for (key, devices) in throttles {
+        for device in devices.iter().flatten() {
+            add(
+                "io.max",
+                format!(
+                    "{}:{} {key}={}",
+                    device.major(),
+                    device.minor(),
+                    device.rate()
+                ),
+            );
+        }
+    }
+}
+
+/// Evaluate whether the device cgroup `rules` permit the `access`, a
+/// combination of `r`, `w` and `m`, to the device of type `typ` with the
+/// `major` and `minor` numbers, like the kernel would for a container using
+/// the cgroup hierarchy `version`.
+///
+/// On cgroup v1 the rules are applied in order onto a cgroup which allows all
+/// devices. A rule of type `a` resets the cgroup to allow or deny all
+/// devices, every other rule adds or removes an exception for exactly its
+/// type and numbers. An exception does not narrow a wildcard exception, so
+/// denying `c 1:3` after allowing `c *:*` on a denying cgroup has no effect.
+///
+/// On cgroup v2 the rules are evaluated like the eBPF programs of runtimes:
+/// the last matching rule decides and access is denied if no rule matches. An
+/// allow rule only matches if it grants the whole access, a deny rule matches
+/// if it denies any part of it.
+///
+/// Rules without a type, major or minor number match any and rules without
+/// access apply to `rwm`. Only block and character devices are governed by
+/// the device cgroup, access to other types is always permitted.
+/// # Example
+/// ```
+/// use oci_spec::runtime::{
+///     is_device_access_allowed, CgroupVersion, LinuxDeviceCgroupBuilder, LinuxDeviceType,
+/// };
+///
+/// let rules = [
+///     LinuxDeviceCgroupBuilder::default()
+///         .allow(false)
+///         .access("rwm")
+///         .build()
+///         .unwrap(),
+///     LinuxDeviceCgroupBuilder::default()
+///         .allow(true)
+///         .typ(LinuxDeviceType::C)
+///         .major(1)
+///         .minor(3)
+///         .access("rw")
+///         .build()
+///         .unwrap(),
+/// ];
+/// let null = |access| {
+///     is_device_access_allowed(&rules, CgroupVersion::V2, LinuxDeviceType::C, 1, 3, access)
+/// };
+/// assert!(null("rw"));
+/// assert!(!null("m"));
+/// ```
+pub fn is_device_access_allowed(
+    rules: &[LinuxDeviceCgroup],
+    version: CgroupVersion,
+    typ: LinuxDeviceType,
+    major: i64,
+    minor: i64,
+    access: &str,
+) -> bool {
+    let typ = match typ {
+        LinuxDeviceType::B => LinuxDeviceType::B,
+        LinuxDeviceType::C | LinuxDeviceType::U => LinuxDeviceType::C,
+        _ => return true,
+    };
+    let requested = device_access_bits(access);
+    if requested == 0 {
+        return true;
+    }
+
+    match version {
+        CgroupVersion::V1 => {
+            let mut default_allow = true;
+            let mut exceptions: Vec<DeviceException> = Vec::new();
+            for rule in rules {
+                let rule_type = match rule.typ() {
+                    None | Some(LinuxDeviceType::A) => {
+                        default_allow = rule.allow();
+                        exceptions.clear();
+                        continue;
+                    }
+                    Some(LinuxDeviceType::U) => LinuxDeviceType::C,
+                    Some(rule_type) => rule_type,
+                };
+                let bits = rule_access_bits(rule);
+                let existing = exceptions.iter_mut().find(|exception| {
+                    exception.typ == rule_type
+                        && exception.major == rule.major()
+                        && exception.minor == rule.minor()
+                });
+                match existing {
+                    // The rule agrees with the default and revokes the
+                    // exception.
+                    Some(exception) if rule.allow() == default_allow => exception.access &= !bits,
+                    None if rule.allow() == default_allow => {}
+                    Some(exception) => exception.access |= bits,
+                    None => exceptions.push(DeviceException {
+                        typ: rule_type,
+                        major: rule.major(),
+                        minor: rule.minor(),
+                        access: bits,
+                    }),
+                }
+            }
+
+            let mut matching = exceptions
+                .iter()
+                .filter(|exception| {
+                    exception.typ == typ
+                        && exception.major.is_none_or(|m| m == major)
+                        && exception.minor.is_none_or(|m| m == minor)
+                })
+                .map(|exception| exception.access);
+            if default_allow {
+                !matching.any(|bits| bits & requested != 0)
+            } else {
+                matching.any(|bits| requested & !bits == 0)
+            }
+        }
+        _ => rules
+            .iter()
+            .rev()
+            .filter(|rule| {
+                let type_matches = match rule.typ() {
+                    None | Some(LinuxDeviceType::A) => true,
+                    Some(LinuxDeviceType::U) => typ == LinuxDeviceType::C,
+                    Some(rule_type) => rule_type == typ,
+                };
+                type_matches
+                    && rule.major().is_none_or(|m| m == major)
+                    && rule.minor().is_none_or(|m| m == minor)
+            })
+            .find_map(|rule| {
+                let bits = rule_access_bits(rule);
+                if rule.allow() {
+                    (requested & !bits == 0).then_some(true)
+                } else {
+                    (requested & bits != 0).then_some(false)
+                }
+            })
+            .unwrap_or(false),
+    }
+}
+
+/// An exception to the default of a cgroup v1 device controller.
+struct DeviceException {
+    typ: LinuxDeviceType,
+    major: Option<i64>,
+    minor: Option<i64>,
+    access: u8,
+}
+
+fn device_access_bits(access: &str) -> u8 {
+    access.chars().fold(0, |bits, c| {
+        bits | match c {
+            'r' => DEVICE_READ,
+            'w' => DEVICE_WRITE,
+            'm' => DEVICE_MKNOD,
+            _ => 0,
+        }
+    })
+}
+
+fn rule_access_bits(rule: &LinuxDeviceCgroup) -> u8 {
+    match rule.access().as_deref() {
+        None | Some("") => DEVICE_ALL,
+        Some(access) => device_access_bits(access),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{
+        LinuxBlockIoBuilder, LinuxCpuBuilder, LinuxMemoryBuilder, LinuxPidsBuilder,
+        LinuxResourcesBuilder, LinuxThrottleDeviceBuilder,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn device_access() {
+        let rule = |allow: bool, typ: Option<LinuxDeviceType>, major: Option<i64>, access: &str| {
+            let mut rule = LinuxDeviceCgroup::default();
+            rule.set_allow(allow);
+            rule.set_typ(typ);
+            rule.set_major(major);
+            rule.set_access(Some(access.to_string()));
+            rule
+        };
+        let check = |rules: &[LinuxDeviceCgroup], version, access| {
+            is_device_access_allowed(rules, version, LinuxDeviceType::C, 1, 3, access)
+        };
+        let c = Some(LinuxDeviceType::C);
+
+        for version in [CgroupVersion::V1, CgroupVersion::V2] {
+            let rules = [rule(false, None, None, "rwm"), rule(true, c, Some(1), "rw")];
+            assert!(check(&rules, version, "rw"));
+            assert!(!check(&rules, version, "rwm"));
+            assert!(!is_device_access_allowed(
+                &rules,
+                version,
+                LinuxDeviceType::B,
+                1,
+                3,
+                "r"
+            ));
+            assert!(is_device_access_allowed(
+                &rules,
+                version,
+                LinuxDeviceType::P,
+                0,
+                0,
+                "rw"
+            ));
+        }
+
+        // Without rules a cgroup v1 allows everything, cgroup v2 nothing.
+        assert!(check(&[], CgroupVersion::V1, "rwm"));
+        assert!(!check(&[], CgroupVersion::V2, "r"));
+
+        // A specific deny does not narrow a wildcard allow on cgroup v1.
+        let rules = [
+            rule(false, None, None, "rwm"),
+            rule(true, c, None, "rwm"),
+            rule(false, c, Some(1), "w"),
+        ];
+        assert!(check(&rules, CgroupVersion::V1, "rw"));
+        assert!(!check(&rules, CgroupVersion::V2, "rw"));
+        assert!(check(&rules, CgroupVersion::V2, "r"));
+
+        // On an allowing cgroup v1 deny rules add exceptions.
+        let rules = [rule(false, c, Some(1), "w"), rule(true, c, Some(1), "w")];
+        assert!(!check(&rules[..1], CgroupVersion::V1, "rw"));
+        assert!(check(&rules[..1], CgroupVersion::V1, "r"));
+        assert!(check(&rules, CgroupVersion::V1, "rw"));
+    }
+
+    #[test]
+    fn weight_conversions() {
+        assert_eq!(convert_cpu_shares_to_cgroup_v2(2), 1);
+        assert_eq!(convert_cpu_shares_to_cgroup_v2(1024), 39);
+        assert_eq!(convert_cpu_shares_to_cgroup_v2(262144), 10000);
+        assert_eq!(convert_blkio_weight_to_cgroup_v2(10), 1);
+        assert_eq!(convert_blkio_weight_to_cgroup_v2(500), 4950);
+        assert_eq!(convert_blkio_weight_to_cgroup_v2(1000), 10000);
+    }
+
+    #[test]
+    fn resources_to_cgroup_v2() {
+        let resources = LinuxResourcesBuilder::default()
+            .memory(
+                LinuxMemoryBuilder::default()
+                    .limit(1024i64)
+                    .swap(4096i64)
+                    .build()
+                    .unwrap(),
+            )
+            .cpu(
+                LinuxCpuBuilder::default()
+                    .shares(1024u64)
+                    .quota(-1i64)
+                    .period(50000u64)
+                    .cpus("0-1")
+                    .build()
+                    .unwrap(),
+            )
+            .pids(LinuxPidsBuilder::default().limit(-1i64).build().unwrap())
+            .block_io(
+                LinuxBlockIoBuilder::default()
+                    .weight(500u16)
+                    .throttle_read_bps_device(vec![LinuxThrottleDeviceBuilder::default()
+                        .major(8)
+                        .minor(0)
+                        .rate(1000u64)
+                        .build()
+                        .unwrap()])
+                    .build()
+                    .unwrap(),
+            )
+            .unified(HashMap::from([("pids.max".to_string(), "10".to_string())]))
+            .build()
+            .unwrap();
+
+        let files = resources.to_cgroup_v2().unwrap();
+        let files: Vec<_> = files
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("memory.max", "1024"),
+                ("memory.swap.max", "3072"),
+                ("cpu.weight", "39"),
+                ("cpu.max", "max 50000"),
+                ("cpuset.cpus", "0-1"),
+                ("io.weight", "default 4950"),
+                ("io.max", "8:0 rbps=1000"),
+                ("pids.max", "10"),
+            ]
+        );
+    }
+
+    #[test]
+    fn swap_without_limit() {
+        let resources = LinuxResourcesBuilder::default()
+            .memory(LinuxMemoryBuilder::default().swap(4096i64).build().unwrap())
+            .build()
+            .unwrap();
+        assert!(resources.to_cgroup_v2().is_err());
+    }
+}
@@ -33,6 +33,7 @@ use serde::{Deserialize, Serialize};
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct Features {
     /// The minimum OCI Runtime Spec version recognized by the runtime, e.g., "1.0.0".
     oci_version_min: String,
@@ -49,6 +50,7 @@ pub struct Features {
     linux: Option<LinuxFeature>,
     /// Implementation-specific annotation strings,
     /// such as the implementation version, and third-party extensions.
+    #[serde(serialize_with = "crate::serialize_sorted")]
     annotations: Option<HashMap<String, String>>,
     /// The list of the potential unsafe annotations
     /// that may appear in `config.json`.
@@ -78,6 +80,7 @@ pub struct Features {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct LinuxFeature {
     /// The list of the recognized namespaces, e.g., "mount".
     /// "None" means "unknown", not "no support for any namespace".
@@ -125,6 +128,7 @@ pub struct LinuxFeature {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct Cgroup {
     /// "v1" field represents whether Cgroup v1 support is compiled in.
     /// Unrelated to whether the host uses cgroup v1 or not.
@@ -170,6 +174,7 @@ pub struct Cgroup {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct Seccomp {
     /// "enabled" field represents whether seccomp support is compiled in.
     /// "None" means "unknown", not "false".
@@ -215,6 +220,7 @@ pub struct Seccomp {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct Apparmor {
     /// "enabled" field represents whether AppArmor support is compiled in.
     /// Unrelated to whether the host supports AppArmor or not.
@@ -244,6 +250,7 @@ pub struct Apparmor {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct Selinux {
     /// "enabled" field represents whether SELinux support is compiled in.
     /// Unrelated to whether the host supports SELinux or not.
@@ -273,6 +280,7 @@ pub struct Selinux {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct IntelRdt {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// "enabled" field represents whether Intel RDT support is compiled in.
@@ -313,6 +321,7 @@ pub struct IntelRdt {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct MemoryPolicy {
     /// modes is the list of known memory policy modes, e.g., "MPOL_INTERLEAVE".
     modes: Option<Vec<String>>,
@@ -342,6 +351,7 @@ pub struct MemoryPolicy {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct MountExtensions {
     /// "idMap" field represents the ID mapping support.
     idmap: Option<IDMap>,
@@ -369,6 +379,7 @@ pub struct MountExtensions {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct NetDevices {
     /// "enabled" field represents whether Net Devices support is compiled in.
     /// Unrelated to whether the host supports Net Devices or not.
@@ -397,6 +408,7 @@ pub struct NetDevices {
     build_fn(error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
 pub struct IDMap {
     /// "enabled" field represents whether idmap mounts supports is compiled in.
     /// Unrelated to whether the host supports it or not.
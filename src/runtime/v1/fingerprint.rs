@@ -0,0 +1,161 @@
+//! Stable fingerprints of runtime specs.
+
+use super::Spec;
+use crate::{error::Result, sha256::Sha256};
+use serde_json::Value;
+
+impl Spec {
+    /// Compute a stable fingerprint of the spec, the hex encoded SHA-256
+    /// digest of its canonical JSON representation.
+    ///
+    /// Object keys are sorted, and `null` values, `false` and empty arrays
+    /// and objects are omitted, so that specs which differ only in field
+    /// order or in an absent field versus its default value share the same
+    /// fingerprint. The order of array elements, e.g. of mounts or process
+    /// arguments, is significant, except for the capability sets. The
+    /// fingerprint does not depend on the Rust version or the platform and
+    /// can therefore be persisted, e.g. to decide whether a container has to
+    /// be restarted because its config actually changed.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe](crate::OciSpecError::SerDe)
+    /// if the spec cannot be serialized.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let spec = Spec::default();
+    /// let mut other = Spec::default();
+    /// other.set_annotations(Some(Default::default()));
+    /// assert_eq!(spec.fingerprint().unwrap(), other.fingerprint().unwrap());
+    ///
+    /// other.set_hostname(Some("other".to_string()));
+    /// assert_ne!(spec.fingerprint().unwrap(), other.fingerprint().unwrap());
+    /// ```
+    pub fn fingerprint(&self) -> Result<String> {
+        let mut value = serde_json::to_value(self)?;
+        // Capabilities are sets, which serialize in an arbitrary order.
+        if let Some(Value::Object(sets)) = value.pointer_mut("/process/capabilities") {
+            for set in sets.values_mut() {
+                if let Value::Array(capabilities) = set {
+                    capabilities.sort_by_key(|capability| capability.to_string());
+                }
+            }
+        }
+        let mut hasher = Sha256::default();
+        if let Some(value) = canonicalize(value) {
+            write_canonical(&value, &mut hasher);
+        }
+        Ok(hasher.finalize_hex())
+    }
+}
+
+/// Remove all values which are equivalent to an absent field, `None` if
+/// `value` itself is such a value.
+fn canonicalize(value: Value) -> Option<Value> {
+    match value {
+        Value::Null | Value::Bool(false) => None,
+        Value::Array(values) if values.is_empty() => None,
+        Value::Array(values) => Some(Value::Array(
+            values
+                .into_iter()
+                // Keep array elements to not shift the position of others.
+                .map(|value| canonicalize(value).unwrap_or(Value::Null))
+                .collect(),
+        )),
+        Value::Object(map) => {
+            let map: serde_json::Map<_, _> = map
+                .into_iter()
+                .filter_map(|(key, value)| canonicalize(value).map(|value| (key, value)))
+                .collect();
+            (!map.is_empty()).then_some(Value::Object(map))
+        }
+        value => Some(value),
+    }
+}
+
+/// Feed the compact JSON representation of `value` with sorted object keys
+/// into `hasher`.
+fn write_canonical(value: &Value, hasher: &mut Sha256) {
+    match value {
+        Value::Array(values) => {
+            hasher.update(b"[");
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    hasher.update(b",");
+                }
+                write_canonical(value, hasher);
+            }
+            hasher.update(b"]");
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            hasher.update(b"{");
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    hasher.update(b",");
+                }
+                hasher.update(Value::String(key.clone()).to_string().as_bytes());
+                hasher.update(b":");
+                write_canonical(value, hasher);
+            }
+            hasher.update(b"}");
+        }
+        value => hasher.update(value.to_string().as_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::ProcessBuilder;
+
+    #[test]
+    fn fingerprint() -> Result<()> {
+        let spec = Spec::default();
+        let fingerprint = spec.fingerprint()?;
+        assert_eq!(fingerprint.len(), 64);
+        assert_eq!(fingerprint, spec.clone().fingerprint()?);
+
+        // Absent fields and their defaults are equivalent.
+        let mut absent = spec.clone();
+        let mut root = spec.root().clone().unwrap();
+        root.set_readonly(None);
+        absent.set_root(Some(root.clone()));
+        let mut default = absent.clone();
+        root.set_readonly(Some(false));
+        default.set_root(Some(root));
+        let mut linux = default.linux().clone().unwrap();
+        linux.set_uid_mappings(Some(vec![]));
+        default.set_linux(Some(linux));
+        assert_eq!(absent.fingerprint()?, default.fingerprint()?);
+        assert_ne!(absent.fingerprint()?, fingerprint);
+
+        // Field order does not matter, the order of arrays does.
+        let a: Spec = serde_json::from_str(
+            r#"{"ociVersion": "1.0.2", "hostname": "a", "annotations": {"x": "1", "y": "2"}}"#,
+        )?;
+        let b: Spec = serde_json::from_str(
+            r#"{"annotations": {"y": "2", "x": "1"}, "hostname": "a", "ociVersion": "1.0.2"}"#,
+        )?;
+        assert_eq!(a.fingerprint()?, b.fingerprint()?);
+
+        let mut other = spec.clone();
+        let process = ProcessBuilder::default()
+            .args(vec!["b".to_string(), "a".to_string()])
+            .build()?;
+        other.set_process(Some(process));
+        let mut swapped = other.clone();
+        swapped.set_process(Some(
+            ProcessBuilder::default()
+                .args(vec!["a".to_string(), "b".to_string()])
+                .build()?,
+        ));
+        assert_ne!(other.fingerprint()?, swapped.fingerprint()?);
+
+        let mut other = spec.clone();
+        other.set_linux(None);
+        assert_ne!(other.fingerprint()?, fingerprint);
+        Ok(())
+    }
+}
@@ -27,6 +27,7 @@ use std::path::PathBuf;
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
 /// Hooks specifies a command that is run in the container at a particular
 /// event in the lifecycle (setup and teardown) of a container.
+#[non_exhaustive]
 pub struct Hooks {
     #[deprecated(
         note = "Prestart hooks were deprecated in favor of `createRuntime`, `createContainer` and `startContainer` hooks"
@@ -98,6 +99,7 @@ pub struct Hooks {
 )]
 /// Hook specifies a command that is run at a particular event in the
 /// lifecycle of a container.
+#[non_exhaustive]
 pub struct Hook {
     #[getset(get_mut = "pub", get = "pub", set = "pub")]
     /// Path to the binary to be executed. Following similar semantics to
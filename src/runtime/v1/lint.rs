@@ -0,0 +1,359 @@
+use super::{Capability, LinuxDeviceType, Spec};
+use crate::{annotations::is_reverse_domain, error::FindingCode};
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
+
+/// Capabilities which grant the container broad control over the host.
+const DANGEROUS_CAPABILITIES: &[Capability] = &[
+    Capability::SysAdmin,
+    Capability::SysModule,
+    Capability::SysRawio,
+    Capability::SysPtrace,
+    Capability::SysBoot,
+    Capability::DacReadSearch,
+    Capability::NetAdmin,
+    Capability::Bpf,
+];
+
+/// Identifies the best practice a [LintWarning] is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LintKind {
+    /// The process is allowed to gain additional privileges, e.g. via setuid
+    /// binaries.
+    NoNewPrivilegesDisabled,
+
+    /// `/sys` is mounted writable into the container.
+    WritableSysfs,
+
+    /// The device cgroup allows access to all devices of the host.
+    BroadDeviceAccess,
+
+    /// No seccomp profile is applied to the container.
+    SeccompDisabled,
+
+    /// The process keeps a capability granting broad control over the host.
+    DangerousCapability,
+
+    /// An annotation key does not follow the recommended reverse domain
+    /// notation, e.g. `com.example.myKey`.
+    AnnotationKeyFormat,
+
+    /// The process sets an OOM score adjustment, which has no effect on
+    /// cgroup OOM events while the OOM killer is disabled.
+    OomScoreAdjConflict,
+}
+
+impl LintKind {
+    /// The stable code of the best practice, e.g. `SECCOMP_DISABLED`.
+    pub fn code(&self) -> FindingCode {
+        match self {
+            Self::NoNewPrivilegesDisabled => FindingCode::NoNewPrivilegesDisabled,
+            Self::WritableSysfs => FindingCode::WritableSysfs,
+            Self::BroadDeviceAccess => FindingCode::BroadDeviceAccess,
+            Self::SeccompDisabled => FindingCode::SeccompDisabled,
+            Self::DangerousCapability => FindingCode::DangerousCapability,
+            Self::AnnotationKeyFormat => FindingCode::AnnotationKeyFormat,
+            Self::OomScoreAdjConflict => FindingCode::OomScoreAdjConflict,
+        }
+    }
+}
+
+/// A non-fatal finding produced by [Spec::lint].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LintWarning {
+    kind: LintKind,
+    path: String,
+    message: String,
+}
+
+impl LintWarning {
+    fn new(kind: LintKind, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// The best practice the warning is about.
+    pub fn kind(&self) -> LintKind {
+        self.kind
+    }
+
+    /// The stable code of the best practice, see [LintKind::code].
+    pub fn code(&self) -> FindingCode {
+        self.kind.code()
+    }
+
+    /// The path of the offending field, using the serialized field names.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The human readable description of the warning.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl Spec {
+    /// Check the spec against common security best practices.
+    ///
+    /// Unlike validation, none of the findings makes the spec invalid, they
+    /// are meant to be surfaced to users of platforms which care about
+    /// hardening their containers.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{LintKind, Spec};
+    ///
+    /// let warnings = Spec::default().lint();
+    /// assert!(warnings.iter().any(|w| w.kind() == LintKind::SeccompDisabled));
+    /// ```
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+        self.lint_process(&mut warnings);
+        self.lint_mounts(&mut warnings);
+        self.lint_linux(&mut warnings);
+        self.lint_annotations(&mut warnings);
+        warnings
+    }
+
+    fn lint_process(&self, warnings: &mut Vec<LintWarning>) {
+        let Some(process) = self.process() else {
+            return;
+        };
+
+        if process.no_new_privileges() != Some(true) {
+            warnings.push(LintWarning::new(
+                LintKind::NoNewPrivilegesDisabled,
+                "process.noNewPrivileges",
+                "should be set to true to prevent privilege escalation",
+            ));
+        }
+
+        let Some(capabilities) = process.capabilities() else {
+            return;
+        };
+        let sets = [
+            ("bounding", capabilities.bounding()),
+            ("effective", capabilities.effective()),
+            ("permitted", capabilities.permitted()),
+            ("inheritable", capabilities.inheritable()),
+            ("ambient", capabilities.ambient()),
+        ];
+        for (name, set) in sets {
+            let Some(set) = set else {
+                continue;
+            };
+            for cap in DANGEROUS_CAPABILITIES.iter().filter(|c| set.contains(c)) {
+                warnings.push(LintWarning::new(
+                    LintKind::DangerousCapability,
+                    format!("process.capabilities.{name}"),
+                    format!("contains {cap}, which grants broad control over the host"),
+                ));
+            }
+        }
+    }
+
+    fn lint_mounts(&self, warnings: &mut Vec<LintWarning>) {
+        let Some(mounts) = self.mounts() else {
+            return;
+        };
+
+        for (i, mount) in mounts.iter().enumerate() {
+            if mount.destination() != Path::new("/sys") {
+                continue;
+            }
+            let readonly = mount
+                .options()
+                .as_ref()
+                .is_some_and(|options| options.iter().any(|o| o == "ro"));
+            if !readonly {
+                warnings.push(LintWarning::new(
+                    LintKind::WritableSysfs,
+                    format!("mounts[{i}]"),
+                    "/sys should be mounted read-only",
+                ));
+            }
+        }
+    }
+
+    fn lint_linux(&self, warnings: &mut Vec<LintWarning>) {
+        let Some(linux) = self.linux() else {
+            return;
+        };
+
+        if linux.seccomp().is_none() {
+            warnings.push(LintWarning::new(
+                LintKind::SeccompDisabled,
+                "linux.seccomp",
+                "no seccomp profile is applied to the container",
+            ));
+        }
+
+        let devices = linux
+            .resources()
+            .as_ref()
+            .and_then(|r| r.devices().as_ref());
+        for (i, device) in devices.into_iter().flatten().enumerate() {
+            let all_types = matches!(device.typ(), None | Some(LinuxDeviceType::A));
+            if device.allow() && all_types && device.major().is_none() && device.minor().is_none() {
+                warnings.push(LintWarning::new(
+                    LintKind::BroadDeviceAccess,
+                    format!("linux.resources.devices[{i}]"),
+                    format!("allows access to all devices ({device})"),
+                ));
+            }
+        }
+
+        let oom_killer_disabled = linux
+            .resources()
+            .as_ref()
+            .and_then(|resources| resources.memory().as_ref())
+            .is_some_and(|memory| memory.disable_oom_killer() == Some(true));
+        let oom_score_adj = self
+            .process()
+            .as_ref()
+            .and_then(|process| process.oom_score_adj());
+        if let (true, Some(oom_score_adj)) = (oom_killer_disabled, oom_score_adj) {
+            warnings.push(LintWarning::new(
+                LintKind::OomScoreAdjConflict,
+                "process.oomScoreAdj",
+                format!(
+                    "{oom_score_adj} has no effect on cgroup OOM events while \
+                     linux.resources.memory.disableOOMKiller is set"
+                ),
+            ));
+        }
+    }
+
+    fn lint_annotations(&self, warnings: &mut Vec<LintWarning>) {
+        let Some(annotations) = self.annotations() else {
+            return;
+        };
+
+        let mut keys: Vec<_> = annotations.keys().collect();
+        keys.sort();
+        for key in keys.into_iter().filter(|key| !is_reverse_domain(key)) {
+            warnings.push(LintWarning::new(
+                LintKind::AnnotationKeyFormat,
+                format!("annotations.{key}"),
+                "annotation keys should use reverse domain notation, e.g. com.example.myKey",
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{
+        LinuxBuilder, LinuxDeviceCgroupBuilder, LinuxMemoryBuilder, LinuxResourcesBuilder,
+        ProcessBuilder, SpecBuilder,
+    };
+
+    #[test]
+    fn lint_default_spec() {
+        let kinds: Vec<_> = Spec::default().lint().iter().map(|w| w.kind()).collect();
+        assert_eq!(kinds, vec![LintKind::SeccompDisabled]);
+        assert_eq!(
+            Spec::default().lint()[0].code().as_str(),
+            "SECCOMP_DISABLED"
+        );
+    }
+
+    #[test]
+    fn lint_broad_device_access() {
+        let device = LinuxDeviceCgroupBuilder::default()
+            .allow(true)
+            .access("rwm")
+            .build()
+            .unwrap();
+        let linux = LinuxBuilder::default()
+            .resources(
+                LinuxResourcesBuilder::default()
+                    .devices(vec![device])
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let mut spec = Spec::default();
+        spec.set_linux(Some(linux));
+
+        let warnings = spec.lint();
+        let warning = warnings
+            .iter()
+            .find(|w| w.kind() == LintKind::BroadDeviceAccess)
+            .expect("broad device access is reported");
+        assert_eq!(warning.path(), "linux.resources.devices[0]");
+    }
+
+    #[test]
+    fn lint_annotations() {
+        let mut spec = Spec::default();
+        spec.set_annotations(Some(
+            [
+                ("com.example.key", "a"),
+                ("io.kubernetes.cri.sandbox-id", "b"),
+                ("mykey", "c"),
+                ("Com.Example.key", "d"),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        ));
+
+        let paths: Vec<_> = spec
+            .lint()
+            .into_iter()
+            .filter(|w| w.kind() == LintKind::AnnotationKeyFormat)
+            .map(|w| w.path().to_string())
+            .collect();
+        assert_eq!(paths, ["annotations.Com.Example.key", "annotations.mykey"]);
+    }
+
+    #[test]
+    fn lint_oom_score_adj_conflict() {
+        let memory = LinuxMemoryBuilder::default()
+            .disable_oom_killer(true)
+            .build()
+            .unwrap();
+        let linux = LinuxBuilder::default()
+            .resources(
+                LinuxResourcesBuilder::default()
+                    .memory(memory)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let process = ProcessBuilder::default()
+            .oom_score_adj(500)
+            .build()
+            .unwrap();
+        let spec = SpecBuilder::default()
+            .linux(linux)
+            .process(process)
+            .build()
+            .unwrap();
+        spec.validate().unwrap();
+
+        let warning = spec
+            .lint()
+            .into_iter()
+            .find(|w| w.kind() == LintKind::OomScoreAdjConflict)
+            .expect("the OOM conflict is reported");
+        assert_eq!(warning.path(), "process.oomScoreAdj");
+    }
+}
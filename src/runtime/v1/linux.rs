@@ -1,10 +1,16 @@
-use crate::error::{oci_error, OciSpecError};
+use crate::error::{oci_error, FindingCode, OciSpecError, ValidationError, ValidationErrorKind};
 use crate::is_none_or_empty;
 
 use derive_builder::Builder;
 use getset::{CopyGetters, Getters, MutGetters, Setters};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display, path::PathBuf, vec};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    vec,
+};
 use strum_macros::{Display as StrumDisplay, EnumString};
 
 #[derive(
@@ -15,13 +21,18 @@ use strum_macros::{Display as StrumDisplay, EnumString};
     default,
     pattern = "owned",
     setter(into, strip_option),
-    build_fn(error = "OciSpecError")
+    build_fn(validate = "Self::validate", error = "OciSpecError")
 )]
 #[getset(get_mut = "pub", get = "pub", set = "pub")]
 /// Linux contains platform-specific configuration for Linux based
 /// containers.
+#[non_exhaustive]
 pub struct Linux {
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
     /// NetDevices are key-value pairs, keyed by network device name on the host, moved to the container's network namespace.
     net_devices: Option<HashMap<String, LinuxNetDevice>>,
 
@@ -33,7 +44,11 @@ pub struct Linux {
     /// GIDMappings specifies group mappings for supporting user namespaces.
     gid_mappings: Option<Vec<LinuxIdMapping>>,
 
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
     /// Sysctl are a set of key value pairs that are set for the container
     /// on start.
     sysctl: Option<HashMap<String, String>>,
@@ -97,7 +112,11 @@ pub struct Linux {
     /// syscall.
     personality: Option<LinuxPersonality>,
 
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
     /// TimeOffsets specifies the offset for supporting time namespaces.
     time_offsets: Option<HashMap<String, LinuxTimeOffset>>,
 }
@@ -170,6 +189,18 @@ impl Linux {
             ..Default::default()
         }
     }
+
+    /// Run the validation of [LinuxBuilder], e.g. for a deserialized value.
+    pub(crate) fn validate(&self) -> Result<(), OciSpecError> {
+        validate_devices(self.devices.as_deref().unwrap_or_default())
+    }
+}
+
+impl LinuxBuilder {
+    fn validate(&self) -> Result<(), OciSpecError> {
+        let devices = self.devices.as_ref().and_then(Option::as_deref);
+        validate_devices(devices.unwrap_or_default())
+    }
 }
 
 #[derive(
@@ -184,6 +215,7 @@ impl Linux {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// LinuxIDMapping specifies UID/GID mappings.
+#[non_exhaustive]
 pub struct LinuxIdMapping {
     #[serde(default, rename = "hostID")]
     /// HostID is the starting UID/GID on the host to be mapped to
@@ -203,6 +235,7 @@ pub struct LinuxIdMapping {
 #[strum(serialize_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 /// Device types
+#[non_exhaustive]
 pub enum LinuxDeviceType {
     /// All
     A,
@@ -260,6 +293,7 @@ impl LinuxDeviceType {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxNetDevice represents a single network device to be added to the container's network namespace
+#[non_exhaustive]
 pub struct LinuxNetDevice {
     #[serde(default)]
     #[getset(get_mut = "pub", get = "pub", set = "pub")]
@@ -289,6 +323,7 @@ pub struct LinuxNetDevice {
 )]
 /// Represents a device rule for the devices specified to the device
 /// controller
+#[non_exhaustive]
 pub struct LinuxDeviceCgroup {
     #[serde(default)]
     #[getset(get_mut = "pub", get_copy = "pub", set = "pub")]
@@ -359,10 +394,11 @@ impl Display for LinuxDeviceCgroup {
     default,
     pattern = "owned",
     setter(into, strip_option),
-    build_fn(error = "OciSpecError")
+    build_fn(validate = "Self::validate", error = "OciSpecError")
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// LinuxMemory for Linux cgroup 'memory' resource management.
+#[non_exhaustive]
 pub struct LinuxMemory {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[getset(get_copy = "pub", set = "pub")]
@@ -419,6 +455,65 @@ pub struct LinuxMemory {
     check_before_update: Option<bool>,
 }
 
+impl LinuxMemory {
+    /// Run the validation of [LinuxMemoryBuilder], e.g. for a deserialized
+    /// value.
+    pub(crate) fn validate(&self) -> Result<(), OciSpecError> {
+        LinuxMemoryBuilder {
+            limit: Some(self.limit),
+            reservation: Some(self.reservation),
+            swap: Some(self.swap),
+            swappiness: Some(self.swappiness),
+            ..Default::default()
+        }
+        .validate()
+    }
+}
+
+impl LinuxMemoryBuilder {
+    fn validate(&self) -> Result<(), OciSpecError> {
+        // Negative values mean unlimited.
+        let limit = self.limit.flatten().filter(|limit| *limit >= 0);
+
+        // A swap of 0 is treated as unset by engines.
+        if let (Some(limit), Some(swap)) = (limit, self.swap.flatten()) {
+            if swap > 0 && swap < limit {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Conflict,
+                    "swap",
+                    "memory+swap limit must not be lower than the memory limit",
+                )
+                .with_code(FindingCode::MemorySwapBelowLimit)
+                .with_expected(format!("at least {limit}, 0 or -1"))
+                .with_actual(swap.to_string())
+                .into());
+            }
+        }
+
+        if let (Some(limit), Some(reservation)) = (limit, self.reservation.flatten()) {
+            if reservation > limit {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Conflict,
+                    "reservation",
+                    "memory reservation must not be greater than the memory limit",
+                )
+                .with_code(FindingCode::MemoryReservationAboveLimit)
+                .with_expected(format!("at most {limit}"))
+                .with_actual(reservation.to_string())
+                .into());
+            }
+        }
+
+        if let Some(swappiness) = self.swappiness.flatten() {
+            if swappiness > 100 {
+                return Err(out_of_range("swappiness", "0-100", swappiness));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(
     Builder,
     Clone,
@@ -437,9 +532,10 @@ pub struct LinuxMemory {
     default,
     pattern = "owned",
     setter(into, strip_option),
-    build_fn(error = "OciSpecError")
+    build_fn(validate = "Self::validate", error = "OciSpecError")
 )]
 /// LinuxCPU for Linux cgroup 'cpu' resource management.
+#[non_exhaustive]
 pub struct LinuxCpu {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[getset(get_copy = "pub", set = "pub")]
@@ -490,6 +586,110 @@ pub struct LinuxCpu {
     mems: Option<String>,
 }
 
+/// The bounds of the CFS period in microseconds accepted by the kernel.
+const CPU_PERIOD_RANGE: std::ops::RangeInclusive<u64> = 1000..=1_000_000;
+
+/// The range of the cgroup v1 `cpu.shares` accepted by the kernel.
+const CPU_SHARES_RANGE: std::ops::RangeInclusive<u64> = 2..=262_144;
+
+impl LinuxCpu {
+    /// Run the validation of [LinuxCpuBuilder], e.g. for a deserialized
+    /// value.
+    pub(crate) fn validate(&self) -> Result<(), OciSpecError> {
+        LinuxCpuBuilder {
+            shares: Some(self.shares),
+            quota: Some(self.quota),
+            period: Some(self.period),
+            realtime_runtime: Some(self.realtime_runtime),
+            realtime_period: Some(self.realtime_period),
+            cpus: Some(self.cpus.clone()),
+            mems: Some(self.mems.clone()),
+            ..Default::default()
+        }
+        .validate()
+    }
+}
+
+impl LinuxCpuBuilder {
+    fn validate(&self) -> Result<(), OciSpecError> {
+        let field = |field: &Option<Option<u64>>| field.flatten();
+
+        if let Some(period) = field(&self.period) {
+            if !CPU_PERIOD_RANGE.contains(&period) {
+                return Err(out_of_range("period", "1000-1000000", period));
+            }
+        }
+
+        // Runtimes treat shares of 0 like unset shares.
+        if let Some(shares) = field(&self.shares) {
+            if shares != 0 && !CPU_SHARES_RANGE.contains(&shares) {
+                return Err(out_of_range("shares", "0 or 2-262144", shares));
+            }
+        }
+
+        if let Some(Some(quota)) = self.quota {
+            if quota < -1 {
+                return Err(out_of_range("quota", "-1 or greater", quota));
+            }
+        }
+
+        if let (Some(Some(runtime)), Some(Some(period))) =
+            (self.realtime_runtime, self.realtime_period)
+        {
+            if runtime > 0 && runtime as u64 > period {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Conflict,
+                    "realtimeRuntime",
+                    "must not be greater than realtimePeriod",
+                )
+                .with_code(FindingCode::CpuRealtimeRuntimeAbovePeriod)
+                .with_expected(format!("at most {period}"))
+                .with_actual(runtime.to_string())
+                .into());
+            }
+        }
+
+        for (path, list) in [("cpus", &self.cpus), ("mems", &self.mems)] {
+            if let Some(Some(list)) = list {
+                if !is_valid_cpu_list(list) {
+                    return Err(ValidationError::new(
+                        ValidationErrorKind::InvalidFormat,
+                        path,
+                        "invalid cpu list",
+                    )
+                    .with_code(FindingCode::CpuListInvalid)
+                    .with_expected("comma separated list of numbers or ranges, e.g. 0-3,7")
+                    .with_actual(list.as_str())
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn out_of_range(path: &str, expected: &str, actual: impl Display) -> OciSpecError {
+    ValidationError::new(ValidationErrorKind::OutOfRange, path, "value out of range")
+        .with_expected(expected)
+        .with_actual(actual.to_string())
+        .into()
+}
+
+/// Returns true if `list` is a cpu or memory node list in the format of the
+/// `cpuset.cpus` and `cpuset.mems` cgroup files, e.g. `0-3,7`. An empty list
+/// is valid and leaves the setting unchanged.
+fn is_valid_cpu_list(list: &str) -> bool {
+    list.is_empty()
+        || list.split(',').all(|item| match item.split_once('-') {
+            Some((start, end)) => match (start.parse::<u32>(), end.parse::<u32>()) {
+                (Ok(start), Ok(end)) => start <= end,
+                _ => false,
+            },
+            None => item.parse::<u32>().is_ok(),
+        })
+}
+
 #[derive(
     Builder,
     Clone,
@@ -511,6 +711,7 @@ pub struct LinuxCpu {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// LinuxPids for Linux cgroup 'pids' resource management (Linux 4.3).
+#[non_exhaustive]
 pub struct LinuxPids {
     #[serde(default)]
     /// Maximum number of PIDs. Default is "no limit".
@@ -530,6 +731,7 @@ pub struct LinuxPids {
 #[getset(get_copy = "pub", set = "pub")]
 /// LinuxWeightDevice struct holds a `major:minor weight` pair for
 /// weightDevice.
+#[non_exhaustive]
 pub struct LinuxWeightDevice {
     #[serde(default)]
     /// Major is the device's major number.
@@ -560,6 +762,7 @@ pub struct LinuxWeightDevice {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// LinuxThrottleDevice struct holds a `major:minor rate_per_second` pair.
+#[non_exhaustive]
 pub struct LinuxThrottleDevice {
     #[serde(default)]
     /// Major is the device's major number.
@@ -595,6 +798,7 @@ pub struct LinuxThrottleDevice {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxBlockIO for Linux cgroup 'blkio' resource management.
+#[non_exhaustive]
 pub struct LinuxBlockIo {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[getset(get_copy = "pub", set = "pub")]
@@ -661,6 +865,7 @@ pub struct LinuxBlockIo {
 )]
 /// LinuxHugepageLimit structure corresponds to limiting kernel hugepages.
 /// Default to reservation limits if supported. Otherwise fallback to page fault limits.
+#[non_exhaustive]
 pub struct LinuxHugepageLimit {
     #[serde(default)]
     #[getset(get = "pub", set = "pub")]
@@ -694,6 +899,7 @@ pub struct LinuxHugepageLimit {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxInterfacePriority for network interfaces.
+#[non_exhaustive]
 pub struct LinuxInterfacePriority {
     #[serde(default)]
     #[getset(get = "pub", set = "pub")]
@@ -737,6 +943,7 @@ impl Display for LinuxInterfacePriority {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxNetwork identification and priority configuration.
+#[non_exhaustive]
 pub struct LinuxNetwork {
     #[serde(skip_serializing_if = "Option::is_none", rename = "classID")]
     #[getset(get_copy = "pub", set = "pub")]
@@ -771,6 +978,7 @@ pub struct LinuxNetwork {
     build_fn(error = "OciSpecError")
 )]
 /// Resource constraints for container
+#[non_exhaustive]
 pub struct LinuxResources {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[getset(get_mut = "pub", get = "pub", set = "pub")]
@@ -807,19 +1015,173 @@ pub struct LinuxResources {
     /// Network restriction configuration.
     network: Option<LinuxNetwork>,
 
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
     #[getset(get_mut = "pub", get = "pub", set = "pub")]
     /// Rdma resource restriction configuration. Limits are a set of key
     /// value pairs that define RDMA resource limits, where the key
     /// is device name and value is resource limits.
     rdma: Option<HashMap<String, LinuxRdma>>,
 
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
     #[getset(get_mut = "pub", get = "pub", set = "pub")]
     /// Unified resources.
     unified: Option<HashMap<String, String>>,
 }
 
+/// Overwrite the fields of `$target` which are set in `$update`.
+macro_rules! merge_set_fields {
+    ($target:expr, $update:expr, $($field:ident),+ $(,)?) => {
+        $(
+            if $update.$field.is_some() {
+                $target.$field.clone_from(&$update.$field);
+            }
+        )+
+    };
+}
+
+/// Merge `update` into `target`, using `merge` if both are set.
+fn merge_option<T: Clone>(target: &mut Option<T>, update: &Option<T>, merge: fn(&mut T, &T)) {
+    match (target.as_mut(), update) {
+        (Some(target), Some(update)) => merge(target, update),
+        (None, Some(update)) => *target = Some(update.clone()),
+        (_, None) => {}
+    }
+}
+
+/// Insert the entries of `update` into `target`, overwriting existing keys.
+fn merge_map<V: Clone>(
+    target: &mut Option<HashMap<String, V>>,
+    update: &Option<HashMap<String, V>>,
+) {
+    if let Some(update) = update {
+        target
+            .get_or_insert_with(HashMap::new)
+            .extend(update.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+}
+
+impl LinuxResources {
+    /// Apply a partial resource update with the semantics of `runc update`:
+    /// only the fields which are set in `update` overwrite the current values,
+    /// unset fields are kept.
+    ///
+    /// Memory, CPU, block IO and network settings are merged field by field.
+    /// Hugepage limits are merged by page size, RDMA and unified entries by
+    /// key. All other fields are replaced as a whole.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{LinuxCpuBuilder, LinuxResourcesBuilder};
+    ///
+    /// let mut resources = LinuxResourcesBuilder::default()
+    ///     .cpu(LinuxCpuBuilder::default().shares(1024u64).quota(50000i64).build().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// let update = LinuxResourcesBuilder::default()
+    ///     .cpu(LinuxCpuBuilder::default().quota(100000i64).build().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// resources.merge_update(&update);
+    /// let cpu = resources.cpu().as_ref().unwrap();
+    /// assert_eq!(cpu.shares(), Some(1024));
+    /// assert_eq!(cpu.quota(), Some(100000));
+    /// ```
+    pub fn merge_update(&mut self, update: &LinuxResources) {
+        merge_set_fields!(self, update, devices, pids);
+        merge_option(&mut self.memory, &update.memory, LinuxMemory::merge_update);
+        merge_option(&mut self.cpu, &update.cpu, LinuxCpu::merge_update);
+        merge_option(
+            &mut self.block_io,
+            &update.block_io,
+            LinuxBlockIo::merge_update,
+        );
+        merge_option(
+            &mut self.network,
+            &update.network,
+            LinuxNetwork::merge_update,
+        );
+        merge_map(&mut self.rdma, &update.rdma);
+        merge_map(&mut self.unified, &update.unified);
+
+        if let Some(update) = &update.hugepage_limits {
+            let limits = self.hugepage_limits.get_or_insert_with(Vec::new);
+            for limit in update {
+                match limits.iter_mut().find(|l| l.page_size == limit.page_size) {
+                    Some(existing) => existing.limit = limit.limit,
+                    None => limits.push(limit.clone()),
+                }
+            }
+        }
+    }
+}
+
+impl LinuxMemory {
+    #[allow(deprecated)]
+    fn merge_update(&mut self, update: &LinuxMemory) {
+        merge_set_fields!(
+            self,
+            update,
+            limit,
+            reservation,
+            swap,
+            kernel,
+            kernel_tcp,
+            swappiness,
+            disable_oom_killer,
+            use_hierarchy,
+            check_before_update,
+        );
+    }
+}
+
+impl LinuxCpu {
+    fn merge_update(&mut self, update: &LinuxCpu) {
+        merge_set_fields!(
+            self,
+            update,
+            shares,
+            quota,
+            idle,
+            burst,
+            period,
+            realtime_runtime,
+            realtime_period,
+            cpus,
+            mems,
+        );
+    }
+}
+
+impl LinuxBlockIo {
+    fn merge_update(&mut self, update: &LinuxBlockIo) {
+        merge_set_fields!(
+            self,
+            update,
+            weight,
+            leaf_weight,
+            weight_device,
+            throttle_read_bps_device,
+            throttle_write_bps_device,
+            throttle_read_iops_device,
+            throttle_write_iops_device,
+        );
+    }
+}
+
+impl LinuxNetwork {
+    fn merge_update(&mut self, update: &LinuxNetwork) {
+        merge_set_fields!(self, update, class_id, priorities);
+    }
+}
+
 #[derive(
     Builder,
     Clone,
@@ -842,6 +1204,7 @@ pub struct LinuxResources {
 )]
 #[getset(get_mut = "pub", get_copy = "pub", set = "pub")]
 /// LinuxRdma for Linux cgroup 'rdma' resource management (Linux 4.11).
+#[non_exhaustive]
 pub struct LinuxRdma {
     #[serde(skip_serializing_if = "Option::is_none")]
     /// Maximum number of HCA handles that can be opened. Default is "no
@@ -860,6 +1223,7 @@ pub struct LinuxRdma {
 #[strum(serialize_all = "lowercase")]
 #[serde(rename_all = "snake_case")]
 /// Available Linux namespaces.
+#[non_exhaustive]
 pub enum LinuxNamespaceType {
     #[strum(to_string = "mnt")]
     /// Mount Namespace for isolating mount points
@@ -909,6 +1273,36 @@ impl TryFrom<&str> for LinuxNamespaceType {
     }
 }
 
+impl FromStr for LinuxNamespaceType {
+    type Err = OciSpecError;
+
+    fn from_str(namespace: &str) -> Result<Self, Self::Err> {
+        Self::try_from(namespace)
+    }
+}
+
+impl LinuxNamespaceType {
+    /// The name of the namespace file in `/proc/<pid>/ns`.
+    pub fn proc_ns_name(&self) -> &'static str {
+        match self {
+            LinuxNamespaceType::Mount => "mnt",
+            LinuxNamespaceType::Cgroup => "cgroup",
+            LinuxNamespaceType::Uts => "uts",
+            LinuxNamespaceType::Ipc => "ipc",
+            LinuxNamespaceType::User => "user",
+            LinuxNamespaceType::Pid => "pid",
+            LinuxNamespaceType::Network => "net",
+            LinuxNamespaceType::Time => "time",
+        }
+    }
+
+    /// The path of the namespace file of the process `pid`, e.g.
+    /// `/proc/42/ns/net`.
+    pub fn proc_ns_path(&self, pid: u32) -> PathBuf {
+        PathBuf::from(format!("/proc/{pid}/ns/{}", self.proc_ns_name()))
+    }
+}
+
 #[derive(
     Builder,
     Clone,
@@ -929,6 +1323,7 @@ impl TryFrom<&str> for LinuxNamespaceType {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxNamespace is the configuration for a Linux namespace.
+#[non_exhaustive]
 pub struct LinuxNamespace {
     #[serde(rename = "type")]
     #[getset(get_copy = "pub", set = "pub")]
@@ -991,10 +1386,11 @@ pub fn get_default_namespaces() -> Vec<LinuxNamespace> {
     default,
     pattern = "owned",
     setter(into, strip_option),
-    build_fn(error = "OciSpecError")
+    build_fn(validate = "Self::validate", error = "OciSpecError")
 )]
 /// LinuxDevice represents the mknod information for a Linux special device
 /// file.
+#[non_exhaustive]
 pub struct LinuxDevice {
     #[serde(default)]
     #[getset(get_mut = "pub", get = "pub", set = "pub")]
@@ -1032,6 +1428,171 @@ pub struct LinuxDevice {
     gid: Option<u32>,
 }
 
+impl LinuxDevice {
+    /// Run the validation of [LinuxDeviceBuilder], e.g. for a deserialized
+    /// value. Missing major and minor numbers deserialize to 0, so unlike
+    /// the builder this cannot check that they are set for character and
+    /// block devices.
+    pub(crate) fn validate(&self) -> Result<(), OciSpecError> {
+        validate_device(&self.path, self.typ, self.file_mode)
+    }
+
+    /// Returns true if the device is one of [get_default_devices], which
+    /// runtimes provide to every container.
+    pub fn is_default(&self) -> bool {
+        get_default_devices().iter().any(|default| {
+            default.path == self.path
+                && default.typ == self.typ
+                && default.major == self.major
+                && default.minor == self.minor
+        })
+    }
+}
+
+impl LinuxDeviceBuilder {
+    fn validate(&self) -> Result<(), OciSpecError> {
+        let typ = self.typ.unwrap_or_default();
+        validate_device(
+            self.path.as_deref().unwrap_or(Path::new("")),
+            typ,
+            self.file_mode.flatten(),
+        )?;
+
+        if typ != LinuxDeviceType::P {
+            for (field, value) in [("major", self.major), ("minor", self.minor)] {
+                if value.is_none() {
+                    return Err(ValidationError::new(
+                        ValidationErrorKind::Missing,
+                        field,
+                        "required for character and block devices",
+                    )
+                    .with_code(FindingCode::DeviceNumberMissing)
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Validate the type, path and file mode of a device.
+fn validate_device(
+    path: &Path,
+    typ: LinuxDeviceType,
+    file_mode: Option<u32>,
+) -> Result<(), OciSpecError> {
+    if !matches!(
+        typ,
+        LinuxDeviceType::C | LinuxDeviceType::B | LinuxDeviceType::U | LinuxDeviceType::P
+    ) {
+        return Err(ValidationError::new(
+            ValidationErrorKind::InvalidFormat,
+            "type",
+            "invalid device type",
+        )
+        .with_code(FindingCode::DeviceTypeInvalid)
+        .with_expected("one of c, b, u or p")
+        .with_actual(typ.as_str())
+        .into());
+    }
+
+    let under_dev = path.is_absolute()
+        && path.starts_with("/dev")
+        && path != Path::new("/dev")
+        && !path
+            .components()
+            .any(|component| component == Component::ParentDir);
+    if !under_dev {
+        return Err(ValidationError::new(
+            ValidationErrorKind::InvalidFormat,
+            "path",
+            "device path must be an absolute path under /dev",
+        )
+        .with_code(FindingCode::DevicePathInvalid)
+        .with_actual(path.display().to_string())
+        .into());
+    }
+
+    if let Some(file_mode) = file_mode {
+        if file_mode > 0o7777 {
+            return Err(out_of_range(
+                "fileMode",
+                "0-0o7777",
+                format!("{file_mode:#o}"),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Utility function to get the devices which runtimes provide to every
+/// container, see
+/// <https://github.com/opencontainers/runtime-spec/blob/main/config-linux.md#default-devices>.
+/// `/dev/console` is only provided along with a terminal and not included.
+pub fn get_default_devices() -> Vec<LinuxDevice> {
+    [
+        ("/dev/null", 1, 3),
+        ("/dev/zero", 1, 5),
+        ("/dev/full", 1, 7),
+        ("/dev/random", 1, 8),
+        ("/dev/urandom", 1, 9),
+        ("/dev/tty", 5, 0),
+        ("/dev/ptmx", 5, 2),
+    ]
+    .into_iter()
+    .map(|(path, major, minor)| LinuxDevice {
+        path: path.into(),
+        typ: LinuxDeviceType::C,
+        major,
+        minor,
+        file_mode: Some(0o666),
+        uid: Some(0),
+        gid: Some(0),
+    })
+    .collect()
+}
+
+/// Check `devices` for entries with the same path and for entries which
+/// redefine one of [get_default_devices] as a different device.
+fn validate_devices(devices: &[LinuxDevice]) -> Result<(), OciSpecError> {
+    let defaults = get_default_devices();
+    let mut paths = HashMap::new();
+    for (i, device) in devices.iter().enumerate() {
+        if let Some(first) = paths.insert(&device.path, i) {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Conflict,
+                format!("devices[{i}].path"),
+                format!("duplicate of devices[{first}]"),
+            )
+            .with_code(FindingCode::DeviceDuplicate)
+            .with_actual(device.path.display().to_string())
+            .into());
+        }
+        if let Some(default) = defaults.iter().find(|default| default.path == device.path) {
+            if !device.is_default() {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Conflict,
+                    format!("devices[{i}]"),
+                    "redefines a default device",
+                )
+                .with_code(FindingCode::DeviceRedefinesDefault)
+                .with_expected(format!("c {}:{}", default.major, default.minor))
+                .with_actual(format!(
+                    "{} {}:{}",
+                    device.typ.as_str(),
+                    device.major,
+                    device.minor
+                ))
+                .into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl From<&LinuxDevice> for LinuxDeviceCgroup {
     fn from(linux_device: &LinuxDevice) -> LinuxDeviceCgroup {
         LinuxDeviceCgroup {
@@ -1065,6 +1626,7 @@ impl From<&LinuxDevice> for LinuxDeviceCgroup {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxSeccomp represents syscall restrictions.
+#[non_exhaustive]
 pub struct LinuxSeccomp {
     #[getset(get_copy = "pub", set = "pub")]
     /// The default action to be done.
@@ -1107,6 +1669,7 @@ pub struct LinuxSeccomp {
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 /// Available seccomp actions.
+#[non_exhaustive]
 pub enum LinuxSeccompAction {
     /// Kill the thread, defined for backward compatibility.
     ScmpActKill,
@@ -1182,6 +1745,7 @@ impl LinuxSeccompAction {
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[repr(u32)]
 /// Available seccomp architectures.
+#[non_exhaustive]
 pub enum Arch {
     /// The native architecture.
     ScmpArchNative = 0x00000000,
@@ -1259,10 +1823,107 @@ pub enum Arch {
     ScmpArchSheb = 0x0000002a,
 }
 
+impl Arch {
+    /// Returns the seccomp architecture of the build target followed by the
+    /// architectures it can execute in compat mode, see [Arch::with_compat].
+    /// This matches the architecture list of the default profiles of common
+    /// container engines.
+    ///
+    /// Returns [Arch::ScmpArchNative] for targets without a seccomp
+    /// architecture.
+    pub fn native_with_compat() -> &'static [Arch] {
+        let little_endian = cfg!(target_endian = "little");
+        let target = if cfg!(target_arch = "x86_64") {
+            "x86_64"
+        } else if cfg!(target_arch = "x86") {
+            "x86"
+        } else if cfg!(target_arch = "aarch64") {
+            "aarch64"
+        } else if cfg!(target_arch = "arm") {
+            "arm"
+        } else if cfg!(target_arch = "mips64") && little_endian {
+            "mips64el"
+        } else if cfg!(target_arch = "mips64") {
+            "mips64"
+        } else if cfg!(target_arch = "mips") && little_endian {
+            "mipsel"
+        } else if cfg!(target_arch = "mips") {
+            "mips"
+        } else if cfg!(target_arch = "powerpc64") && little_endian {
+            "powerpc64le"
+        } else if cfg!(target_arch = "powerpc64") {
+            "powerpc64"
+        } else if cfg!(target_arch = "powerpc") {
+            "powerpc"
+        } else if cfg!(target_arch = "s390x") {
+            "s390x"
+        } else if cfg!(target_arch = "riscv64") {
+            "riscv64"
+        } else if cfg!(target_arch = "loongarch64") {
+            "loongarch64"
+        } else if cfg!(target_arch = "m68k") {
+            "m68k"
+        } else {
+            ""
+        };
+        Self::with_compat(target).unwrap_or(&[Arch::ScmpArchNative])
+    }
+
+    /// Returns the seccomp architecture of the target architecture `target`
+    /// followed by the architectures it can execute in compat mode, e.g.
+    /// `SCMP_ARCH_X86_64`, `SCMP_ARCH_X86` and `SCMP_ARCH_X32` for x86_64.
+    /// Profiles have to list all of them, otherwise syscalls made through a
+    /// compat ABI are not filtered by the rules of the profile.
+    ///
+    /// `target` is the architecture of a Rust target triple, with the `el`
+    /// or `le` suffix for little-endian MIPS and PowerPC, e.g. `mips64el`,
+    /// or a `GOARCH` value like `amd64` or `arm64`. Returns `None` for
+    /// unknown architectures.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::Arch;
+    ///
+    /// assert_eq!(
+    ///     Arch::with_compat("arm64"),
+    ///     Some(&[Arch::ScmpArchAarch64, Arch::ScmpArchArm][..])
+    /// );
+    /// ```
+    pub fn with_compat(target: &str) -> Option<&'static [Arch]> {
+        let arches: &'static [Arch] = match target {
+            "x86_64" | "amd64" => &[Arch::ScmpArchX86_64, Arch::ScmpArchX86, Arch::ScmpArchX32],
+            "x86" | "i386" | "i586" | "i686" | "386" => &[Arch::ScmpArchX86],
+            "aarch64" | "arm64" => &[Arch::ScmpArchAarch64, Arch::ScmpArchArm],
+            "arm" | "armv7" | "armel" | "armhf" => &[Arch::ScmpArchArm],
+            "mips64el" | "mips64le" => &[
+                Arch::ScmpArchMipsel64,
+                Arch::ScmpArchMipsel,
+                Arch::ScmpArchMipsel64n32,
+            ],
+            "mips64" => &[
+                Arch::ScmpArchMips64,
+                Arch::ScmpArchMips,
+                Arch::ScmpArchMips64n32,
+            ],
+            "mipsel" | "mipsle" => &[Arch::ScmpArchMipsel],
+            "mips" => &[Arch::ScmpArchMips],
+            "powerpc64le" | "ppc64le" => &[Arch::ScmpArchPpc64le],
+            "powerpc64" | "ppc64" => &[Arch::ScmpArchPpc64, Arch::ScmpArchPpc],
+            "powerpc" | "ppc" => &[Arch::ScmpArchPpc],
+            "s390x" => &[Arch::ScmpArchS390x, Arch::ScmpArchS390],
+            "riscv64" | "riscv64gc" => &[Arch::ScmpArchRiscv64],
+            "loongarch64" | "loong64" => &[Arch::ScmpArchLoongarch64],
+            "m68k" => &[Arch::ScmpArchM68k],
+            _ => return None,
+        };
+        Some(arches)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, StrumDisplay, EnumString)]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 /// Available seccomp filter flags.
+#[non_exhaustive]
 pub enum LinuxSeccompFilterFlag {
     /// All filter return actions except SECCOMP_RET_ALLOW should be logged. An administrator may
     /// override this filter flag by preventing specific actions from being logged via the
@@ -1294,6 +1955,7 @@ pub enum LinuxSeccompFilterFlag {
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[repr(u32)]
 /// The seccomp operator to be used for args.
+#[non_exhaustive]
 pub enum LinuxSeccompOperator {
     /// Refers to the SCMP_CMP_NE operator (not equal).
     ScmpCmpNe = 1,
@@ -1339,6 +2001,7 @@ pub enum LinuxSeccompOperator {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxSyscall is used to match a syscall in seccomp.
+#[non_exhaustive]
 pub struct LinuxSyscall {
     #[getset(get = "pub", set = "pub")]
     /// The names of the syscalls.
@@ -1371,6 +2034,7 @@ pub struct LinuxSyscall {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// LinuxSeccompArg used for matching specific syscall arguments in seccomp.
+#[non_exhaustive]
 pub struct LinuxSeccompArg {
     /// The index of the argument.
     index: usize,
@@ -1416,6 +2080,129 @@ pub fn get_default_readonly_paths() -> Vec<String> {
     ]
 }
 
+/// The `clone` flags creating a new namespace: `CLONE_NEWNS`,
+/// `CLONE_NEWCGROUP`, `CLONE_NEWUTS`, `CLONE_NEWIPC`, `CLONE_NEWUSER`,
+/// `CLONE_NEWPID` and `CLONE_NEWNET`.
+const CLONE_NAMESPACE_FLAGS: [u64; 7] = [
+    0x00020000, 0x02000000, 0x04000000, 0x08000000, 0x10000000, 0x20000000, 0x40000000,
+];
+
+/// Default seccomp profile, which allows all syscalls except the ones that
+/// container engines deny by default because they affect the whole host, e.g.
+/// loading kernel modules, rebooting or changing the system time, or which
+/// bypass the other denied syscalls, like the mount API of `fsopen` and
+/// `open_tree` or `io_uring`. Denied syscalls fail with `EPERM`, as does
+/// `clone` with a flag creating a new namespace. `clone3` fails with
+/// `ENOSYS`, as seccomp cannot inspect its flags, so that the C library
+/// falls back to `clone`.
+///
+/// The `clone` flags are the first argument on all architectures but s390
+/// and s390x, where the profile does not restrict them.
+pub fn get_default_seccomp() -> LinuxSeccomp {
+    let denied = [
+        "acct",
+        "add_key",
+        "bpf",
+        "clock_adjtime",
+        "clock_settime",
+        "create_module",
+        "delete_module",
+        "finit_module",
+        "fsconfig",
+        "fsmount",
+        "fsopen",
+        "fspick",
+        "get_kernel_syms",
+        "get_mempolicy",
+        "init_module",
+        "ioperm",
+        "iopl",
+        "io_uring_enter",
+        "io_uring_register",
+        "io_uring_setup",
+        "kcmp",
+        "kexec_file_load",
+        "kexec_load",
+        "keyctl",
+        "lookup_dcookie",
+        "mbind",
+        "mount",
+        "mount_setattr",
+        "move_mount",
+        "move_pages",
+        "name_to_handle_at",
+        "nfsservctl",
+        "open_tree",
+        "open_tree_attr",
+        "open_by_handle_at",
+        "perf_event_open",
+        "pivot_root",
+        "process_vm_readv",
+        "process_vm_writev",
+        "ptrace",
+        "query_module",
+        "quotactl",
+        "reboot",
+        "request_key",
+        "set_mempolicy",
+        "setns",
+        "settimeofday",
+        "stime",
+        "swapoff",
+        "swapon",
+        "sysfs",
+        "_sysctl",
+        "umount",
+        "umount2",
+        "unshare",
+        "uselib",
+        "userfaultfd",
+        "ustat",
+        "vm86",
+        "vm86old",
+    ];
+
+    LinuxSeccomp {
+        default_action: LinuxSeccompAction::ScmpActAllow,
+        syscalls: Some(vec![
+            LinuxSyscall {
+                names: denied.iter().map(|name| name.to_string()).collect(),
+                action: LinuxSeccompAction::ScmpActErrno,
+                // EPERM
+                errno_ret: Some(1),
+                args: None,
+            },
+            LinuxSyscall {
+                names: vec!["clone".to_string()],
+                action: LinuxSeccompAction::ScmpActErrno,
+                // EPERM
+                errno_ret: Some(1),
+                // Conditions on the same argument are alternatives, so any
+                // of the flags is denied.
+                args: Some(
+                    CLONE_NAMESPACE_FLAGS
+                        .iter()
+                        .map(|flag| LinuxSeccompArg {
+                            index: 0,
+                            value: *flag,
+                            value_two: Some(*flag),
+                            op: LinuxSeccompOperator::ScmpCmpMaskedEq,
+                        })
+                        .collect(),
+                ),
+            },
+            LinuxSyscall {
+                names: vec!["clone3".to_string()],
+                action: LinuxSeccompAction::ScmpActErrno,
+                // ENOSYS
+                errno_ret: Some(38),
+                args: None,
+            },
+        ]),
+        ..Default::default()
+    }
+}
+
 #[derive(
     Builder,
     Clone,
@@ -1440,6 +2227,7 @@ pub fn get_default_readonly_paths() -> Vec<String> {
 /// LinuxIntelRdt has container runtime resource constraints for Intel RDT CAT and MBA
 /// features and flags enabling Intel RDT CMT and MBM features.
 /// Intel RDT features are available in Linux 4.14 and newer kernel versions.
+#[non_exhaustive]
 pub struct LinuxIntelRdt {
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "closID")]
     /// The identity for RDT Class of Service.
@@ -1471,7 +2259,12 @@ pub struct LinuxIntelRdt {
         since = "0.10.0",
         note = "enable_cmt is deprecated in runtime-spec v1.3.0. Use enable_monitoring instead."
     )]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "enableCMT",
+        alias = "enableCmt"
+    )]
     /// EnableCMT is the flag to indicate if the Intel RDT CMT is enabled. CMT (Cache Monitoring Technology) supports monitoring of
     /// the last-level cache (LLC) occupancy for the container.
     ///
@@ -1484,7 +2277,12 @@ pub struct LinuxIntelRdt {
         since = "0.10.0",
         note = "enable_mbm is deprecated in runtime-spec v1.3.0. Use enable_monitoring instead."
     )]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "enableMBM",
+        alias = "enableMbm"
+    )]
     /// EnableMBM is the flag to indicate if the Intel RDT MBM is enabled. MBM (Memory Bandwidth Monitoring) supports monitoring of
     /// total and local memory bandwidth for the container.
     ///
@@ -1519,6 +2317,7 @@ pub struct LinuxIntelRdt {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxPersonality represents the Linux personality syscall input.
+#[non_exhaustive]
 pub struct LinuxPersonality {
     #[getset(get_copy = "pub", set = "pub")]
     /// Domain for the personality.
@@ -1534,6 +2333,7 @@ pub struct LinuxPersonality {
     Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, StrumDisplay, EnumString,
 )]
 /// Define domain and flags for LinuxPersonality.
+#[non_exhaustive]
 pub enum LinuxPersonalityDomain {
     #[serde(rename = "LINUX")]
     #[strum(serialize = "LINUX")]
@@ -1567,6 +2367,7 @@ pub enum LinuxPersonalityDomain {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxMemoryPolicy represents input for the set_mempolicy syscall.
+#[non_exhaustive]
 pub struct LinuxMemoryPolicy {
     #[getset(get_copy = "pub", set = "pub")]
     /// Mode for the set_mempolicy syscall.
@@ -1598,6 +2399,7 @@ impl Default for LinuxMemoryPolicy {
     Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, StrumDisplay, EnumString,
 )]
 /// MemoryPolicyModeType defines the memory policy mode.
+#[non_exhaustive]
 pub enum MemoryPolicyModeType {
     #[serde(rename = "MPOL_DEFAULT")]
     #[strum(serialize = "MPOL_DEFAULT")]
@@ -1638,6 +2440,7 @@ pub enum MemoryPolicyModeType {
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, StrumDisplay, EnumString)]
 /// MemoryPolicyFlagType defines the memory policy flags.
+#[non_exhaustive]
 pub enum MemoryPolicyFlagType {
     #[serde(rename = "MPOL_F_NUMA_BALANCING")]
     #[strum(serialize = "MPOL_F_NUMA_BALANCING")]
@@ -1675,6 +2478,7 @@ pub enum MemoryPolicyFlagType {
     build_fn(error = "OciSpecError")
 )]
 /// LinuxTimeOffset specifies the offset for Time Namespace
+#[non_exhaustive]
 pub struct LinuxTimeOffset {
     /// Secs is the offset of clock (in secs) in the container
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -1831,6 +2635,158 @@ mod tests {
         let invalid_nstype_str = "xxx";
         let unknown_nstype = LinuxNamespaceType::try_from(invalid_nstype_str);
         assert!(unknown_nstype.is_err());
+
+        let nstype_enum: LinuxNamespaceType = "time".parse().unwrap();
+        assert_eq!(nstype_enum, LinuxNamespaceType::Time);
+        assert!("xxx".parse::<LinuxNamespaceType>().is_err());
+    }
+
+    #[test]
+    fn ns_proc_path() {
+        assert_eq!(LinuxNamespaceType::Mount.proc_ns_name(), "mnt");
+        assert_eq!(
+            LinuxNamespaceType::Network.proc_ns_path(42),
+            PathBuf::from("/proc/42/ns/net")
+        );
+        assert_eq!(
+            LinuxNamespaceType::Time.proc_ns_path(1),
+            PathBuf::from("/proc/1/ns/time")
+        );
+    }
+
+    #[test]
+    fn resources_merge_update() {
+        let mut resources = LinuxResourcesBuilder::default()
+            .memory(
+                LinuxMemoryBuilder::default()
+                    .limit(1024i64)
+                    .swap(2048i64)
+                    .build()
+                    .unwrap(),
+            )
+            .pids(LinuxPidsBuilder::default().limit(10i64).build().unwrap())
+            .hugepage_limits(vec![LinuxHugepageLimitBuilder::default()
+                .page_size("2MB")
+                .limit(1i64)
+                .build()
+                .unwrap()])
+            .unified(HashMap::from([("io.max".to_string(), "max".to_string())]))
+            .build()
+            .unwrap();
+        let update = LinuxResourcesBuilder::default()
+            .memory(LinuxMemoryBuilder::default().swap(4096i64).build().unwrap())
+            .cpu(LinuxCpuBuilder::default().cpus("0-1").build().unwrap())
+            .hugepage_limits(vec![
+                LinuxHugepageLimitBuilder::default()
+                    .page_size("2MB")
+                    .limit(2i64)
+                    .build()
+                    .unwrap(),
+                LinuxHugepageLimitBuilder::default()
+                    .page_size("1GB")
+                    .limit(3i64)
+                    .build()
+                    .unwrap(),
+            ])
+            .unified(HashMap::from([("pids.max".to_string(), "5".to_string())]))
+            .build()
+            .unwrap();
+
+        resources.merge_update(&update);
+        let memory = resources.memory().as_ref().unwrap();
+        assert_eq!(memory.limit(), Some(1024));
+        assert_eq!(memory.swap(), Some(4096));
+        assert_eq!(
+            resources.cpu().as_ref().unwrap().cpus().as_deref(),
+            Some("0-1")
+        );
+        assert_eq!(resources.pids().as_ref().unwrap().limit(), 10);
+        let limits: Vec<_> = resources
+            .hugepage_limits()
+            .iter()
+            .flatten()
+            .map(|l| (l.page_size().as_str(), l.limit()))
+            .collect();
+        assert_eq!(limits, [("2MB", 2), ("1GB", 3)]);
+        assert_eq!(resources.unified().as_ref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn memory_builder_validation() {
+        assert!(LinuxMemoryBuilder::default()
+            .limit(1024i64)
+            .swap(-1i64)
+            .reservation(512i64)
+            .swappiness(60u64)
+            .build()
+            .is_ok());
+        assert!(LinuxMemoryBuilder::default()
+            .limit(-1i64)
+            .swap(10i64)
+            .build()
+            .is_ok());
+        assert!(LinuxMemoryBuilder::default()
+            .limit(1024i64)
+            .swap(0i64)
+            .build()
+            .is_ok());
+
+        let err = LinuxMemoryBuilder::default()
+            .limit(1024i64)
+            .swap(512i64)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "swap: memory+swap limit must not be lower than the memory limit \
+             (expected at least 1024, 0 or -1, got 512)"
+        );
+        assert!(LinuxMemoryBuilder::default()
+            .limit(1024i64)
+            .reservation(2048i64)
+            .build()
+            .is_err());
+        assert!(LinuxMemoryBuilder::default()
+            .swappiness(101u64)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn cpu_builder_validation() {
+        assert!(LinuxCpuBuilder::default()
+            .period(100000u64)
+            .quota(-1i64)
+            .shares(1024u64)
+            .realtime_runtime(950000i64)
+            .realtime_period(1000000u64)
+            .cpus("0-3,7")
+            .mems("")
+            .build()
+            .is_ok());
+
+        let err = LinuxCpuBuilder::default()
+            .period(100u64)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "period: value out of range (expected 1000-1000000, got 100)"
+        );
+        assert!(LinuxCpuBuilder::default().shares(1u64).build().is_err());
+        assert!(LinuxCpuBuilder::default().shares(0u64).build().is_ok());
+        assert!(LinuxCpuBuilder::default().quota(-2i64).build().is_err());
+        assert!(LinuxCpuBuilder::default()
+            .realtime_runtime(2000i64)
+            .realtime_period(1000u64)
+            .build()
+            .is_err());
+        for invalid in ["0-", "3-1", "a", "0,,1"] {
+            assert!(
+                LinuxCpuBuilder::default().cpus(invalid).build().is_err(),
+                "{invalid}"
+            );
+        }
     }
 
     // LinuxSeccompAction test cases
@@ -1896,6 +2852,36 @@ mod tests {
         assert_eq!(type_i.to_string(), "SCMP_ARCH_SHEB");
     }
 
+    #[test]
+    fn seccomp_native_arch_with_compat() {
+        let arches = Arch::native_with_compat();
+        assert!(!arches.is_empty());
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(
+            arches,
+            [Arch::ScmpArchX86_64, Arch::ScmpArchX86, Arch::ScmpArchX32]
+        );
+        #[cfg(target_arch = "aarch64")]
+        assert_eq!(arches, [Arch::ScmpArchAarch64, Arch::ScmpArchArm]);
+
+        assert_eq!(Arch::with_compat("amd64"), Arch::with_compat("x86_64"));
+        assert_eq!(
+            Arch::with_compat("mips64el"),
+            Some(
+                &[
+                    Arch::ScmpArchMipsel64,
+                    Arch::ScmpArchMipsel,
+                    Arch::ScmpArchMipsel64n32
+                ][..]
+            )
+        );
+        assert_eq!(
+            Arch::with_compat("s390x"),
+            Some(&[Arch::ScmpArchS390x, Arch::ScmpArchS390][..])
+        );
+        assert_eq!(Arch::with_compat("wasm32"), None);
+    }
+
     #[test]
     fn seccomp_arch_string_to_enum() {
         let arch_type_str = "SCMP_ARCH_X86_64";
@@ -1934,6 +2920,14 @@ mod tests {
         let arch_type_enum: Arch = arch_type_str.parse().unwrap();
         assert_eq!(arch_type_enum, Arch::ScmpArchSheb);
 
+        let arch_type_str = "SCMP_ARCH_RISCV64";
+        let arch_type_enum: Arch = arch_type_str.parse().unwrap();
+        assert_eq!(arch_type_enum, Arch::ScmpArchRiscv64);
+
+        let arch_type_str = "SCMP_ARCH_MIPSEL64N32";
+        let arch_type_enum: Arch = arch_type_str.parse().unwrap();
+        assert_eq!(arch_type_enum, Arch::ScmpArchMipsel64n32);
+
         let invalid_arch_str = "x";
         let unknown_arch = invalid_arch_str.parse::<Arch>();
         assert!(unknown_arch.is_err());
@@ -2162,4 +3156,91 @@ mod tests {
         assert_eq!(memory_policy.nodes, None);
         assert_eq!(memory_policy.flags, None);
     }
+
+    #[test]
+    fn test_linux_device_validation() {
+        let device = LinuxDeviceBuilder::default()
+            .path("/dev/fuse")
+            .typ(LinuxDeviceType::C)
+            .major(10)
+            .minor(229)
+            .file_mode(0o666u32)
+            .build()
+            .unwrap();
+        assert!(!device.is_default());
+        assert!(LinuxDeviceBuilder::default()
+            .path("/dev/fifo")
+            .typ(LinuxDeviceType::P)
+            .build()
+            .is_ok());
+
+        let path = |res: Result<LinuxDevice, OciSpecError>| match res {
+            Err(OciSpecError::Validation(err)) => err.path().to_string(),
+            res => panic!("expected a validation error, got {res:?}"),
+        };
+        let code = |res: Result<LinuxDevice, OciSpecError>| match res {
+            Err(OciSpecError::Validation(err)) => err.code(),
+            res => panic!("expected a validation error, got {res:?}"),
+        };
+        let builder = || {
+            LinuxDeviceBuilder::default()
+                .path("/dev/fuse")
+                .typ(LinuxDeviceType::C)
+                .major(10)
+                .minor(229)
+        };
+        assert_eq!(path(builder().typ(LinuxDeviceType::A).build()), "type");
+        assert_eq!(path(builder().path("/tmp/fuse").build()), "path");
+        assert_eq!(path(builder().path("/dev/../etc/passwd").build()), "path");
+        assert_eq!(path(builder().path("dev/fuse").build()), "path");
+        assert_eq!(path(builder().file_mode(0o10000u32).build()), "fileMode");
+        assert_eq!(
+            code(builder().path("/tmp/fuse").build()),
+            FindingCode::DevicePathInvalid
+        );
+        assert_eq!(
+            code(builder().file_mode(0o10000u32).build()),
+            FindingCode::ValueOutOfRange
+        );
+        assert_eq!(
+            path(
+                LinuxDeviceBuilder::default()
+                    .path("/dev/sda")
+                    .typ(LinuxDeviceType::B)
+                    .major(8)
+                    .build()
+            ),
+            "minor"
+        );
+
+        // A deserialized device always has numbers, missing ones are 0.
+        let sda: LinuxDevice =
+            serde_json::from_str(r#"{"path": "/dev/sda", "type": "b", "major": 8}"#).unwrap();
+        assert_eq!(sda.minor(), 0);
+        assert!(sda.validate().is_ok());
+        let invalid: LinuxDevice =
+            serde_json::from_str(r#"{"path": "/tmp/sda", "type": "b"}"#).unwrap();
+        assert_eq!(path(invalid.validate().map(|_| invalid)), "path");
+
+        let defaults = get_default_devices();
+        assert!(defaults.iter().all(LinuxDevice::is_default));
+        assert!(LinuxBuilder::default()
+            .devices(vec![device.clone(), defaults[0].clone()])
+            .build()
+            .is_ok());
+        let err = LinuxBuilder::default()
+            .devices(vec![device.clone(), device.clone()])
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("devices[1].path"));
+        let mut null = defaults[0].clone();
+        null.set_minor(4);
+        let err = LinuxBuilder::default()
+            .devices(vec![null])
+            .build()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("devices[0]: redefines a default device"));
+    }
 }
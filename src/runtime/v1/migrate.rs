@@ -0,0 +1,386 @@
+use super::Spec;
+use crate::error::{oci_error, Result};
+use serde_json::Value;
+use std::path::Path;
+
+/// A parsed `major.minor.patch` runtime spec version.
+type SpecVersion = (u64, u64, u64);
+
+/// Fields which were added to the runtime spec after 1.0.0, grouped by the
+/// version which introduced them. Paths use the serialized field names, `*`
+/// matches every element of an array.
+const VERSIONED_FIELDS: &[(SpecVersion, &[&str])] = &[
+    (
+        (1, 0, 1),
+        &["linux.resources.rdma", "linux.intelRdt.memBwSchema"],
+    ),
+    (
+        (1, 0, 2),
+        &[
+            "vm",
+            "hooks.createRuntime",
+            "hooks.createContainer",
+            "hooks.startContainer",
+            "linux.seccomp.flags",
+            "linux.intelRdt.closID",
+        ],
+    ),
+    (
+        (1, 1, 0),
+        &[
+            "domainname",
+            "zos",
+            "mounts.*.uidMappings",
+            "mounts.*.gidMappings",
+            "process.ioPriority",
+            "process.scheduler",
+            "process.user.umask",
+            "linux.personality",
+            "linux.timeOffsets",
+            "linux.resources.cpu.idle",
+            "linux.resources.cpu.burst",
+            "linux.resources.memory.checkBeforeUpdate",
+            "linux.resources.memory.useHierarchy",
+            "linux.resources.unified",
+            "linux.seccomp.defaultErrnoRet",
+            "linux.seccomp.listenerPath",
+            "linux.seccomp.listenerMetadata",
+            "linux.intelRdt.enableCMT",
+            "linux.intelRdt.enableMBM",
+        ],
+    ),
+    ((1, 2, 0), &["process.execCPUAffinity", "linux.netDevices"]),
+    (
+        (1, 3, 0),
+        &[
+            "linux.memoryPolicy",
+            "linux.intelRdt.schemata",
+            "linux.intelRdt.enableMonitoring",
+        ],
+    ),
+];
+
+/// Describes the changes made by [Spec::migrate_to].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MigrationReport {
+    from: String,
+    to: String,
+    dropped: Vec<String>,
+}
+
+impl MigrationReport {
+    /// The version of the spec before the migration.
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// The version of the spec after the migration.
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    /// The paths of the fields which had to be removed because the target
+    /// version does not know them, e.g. `process.scheduler` or
+    /// `mounts[2].uidMappings`.
+    pub fn dropped(&self) -> &[String] {
+        &self.dropped
+    }
+
+    /// Returns true if no field had to be removed.
+    pub fn is_lossless(&self) -> bool {
+        self.dropped.is_empty()
+    }
+}
+
+fn parse_version(version: &str) -> Result<SpecVersion> {
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let mut parts = core.split('.').map(|p| p.parse::<u64>());
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) => Ok((major, minor, patch)),
+        _ => Err(oci_error(format!(
+            "invalid runtime spec version: {version}"
+        ))),
+    }
+}
+
+fn remove_path(value: &mut Value, segments: &[&str], prefix: &str, dropped: &mut Vec<String>) {
+    let Some((first, rest)) = segments.split_first() else {
+        return;
+    };
+
+    if *first == "*" {
+        if let Value::Array(items) = value {
+            for (i, item) in items.iter_mut().enumerate() {
+                remove_path(item, rest, &format!("{prefix}[{i}]"), dropped);
+            }
+        }
+        return;
+    }
+
+    let Value::Object(map) = value else {
+        return;
+    };
+    let path = if prefix.is_empty() {
+        first.to_string()
+    } else {
+        format!("{prefix}.{first}")
+    };
+    if rest.is_empty() {
+        if map.remove(*first).is_some_and(|v| !v.is_null()) {
+            dropped.push(path);
+        }
+    } else if let Some(child) = map.get_mut(*first) {
+        remove_path(child, rest, &path, dropped);
+    }
+}
+
+impl Spec {
+    /// Migrate the spec to the runtime spec version `target`, e.g. to pass it
+    /// to a runtime which only supports an older version of the spec.
+    ///
+    /// When downgrading, fields which were introduced after `target` are
+    /// removed and listed in the returned [MigrationReport]. Upgrading only
+    /// updates the version, since newer versions are backwards compatible.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other) if
+    /// one of the versions cannot be parsed or if the major versions differ.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{SchedulerBuilder, Spec};
+    ///
+    /// let mut spec = Spec::default();
+    /// spec.process_mut()
+    ///     .as_mut()
+    ///     .unwrap()
+    ///     .set_scheduler(Some(SchedulerBuilder::default().build().unwrap()));
+    ///
+    /// let report = spec.migrate_to("1.0.2").unwrap();
+    /// assert_eq!(report.dropped(), ["process.scheduler"]);
+    /// assert_eq!(spec.version(), "1.0.2");
+    /// ```
+    pub fn migrate_to(&mut self, target: &str) -> Result<MigrationReport> {
+        let from = parse_version(&self.version)?;
+        let to = parse_version(target)?;
+        if from.0 != to.0 {
+            return Err(oci_error(format!(
+                "cannot migrate spec from version {} to {target}",
+                self.version
+            )));
+        }
+
+        let mut dropped = Vec::new();
+        let removed: Vec<&str> = VERSIONED_FIELDS
+            .iter()
+            .filter(|(introduced, _)| *introduced > to)
+            .flat_map(|(_, fields)| fields.iter().copied())
+            .collect();
+        if !removed.is_empty() {
+            let mut value = serde_json::to_value(&*self)?;
+            for field in removed {
+                let segments: Vec<&str> = field.split('.').collect();
+                remove_path(&mut value, &segments, "", &mut dropped);
+            }
+            if !dropped.is_empty() {
+                *self = serde_json::from_value(value)?;
+            }
+        }
+
+        let report = MigrationReport {
+            from: std::mem::replace(&mut self.version, target.to_string()),
+            to: target.to_string(),
+            dropped,
+        };
+        Ok(report)
+    }
+
+    /// Serialize the spec as of the runtime spec version `target`, leaving
+    /// the spec itself unchanged, see [Spec::migrate_to]. The returned report
+    /// lists the fields which are missing from the output, so that callers
+    /// can decide whether the spec is still usable with an older runtime.
+    /// # Errors
+    /// This function will return the errors of [Spec::migrate_to] or an
+    /// [OciSpecError::SerDe](crate::OciSpecError::SerDe) if the spec cannot
+    /// be serialized.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let mut spec = Spec::default();
+    /// spec.set_domainname(Some("example.com".to_string()));
+    ///
+    /// let (json, report) = spec.to_string_as_of("1.0.2").unwrap();
+    /// assert!(json.starts_with(r#"{"ociVersion":"1.0.2","#));
+    /// assert_eq!(report.dropped(), ["domainname"]);
+    /// assert!(spec.domainname().is_some());
+    /// ```
+    pub fn to_string_as_of(&self, target: &str) -> Result<(String, MigrationReport)> {
+        let mut spec = self.clone();
+        let report = spec.migrate_to(target)?;
+        spec.validate_utf8_paths()?;
+        Ok((serde_json::to_string(&spec)?, report))
+    }
+
+    /// Save the spec to the JSON file `path` as of the runtime spec version
+    /// `target`, leaving the spec itself unchanged, see
+    /// [Spec::to_string_as_of].
+    /// # Errors
+    /// This function will return the errors of [Spec::migrate_to] and
+    /// [Spec::save].
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let spec = Spec::load("config.json").unwrap();
+    /// let report = spec.save_as_of("config-1.0.2.json", "1.0.2").unwrap();
+    /// for field in report.dropped() {
+    ///     eprintln!("dropped {field}, which runtime spec 1.0.2 does not support");
+    /// }
+    /// ```
+    pub fn save_as_of<P: AsRef<Path>>(&self, path: P, target: &str) -> Result<MigrationReport> {
+        let mut spec = self.clone();
+        let report = spec.migrate_to(target)?;
+        spec.save(path)?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{LinuxCpuBuilder, LinuxResourcesBuilder};
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!(parse_version("1.0.2-dev").unwrap(), (1, 0, 2));
+        assert_eq!(parse_version("1.2.0").unwrap(), (1, 2, 0));
+        assert!(parse_version("1.2").is_err());
+        assert!(parse_version("latest").is_err());
+    }
+
+    #[test]
+    fn downgrade_drops_newer_fields() {
+        let mut spec = Spec::default();
+        spec.set_domainname(Some("example.com".to_string()));
+        let resources = LinuxResourcesBuilder::default()
+            .cpu(LinuxCpuBuilder::default().idle(1i64).build().unwrap())
+            .build()
+            .unwrap();
+        spec.linux_mut()
+            .as_mut()
+            .unwrap()
+            .set_resources(Some(resources));
+
+        let report = spec.migrate_to("1.0.2").unwrap();
+        assert_eq!(report.from(), "1.0.2-dev");
+        assert_eq!(report.dropped(), ["domainname", "linux.resources.cpu.idle"]);
+        assert!(spec.domainname().is_none());
+
+        let report = spec.migrate_to("1.2.0").unwrap();
+        assert!(report.is_lossless());
+        assert_eq!(spec.version(), "1.2.0");
+        assert!(spec.migrate_to("2.0.0").is_err());
+    }
+
+    #[test]
+    fn downgrade_drops_every_versioned_field() {
+        let spec: Spec = serde_json::from_value(serde_json::json!({
+            "ociVersion": "1.3.0",
+            "domainname": "example.com",
+            "mounts": [{
+                "destination": "/data",
+                "uidMappings": [{"containerID": 0, "hostID": 1000, "size": 1}],
+                "gidMappings": [{"containerID": 0, "hostID": 1000, "size": 1}]
+            }],
+            "process": {
+                "cwd": "/",
+                "user": {"uid": 0, "gid": 0, "umask": 18},
+                "ioPriority": {"class": "IOPRIO_CLASS_IDLE", "priority": 0},
+                "scheduler": {"policy": "SCHED_OTHER"},
+                "execCPUAffinity": {"initial": "0"}
+            },
+            "hooks": {
+                "createRuntime": [{"path": "/bin/true"}],
+                "createContainer": [{"path": "/bin/true"}],
+                "startContainer": [{"path": "/bin/true"}]
+            },
+            "linux": {
+                "personality": {"domain": "LINUX"},
+                "timeOffsets": {"monotonic": {"secs": 1}},
+                "resources": {
+                    "cpu": {"idle": 1, "burst": 1},
+                    "memory": {"checkBeforeUpdate": true, "useHierarchy": true},
+                    "unified": {"memory.high": "1G"},
+                    "rdma": {"mlx5_1": {"hcaHandles": 3}}
+                },
+                "seccomp": {
+                    "defaultAction": "SCMP_ACT_ALLOW",
+                    "defaultErrnoRet": 1,
+                    "flags": ["SECCOMP_FILTER_FLAG_LOG"],
+                    "listenerPath": "/run/seccomp.sock",
+                    "listenerMetadata": "metadata"
+                },
+                "intelRdt": {
+                    "closID": "guaranteed",
+                    "memBwSchema": "MB:0=20",
+                    "schemata": ["L3:0=ff"],
+                    "enableCMT": true,
+                    "enableMBM": true,
+                    "enableMonitoring": true
+                },
+                "netDevices": {"eth0": {"name": "eth1"}},
+                "memoryPolicy": {"mode": "MPOL_DEFAULT"}
+            },
+            "vm": {"kernel": {"path": "/boot/vmlinuz"}},
+            "zos": {"namespaces": [{"type": "mount"}]}
+        }))
+        .unwrap();
+
+        let introduced_after = |version: SpecVersion| {
+            let mut fields: Vec<String> = VERSIONED_FIELDS
+                .iter()
+                .filter(|(introduced, _)| *introduced > version)
+                .flat_map(|(_, fields)| fields.iter())
+                .map(|field| field.replace(".*", "[0]"))
+                .collect();
+            fields.sort();
+            fields
+        };
+        for target in ["1.0.0", "1.0.1", "1.0.2", "1.1.0", "1.2.0", "1.3.0"] {
+            let mut migrated = spec.clone();
+            let report = migrated.migrate_to(target).unwrap();
+            let mut dropped = report.dropped().to_vec();
+            dropped.sort();
+            assert_eq!(
+                dropped,
+                introduced_after(parse_version(target).unwrap()),
+                "{target}"
+            );
+        }
+        assert_eq!(
+            introduced_after((1, 0, 0)).len(),
+            VERSIONED_FIELDS.iter().map(|(_, f)| f.len()).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn serialize_as_of_older_version() {
+        let mut spec = Spec::default();
+        spec.set_domainname(Some("example.com".to_string()));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json");
+        let report = spec.save_as_of(&path, "1.0.2").unwrap();
+        assert_eq!(report.dropped(), ["domainname"]);
+        assert_eq!(spec.version(), "1.0.2-dev");
+        assert!(spec.domainname().is_some());
+
+        let saved = Spec::load(&path).unwrap();
+        assert_eq!(saved.version(), "1.0.2");
+        assert!(saved.domainname().is_none());
+
+        let (_, report) = spec.to_string_as_of("1.1.0").unwrap();
+        assert!(report.is_lossless());
+        assert!(spec.to_string_as_of("1.0").is_err());
+    }
+}
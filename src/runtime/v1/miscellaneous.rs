@@ -0,0 +1,647 @@
+use crate::error::{
+    file_io_error, FindingCode, OciSpecError, ValidationError, ValidationErrorKind,
+};
+use crate::runtime::LinuxIdMapping;
+use derive_builder::Builder;
+use getset::{CopyGetters, Getters, MutGetters, Setters};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use strum_macros::{Display as StrumDisplay, EnumString};
+
+#[derive(
+    Builder, Clone, CopyGetters, Debug, Deserialize, Eq, Getters, Setters, PartialEq, Serialize,
+)]
+#[builder(
+    default,
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(validate = "Self::validate", error = "OciSpecError")
+)]
+/// Root contains information about the container's root filesystem on the
+/// host.
+#[non_exhaustive]
+pub struct Root {
+    /// Path is the absolute path to the container's root filesystem.
+    #[serde(default)]
+    #[getset(get = "pub", set = "pub")]
+    path: PathBuf,
+
+    /// Readonly makes the root filesystem for the container readonly before
+    /// the process is executed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[getset(get_copy = "pub", set = "pub")]
+    readonly: Option<bool>,
+}
+
+/// Default path for container root is "./rootfs" from config.json, with
+/// readonly true
+impl Default for Root {
+    fn default() -> Self {
+        Root {
+            path: PathBuf::from("rootfs"),
+            readonly: true.into(),
+        }
+    }
+}
+
+impl Root {
+    /// Returns the absolute path of the root filesystem for the bundle
+    /// directory `bundle`, which relative paths are relative to. The path is
+    /// not required to exist and symbolic links are not resolved, see
+    /// [Spec::canonicalize_rootfs](crate::runtime::Spec::canonicalize_rootfs) for that.
+    /// # Errors
+    /// This function will return an [OciSpecError::Io] if the current
+    /// directory is needed for a relative `bundle` but cannot be determined.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::Root;
+    /// use std::path::Path;
+    ///
+    /// let root = Root::default();
+    /// assert_eq!(root.resolve("/bundle").unwrap(), Path::new("/bundle/rootfs"));
+    /// ```
+    pub fn resolve<P: AsRef<Path>>(&self, bundle: P) -> Result<PathBuf, OciSpecError> {
+        if self.path.is_absolute() {
+            return Ok(self.path.clone());
+        }
+        Ok(std::path::absolute(bundle.as_ref())?.join(&self.path))
+    }
+
+    /// Check that the root filesystem exists as a directory for the bundle
+    /// directory `bundle` and return its absolute path, see [Root::resolve].
+    /// Unless `allow_empty` is set, an empty directory is rejected as well,
+    /// which usually means that unpacking the image did not succeed.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo] if the root
+    /// filesystem cannot be read or an [OciSpecError::Validation] if it is
+    /// not a directory or empty.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let spec = Spec::load("bundle/config.json").unwrap();
+    /// let rootfs = spec.root().as_ref().unwrap().check("bundle", false).unwrap();
+    /// ```
+    pub fn check<P: AsRef<Path>>(
+        &self,
+        bundle: P,
+        allow_empty: bool,
+    ) -> Result<PathBuf, OciSpecError> {
+        let path = self.resolve(bundle)?;
+        let invalid = |kind, code, message| {
+            ValidationError::new(kind, "path", message)
+                .with_code(code)
+                .with_actual(path.display().to_string())
+        };
+        let metadata = fs::metadata(&path).map_err(file_io_error(&path))?;
+        if !metadata.is_dir() {
+            return Err(invalid(
+                ValidationErrorKind::InvalidFormat,
+                FindingCode::RootNotDirectory,
+                "root filesystem is not a directory",
+            )
+            .into());
+        }
+        if !allow_empty {
+            let mut entries = fs::read_dir(&path).map_err(file_io_error(&path))?;
+            if entries.next().is_none() {
+                return Err(invalid(
+                    ValidationErrorKind::Missing,
+                    FindingCode::RootEmpty,
+                    "root filesystem is empty",
+                )
+                .into());
+            }
+        }
+        Ok(path)
+    }
+}
+
+impl RootBuilder {
+    fn validate(&self) -> Result<(), OciSpecError> {
+        if self
+            .path
+            .as_ref()
+            .is_some_and(|path| path.as_os_str().is_empty())
+        {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Missing,
+                "path",
+                "root filesystem path must not be empty",
+            )
+            .with_code(FindingCode::RootPathEmpty)
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(
+    Builder,
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    Getters,
+    MutGetters,
+    Setters,
+    PartialEq,
+    Serialize,
+)]
+#[builder(
+    default,
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(error = "OciSpecError", validate = "Self::validate")
+)]
+#[getset(get_mut = "pub", get = "pub", set = "pub")]
+/// Mount specifies a mount for a container.
+#[non_exhaustive]
+pub struct Mount {
+    /// Destination is the absolute path where the mount will be placed in
+    /// the container.
+    destination: PathBuf,
+
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "type")]
+    /// Type specifies the mount kind.
+    typ: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Source specifies the source path of the mount.
+    source: Option<PathBuf>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Options are fstab style mount options.
+    options: Option<Vec<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "uidMappings"
+    )]
+    /// UID mappings for ID-mapped mounts (Linux 5.12+).  
+    ///  
+    /// Specifies how to map UIDs from the source filesystem to the destination mount point.  
+    /// This allows changing file ownership without calling chown.  
+    ///  
+    /// **Important**: If specified, gid_mappings MUST also be specified.  
+    /// The mount options SHOULD include "idmap" or "ridmap".  
+    ///  
+    /// See: <https://github.com/opencontainers/runtime-spec/blob/main/config.md#posix-platform-mounts>
+    uid_mappings: Option<Vec<LinuxIdMapping>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "gidMappings"
+    )]
+    /// GID mappings for ID-mapped mounts (Linux 5.12+).
+    ///
+    /// Specifies how to map GIDs from the source filesystem to the destination mount point.
+    /// This allows changing file group ownership without calling chown.
+    ///
+    /// **Important**: If specified, `uid_mappings` MUST also be specified.
+    /// The mount options SHOULD include `"idmap"` or `"ridmap"`.
+    ///
+    /// See: <https://github.com/opencontainers/runtime-spec/blob/main/config.md#posix-platform-mounts>
+    gid_mappings: Option<Vec<LinuxIdMapping>>,
+}
+
+impl Mount {
+    /// Convert the `Mount` back into a [`MountBuilder`], so that it can be modified and
+    /// validated again when calling `build()`.
+    pub fn into_builder(self) -> MountBuilder {
+        let Mount {
+            destination,
+            typ,
+            source,
+            options,
+            uid_mappings,
+            gid_mappings,
+        } = self;
+        MountBuilder {
+            destination: Some(destination),
+            typ: Some(typ),
+            source: Some(source),
+            options: Some(options),
+            uid_mappings: Some(uid_mappings),
+            gid_mappings: Some(gid_mappings),
+        }
+    }
+
+    /// Create a [`MountBuilder`] pre-populated with a copy of this mount.
+    pub fn to_builder(&self) -> MountBuilder {
+        self.clone().into_builder()
+    }
+
+    /// Returns true if the mount is read-only. Like for fstab, the last of
+    /// the `ro` and `rw` options wins, `rro` makes the mount read-only as well.
+    pub fn is_read_only(&self) -> bool {
+        self.has_option("ro") || self.has_option("rro")
+    }
+
+    /// Make the mount read-only or read-write, replacing any `ro` and `rw`
+    /// options. Read-write is the default, so no option is added for it.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        if let Some(options) = &mut self.options {
+            options.retain(|option| !matches!(option.as_str(), "ro" | "rw" | "rro" | "rrw"));
+        }
+        if read_only {
+            self.options.get_or_insert_with(Vec::new).push("ro".into());
+        }
+    }
+
+    /// Returns true if `option` is in effect for the mount.
+    ///
+    /// Flags are matched with their inverse, where the last one wins, e.g.
+    /// `nosuid` is in effect for the options `suid,nosuid` but not for
+    /// `nosuid,suid`. An option without value like `mode` matches any value
+    /// set for it, e.g. `mode=755`.
+    pub fn has_option(&self, option: &str) -> bool {
+        let inverse = inverse_mount_flag(option);
+        let mut in_effect = false;
+        for current in self.options.iter().flatten() {
+            let key = current
+                .split_once('=')
+                .map_or(current.as_str(), |(key, _)| key);
+            if current == option || (!option.contains('=') && key == option) {
+                in_effect = true;
+            } else if inverse.is_some_and(|inverse| current == inverse) {
+                in_effect = false;
+            }
+        }
+        in_effect
+    }
+
+    /// Returns the mount propagation set by the options, where the last one
+    /// wins, or `None` to use the propagation of the parent mount.
+    pub fn propagation(&self) -> Option<MountPropagation> {
+        self.options
+            .iter()
+            .flatten()
+            .rev()
+            .find_map(|option| option.parse().ok())
+    }
+
+    /// Replace the mount propagation set by the options.
+    pub fn set_propagation(&mut self, propagation: Option<MountPropagation>) {
+        if let Some(options) = &mut self.options {
+            options.retain(|option| option.parse::<MountPropagation>().is_err());
+        }
+        if let Some(propagation) = propagation {
+            self.options
+                .get_or_insert_with(Vec::new)
+                .push(propagation.to_string());
+        }
+    }
+}
+
+/// Returns the flag which reverts the mount flag `option`, if any.
+fn inverse_mount_flag(option: &str) -> Option<&'static str> {
+    const FLAGS: &[(&str, &str)] = &[
+        ("ro", "rw"),
+        ("rro", "rrw"),
+        ("suid", "nosuid"),
+        ("dev", "nodev"),
+        ("exec", "noexec"),
+        ("sync", "async"),
+        ("mand", "nomand"),
+        ("atime", "noatime"),
+        ("diratime", "nodiratime"),
+        ("relatime", "norelatime"),
+        ("strictatime", "nostrictatime"),
+        ("lazytime", "nolazytime"),
+        ("iversion", "noiversion"),
+        ("rsuid", "rnosuid"),
+        ("rdev", "rnodev"),
+        ("rexec", "rnoexec"),
+        ("ratime", "rnoatime"),
+        ("rdiratime", "rnodiratime"),
+        ("rrelatime", "rnorelatime"),
+        ("rstrictatime", "rnostrictatime"),
+    ];
+    FLAGS.iter().find_map(|(flag, inverse)| match option {
+        _ if option == *flag => Some(*inverse),
+        _ if option == *inverse => Some(*flag),
+        _ => None,
+    })
+}
+
+#[derive(
+    Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, StrumDisplay, EnumString,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+/// Mount propagation types, see
+/// <https://docs.kernel.org/filesystems/sharedsubtree.html>.
+#[non_exhaustive]
+pub enum MountPropagation {
+    /// Mount and unmount events propagate to and from the peer group.
+    Shared,
+    /// Like `Shared`, applied to all submounts as well.
+    RShared,
+    /// Mount and unmount events propagate from the master only.
+    Slave,
+    /// Like `Slave`, applied to all submounts as well.
+    RSlave,
+    /// No events propagate to or from the mount.
+    Private,
+    /// Like `Private`, applied to all submounts as well.
+    RPrivate,
+    /// Like `Private`, and the mount cannot be bind mounted.
+    Unbindable,
+    /// Like `Unbindable`, applied to all submounts as well.
+    RUnbindable,
+}
+
+impl MountPropagation {
+    /// Returns true if the propagation applies to all submounts as well.
+    pub fn is_recursive(&self) -> bool {
+        matches!(
+            self,
+            Self::RShared | Self::RSlave | Self::RPrivate | Self::RUnbindable
+        )
+    }
+}
+
+/// utility function to generate default config for mounts.
+pub fn get_default_mounts() -> Vec<Mount> {
+    vec![
+        Mount {
+            destination: PathBuf::from("/proc"),
+            typ: "proc".to_string().into(),
+            source: PathBuf::from("proc").into(),
+            options: None,
+            uid_mappings: None,
+            gid_mappings: None,
+        },
+        Mount {
+            destination: PathBuf::from("/dev"),
+            typ: "tmpfs".to_string().into(),
+            source: PathBuf::from("tmpfs").into(),
+            options: vec![
+                "nosuid".into(),
+                "strictatime".into(),
+                "mode=755".into(),
+                "size=65536k".into(),
+            ]
+            .into(),
+            uid_mappings: None,
+            gid_mappings: None,
+        },
+        Mount {
+            destination: PathBuf::from("/dev/pts"),
+            typ: "devpts".to_string().into(),
+            source: PathBuf::from("devpts").into(),
+            options: vec![
+                "nosuid".into(),
+                "noexec".into(),
+                "newinstance".into(),
+                "ptmxmode=0666".into(),
+                "mode=0620".into(),
+                "gid=5".into(),
+            ]
+            .into(),
+            uid_mappings: None,
+            gid_mappings: None,
+        },
+        Mount {
+            destination: PathBuf::from("/dev/shm"),
+            typ: "tmpfs".to_string().into(),
+            source: PathBuf::from("shm").into(),
+            options: vec![
+                "nosuid".into(),
+                "noexec".into(),
+                "nodev".into(),
+                "mode=1777".into(),
+                "size=65536k".into(),
+            ]
+            .into(),
+            uid_mappings: None,
+            gid_mappings: None,
+        },
+        Mount {
+            destination: PathBuf::from("/dev/mqueue"),
+            typ: "mqueue".to_string().into(),
+            source: PathBuf::from("mqueue").into(),
+            options: vec!["nosuid".into(), "noexec".into(), "nodev".into()].into(),
+            uid_mappings: None,
+            gid_mappings: None,
+        },
+        Mount {
+            destination: PathBuf::from("/sys"),
+            typ: "sysfs".to_string().into(),
+            source: PathBuf::from("sysfs").into(),
+            options: vec![
+                "nosuid".into(),
+                "noexec".into(),
+                "nodev".into(),
+                "ro".into(),
+            ]
+            .into(),
+            uid_mappings: None,
+            gid_mappings: None,
+        },
+        Mount {
+            destination: PathBuf::from("/sys/fs/cgroup"),
+            typ: "cgroup".to_string().into(),
+            source: PathBuf::from("cgroup").into(),
+            options: vec![
+                "nosuid".into(),
+                "noexec".into(),
+                "nodev".into(),
+                "relatime".into(),
+                "ro".into(),
+            ]
+            .into(),
+            uid_mappings: None,
+            gid_mappings: None,
+        },
+    ]
+}
+
+impl Mount {
+    /// Run the validation of [MountBuilder], e.g. for a deserialized value.
+    pub(crate) fn validate(&self) -> Result<(), OciSpecError> {
+        MountBuilder {
+            uid_mappings: Some(self.uid_mappings.clone()),
+            gid_mappings: Some(self.gid_mappings.clone()),
+            ..Default::default()
+        }
+        .validate()
+    }
+}
+
+impl MountBuilder {
+    fn validate(&self) -> Result<(), OciSpecError> {
+        let uid_specified = self
+            .uid_mappings
+            .as_ref()
+            .and_then(|v| v.as_ref())
+            .map(|v| !v.is_empty())
+            .unwrap_or(false);
+
+        let gid_specified = self
+            .gid_mappings
+            .as_ref()
+            .and_then(|v| v.as_ref())
+            .map(|v| !v.is_empty())
+            .unwrap_or(false);
+
+        if uid_specified && !gid_specified {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Missing,
+                "gidMappings",
+                "must be specified together with uidMappings",
+            )
+            .with_code(FindingCode::MountIdMappingsIncomplete)
+            .into());
+        }
+
+        if gid_specified && !uid_specified {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Missing,
+                "uidMappings",
+                "must be specified together with gidMappings",
+            )
+            .with_code(FindingCode::MountIdMappingsIncomplete)
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// utility function to generate default rootless config for mounts.
+// TODO(saschagrunert): remove once clippy does not report this false positive any more. We cannot
+// use `inspect` instead of `map` because we need to mutate the mounts.
+// Ref: https://github.com/rust-lang/rust-clippy/issues/13185
+#[allow(clippy::manual_inspect)]
+pub fn get_rootless_mounts() -> Vec<Mount> {
+    let mut mounts = get_default_mounts();
+    mounts
+        .iter_mut()
+        .find(|m| m.destination == Path::new("/dev/pts"))
+        .map(|m| {
+            if let Some(opts) = &mut m.options {
+                opts.retain(|o| o != "gid=5")
+            }
+            m
+        });
+    mounts
+        .iter_mut()
+        .find(|m| m.destination == Path::new("/sys"))
+        .map(|m| {
+            m.typ = Some("none".to_string());
+            m.source = Some("/sys".into());
+            if let Some(o) = m.options.as_mut() {
+                o.push("rbind".to_string())
+            }
+            m
+        });
+    mounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::LinuxIdMappingBuilder;
+
+    #[test]
+    fn mount_to_builder_revalidates() {
+        let mount = get_default_mounts()[0].clone();
+        assert_eq!(mount.to_builder().build().unwrap(), mount);
+
+        let mapping = LinuxIdMappingBuilder::default()
+            .host_id(1000u32)
+            .container_id(0u32)
+            .size(1u32)
+            .build()
+            .unwrap();
+        assert!(mount
+            .into_builder()
+            .uid_mappings(vec![mapping])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn mount_options() {
+        let mut mount = get_default_mounts()
+            .into_iter()
+            .find(|m| m.destination == Path::new("/sys"))
+            .unwrap();
+        assert!(mount.is_read_only());
+        assert!(mount.has_option("nosuid"));
+        assert!(!mount.has_option("suid"));
+        assert!(!mount.has_option("nosu"));
+        assert_eq!(mount.propagation(), None);
+
+        mount.set_read_only(false);
+        assert!(!mount.is_read_only());
+        assert!(!mount.options().as_ref().unwrap().contains(&"ro".into()));
+        mount.set_read_only(true);
+        assert!(mount.is_read_only());
+
+        mount.set_options(Some(vec![
+            "nosuid".into(),
+            "suid".into(),
+            "ro".into(),
+            "rw".into(),
+            "mode=755".into(),
+            "rprivate".into(),
+            "rslave".into(),
+        ]));
+        assert!(!mount.has_option("nosuid"));
+        assert!(mount.has_option("suid"));
+        assert!(!mount.is_read_only());
+        assert!(mount.has_option("mode"));
+        assert!(mount.has_option("mode=755"));
+        assert!(!mount.has_option("mode=700"));
+        assert_eq!(mount.propagation(), Some(MountPropagation::RSlave));
+        assert!(MountPropagation::RSlave.is_recursive());
+
+        mount.set_propagation(Some(MountPropagation::Shared));
+        assert_eq!(mount.propagation(), Some(MountPropagation::Shared));
+        assert!(!mount
+            .options()
+            .as_ref()
+            .unwrap()
+            .contains(&"rprivate".into()));
+        mount.set_propagation(None);
+        assert_eq!(mount.propagation(), None);
+    }
+
+    #[test]
+    fn root_helpers() {
+        assert!(RootBuilder::default().path("").build().is_err());
+        assert!(RootBuilder::default().build().is_ok());
+
+        let bundle = tempfile::tempdir().unwrap();
+        let root = Root::default();
+        let rootfs = bundle.path().join("rootfs");
+        assert_eq!(root.resolve(bundle.path()).unwrap(), rootfs);
+        let absolute = RootBuilder::default().path("/rootfs").build().unwrap();
+        assert_eq!(
+            absolute.resolve(bundle.path()).unwrap(),
+            Path::new("/rootfs")
+        );
+
+        assert!(matches!(
+            root.check(bundle.path(), true),
+            Err(OciSpecError::FileIo { .. })
+        ));
+        fs::create_dir(&rootfs).unwrap();
+        assert_eq!(root.check(bundle.path(), true).unwrap(), rootfs);
+        let err = root.check(bundle.path(), false).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("path: root filesystem is empty"));
+        fs::create_dir(rootfs.join("etc")).unwrap();
+        assert_eq!(root.check(bundle.path(), false).unwrap(), rootfs);
+    }
+}
@@ -0,0 +1,1179 @@
+//! Types and definitions of the 1.x releases of the [OCI runtime spec](https://github.com/opencontainers/runtime-spec).
+//!
+//! [`Spec`] represents the root object from the specification.
+
+use derive_builder::Builder;
+use getset::{Getters, MutGetters, Setters};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::error::{
+    file_io_error, file_serde_error, oci_error, with_parent, FindingCode, OciSpecError, Result,
+    ValidationError, ValidationErrorKind,
+};
+use crate::{annotations::validate_annotations, ParseOptions};
+
+mod capability;
+mod cgroup;
+mod features;
+mod fingerprint;
+mod hooks;
+mod lint;
+mod linux;
+mod migrate;
+mod miscellaneous;
+mod paths;
+mod platform;
+mod preset;
+mod process;
+#[cfg(feature = "run-options")]
+mod run_options;
+mod seccomp;
+mod solaris;
+mod state;
+mod test;
+#[cfg(feature = "user-lookup")]
+mod user_lookup;
+mod version;
+mod vm;
+mod windows;
+mod zos;
+
+// re-export for ease of use
+pub use capability::*;
+pub use cgroup::*;
+pub use features::*;
+pub use hooks::*;
+pub use lint::*;
+pub use linux::*;
+pub use migrate::*;
+pub use miscellaneous::*;
+pub use platform::*;
+pub use preset::*;
+pub use process::*;
+#[cfg(feature = "run-options")]
+pub use run_options::*;
+pub use seccomp::*;
+pub use solaris::*;
+pub use state::*;
+pub use version::*;
+pub use vm::*;
+pub use windows::*;
+pub use zos::*;
+
+/// `config.json` file root object.
+#[derive(
+    Builder, Clone, Debug, Deserialize, Getters, MutGetters, Setters, PartialEq, Eq, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[builder(
+    default,
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(validate = "Self::validate", error = "OciSpecError")
+)]
+#[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
+pub struct Spec {
+    #[serde(default, rename = "ociVersion")]
+    ///  MUST be in SemVer v2.0.0 format and specifies the version of the
+    /// Open Container Initiative  Runtime Specification with which
+    /// the bundle complies. The Open Container Initiative
+    ///  Runtime Specification follows semantic versioning and retains
+    /// forward and backward  compatibility within major versions.
+    /// For example, if a configuration is compliant with
+    ///  version 1.1 of this specification, it is compatible with all
+    /// runtimes that support any 1.1  or later release of this
+    /// specification, but is not compatible with a runtime that supports
+    ///  1.0 and not 1.1.
+    version: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Specifies the container's root filesystem. On Windows, for Windows
+    /// Server Containers, this field is REQUIRED. For Hyper-V
+    /// Containers, this field MUST NOT be set.
+    ///
+    /// On all other platforms, this field is REQUIRED.
+    root: Option<Root>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Specifies additional mounts beyond `root`. The runtime MUST mount
+    /// entries in the listed order.
+    ///
+    /// For Linux, the parameters are as documented in
+    /// [`mount(2)`](http://man7.org/linux/man-pages/man2/mount.2.html) system call man page. For
+    /// Solaris, the mount entry corresponds to the 'fs' resource in the
+    /// [`zonecfg(1M)`](http://docs.oracle.com/cd/E86824_01/html/E54764/zonecfg-1m.html) man page.
+    mounts: Option<Vec<Mount>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Specifies the container process. This property is REQUIRED when
+    /// [`start`](https://github.com/opencontainers/runtime-spec/blob/master/runtime.md#start) is
+    /// called.
+    process: Option<Process>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Specifies the container's hostname as seen by processes running
+    /// inside the container. On Linux, for example, this will
+    /// change the hostname in the container [UTS namespace](http://man7.org/linux/man-pages/man7/namespaces.7.html). Depending on your
+    /// [namespace
+    /// configuration](https://github.com/opencontainers/runtime-spec/blob/master/config-linux.md#namespaces),
+    /// the container UTS namespace may be the runtime UTS namespace.
+    hostname: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Specifies the container's domainame as seen by processes running
+    /// inside the container. On Linux, for example, this will
+    /// change the domainame in the container [UTS namespace](http://man7.org/linux/man-pages/man7/namespaces.7.html). Depending on your
+    /// [namespace
+    /// configuration](https://github.com/opencontainers/runtime-spec/blob/master/config-linux.md#namespaces),
+    /// the container UTS namespace may be the runtime UTS namespace.
+    domainname: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Hooks allow users to specify programs to run before or after various
+    /// lifecycle events. Hooks MUST be called in the listed order.
+    /// The state of the container MUST be passed to hooks over
+    /// stdin so that they may do work appropriate to the current state of
+    /// the container.
+    hooks: Option<Hooks>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
+    /// Annotations contains arbitrary metadata for the container. This
+    /// information MAY be structured or unstructured. Annotations
+    /// MUST be a key-value map. If there are no annotations then
+    /// this property MAY either be absent or an empty map.
+    ///
+    /// Keys MUST be strings. Keys MUST NOT be an empty string. Keys SHOULD
+    /// be named using a reverse domain notation - e.g.
+    /// com.example.myKey. Keys using the org.opencontainers
+    /// namespace are reserved and MUST NOT be used by subsequent
+    /// specifications. Runtimes MUST handle unknown annotation keys
+    /// like any other unknown property.
+    ///
+    /// Values MUST be strings. Values MAY be an empty string.
+    annotations: Option<HashMap<String, String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Linux is platform-specific configuration for Linux based containers.
+    linux: Option<Linux>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Solaris is platform-specific configuration for Solaris based
+    /// containers.
+    solaris: Option<Solaris>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Windows is platform-specific configuration for Windows based
+    /// containers.
+    windows: Option<Windows>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// VM specifies configuration for Virtual Machine based containers.
+    vm: Option<VM>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// z/OS is platform-specific configuration for z/OS based containers.
+    zos: Option<ZOS>,
+
+    #[deprecated(
+        since = "0.10.0",
+        note = "uid_mappings on the top-level Spec struct has never existed in the OCI runtime spec. Use Linux::uid_mappings or Mount::uid_mappings instead."
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// UID mappings used for changing file owners w/o calling chown, fs should support it.
+    /// Every mount point could have its own mapping.
+    ///
+    /// # Deprecated
+    /// This field has never existed on the top-level `Spec` struct in the OCI runtime spec.
+    /// Use [`Linux::uid_mappings`] or [`Mount::uid_mappings`] instead.
+    uid_mappings: Option<Vec<LinuxIdMapping>>,
+
+    #[deprecated(
+        since = "0.10.0",
+        note = "gid_mappings on the top-level Spec struct has never existed in the OCI runtime spec. Use Linux::gid_mappings or Mount::gid_mappings instead."
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// GID mappings used for changing file owners w/o calling chown, fs should support it.
+    /// Every mount point could have its own mapping.
+    ///
+    /// # Deprecated
+    /// This field has never existed on the top-level `Spec` struct in the OCI runtime spec.
+    /// Use [`Linux::gid_mappings`] or [`Mount::gid_mappings`] instead.
+    gid_mappings: Option<Vec<LinuxIdMapping>>,
+}
+
+// This gives a basic boilerplate for Spec that can be used calling
+// Default::default(). The values given are similar to the defaults seen in
+// docker and runc, it creates a containerized shell! (see respective types
+// default impl for more info)
+#[allow(deprecated)]
+impl Default for Spec {
+    fn default() -> Self {
+        Spec {
+            // Defaults to most current oci version
+            version: String::from("1.0.2-dev"),
+            process: Some(Default::default()),
+            root: Some(Default::default()),
+            hostname: "youki".to_string().into(),
+            domainname: None,
+            mounts: get_default_mounts().into(),
+            // Defaults to empty metadata
+            annotations: Some(Default::default()),
+            linux: Some(Default::default()),
+            hooks: None,
+            solaris: None,
+            windows: None,
+            vm: None,
+            zos: None,
+            uid_mappings: None,
+            gid_mappings: None,
+        }
+    }
+}
+
+impl SpecBuilder {
+    fn validate(&self) -> Result<()> {
+        validate_process(
+            self.process.as_ref().and_then(Option::as_ref),
+            self.windows.as_ref().is_some_and(Option::is_some),
+        )?;
+        if let Some(Some(annotations)) = &self.annotations {
+            validate_annotations(annotations)?;
+        }
+        validate_mount_mappings(
+            self.mounts
+                .as_ref()
+                .and_then(Option::as_deref)
+                .unwrap_or_default(),
+            self.linux.as_ref().and_then(Option::as_ref),
+        )
+    }
+
+    /// Append a single mount to the mounts set on this builder.
+    pub fn add_mount(self, mount: Mount) -> Self {
+        self.extend_mounts([mount])
+    }
+
+    /// Append the provided mounts to the mounts set on this builder. Note that
+    /// mounts which were never set on the builder start out empty rather than
+    /// with the mounts of [`Spec::default`].
+    pub fn extend_mounts<I: IntoIterator<Item = Mount>>(mut self, mounts: I) -> Self {
+        self.mounts
+            .get_or_insert_with(|| Some(Vec::new()))
+            .get_or_insert_with(Vec::new)
+            .extend(mounts);
+        self
+    }
+
+    /// Add a single annotation, keeping any annotations set before.
+    pub fn add_annotation(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extend_annotations([(key.into(), value.into())])
+    }
+
+    /// Extend the annotations with the provided key-value pairs, keeping any annotations set
+    /// before.
+    pub fn extend_annotations<I>(mut self, annotations: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        self.annotations
+            .get_or_insert_with(|| Some(HashMap::new()))
+            .get_or_insert_with(HashMap::new)
+            .extend(annotations);
+        self
+    }
+}
+
+/// Check the ID mappings of mounts against the user namespace of the
+/// container: mappings of a mount are only applied along with a user
+/// namespace or the `idmap` and `ridmap` options, and these options without
+/// mappings of the mount use the mappings of the user namespace, which must
+/// therefore be known.
+fn validate_mount_mappings(mounts: &[Mount], linux: Option<&Linux>) -> Result<()> {
+    let user_namespace = linux
+        .and_then(|linux| linux.namespaces().as_ref())
+        .and_then(|namespaces| {
+            namespaces
+                .iter()
+                .find(|namespace| namespace.typ() == LinuxNamespaceType::User)
+        });
+    let has_mappings = |mappings: Option<&Vec<LinuxIdMapping>>| {
+        mappings.is_some_and(|mappings| !mappings.is_empty())
+    };
+
+    for (i, mount) in mounts.iter().enumerate() {
+        let idmap = mount.has_option("idmap") || mount.has_option("ridmap");
+        if has_mappings(mount.uid_mappings().as_ref())
+            || has_mappings(mount.gid_mappings().as_ref())
+        {
+            if user_namespace.is_none() && !idmap {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Conflict,
+                    format!("mounts[{i}].uidMappings"),
+                    "ID mappings of a mount require a user namespace or the idmap option",
+                )
+                .with_code(FindingCode::MountIdMappingsWithoutUserns)
+                .into());
+            }
+            continue;
+        }
+        if !idmap {
+            continue;
+        }
+
+        let Some(user_namespace) = user_namespace else {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Missing,
+                format!("mounts[{i}].uidMappings"),
+                "idmapped mount without ID mappings requires a user namespace",
+            )
+            .with_code(FindingCode::IdmapMountWithoutUserns)
+            .into());
+        };
+        // The mappings of a joined user namespace are not known.
+        if user_namespace.path().is_some() {
+            continue;
+        }
+        for (path, mappings) in [
+            (
+                "linux.uidMappings",
+                linux.and_then(|linux| linux.uid_mappings().as_ref()),
+            ),
+            (
+                "linux.gidMappings",
+                linux.and_then(|linux| linux.gid_mappings().as_ref()),
+            ),
+        ] {
+            if !has_mappings(mappings) {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Missing,
+                    path,
+                    format!("required by the idmapped mount mounts[{i}] without ID mappings"),
+                )
+                .with_code(FindingCode::IdmapMountMappingsMissing)
+                .into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the process settings which depend on the platform of the spec,
+/// rejecting POSIX only settings for a spec with a `windows` section.
+fn validate_process(process: Option<&Process>, windows: bool) -> Result<()> {
+    let Some(process) = process else {
+        return Ok(());
+    };
+    process
+        .validate_console_size()
+        .map_err(with_parent("process"))?;
+    if windows {
+        process.validate_windows().map_err(with_parent("process"))?;
+    }
+
+    Ok(())
+}
+
+impl Spec {
+    /// Load a new `Spec` from the provided JSON file `path`.
+    ///
+    /// With the `jsonc` feature enabled, comments and trailing commas are
+    /// accepted as well, as found in hand written bundle configs. Saving a
+    /// spec always writes standard JSON.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo] if the spec does not exist or an
+    /// [OciSpecError::FileSerDe] if it is invalid.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let spec = Spec::load("config.json").unwrap();
+    /// ```
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = fs::File::open(path).map_err(file_io_error(path))?;
+        let reader = BufReader::new(file);
+        #[cfg(feature = "jsonc")]
+        let s = crate::jsonc::from_reader(reader).map_err(|err| match err {
+            OciSpecError::Io(err) => file_io_error(path)(err),
+            OciSpecError::SerDe(err) => file_serde_error(path)(err),
+            err => err,
+        })?;
+        #[cfg(not(feature = "jsonc"))]
+        let s = serde_json::from_reader(reader).map_err(file_serde_error(path))?;
+        Ok(s)
+    }
+
+    /// Load a new `Spec` from the provided JSON file `path`, enforcing the
+    /// limits of `options`.
+    /// # Errors
+    /// This function will return the errors of [Spec::load] or an
+    /// [OciSpecError::Validation] if a limit is exceeded.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::runtime::Spec;
+    /// use oci_spec::ParseOptionsBuilder;
+    ///
+    /// let options = ParseOptionsBuilder::default()
+    ///     .max_bytes(1024 * 1024u64)
+    ///     .build()
+    ///     .unwrap();
+    /// let spec = Spec::load_with_options("config.json", &options).unwrap();
+    /// ```
+    pub fn load_with_options<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Self> {
+        crate::parse::from_file_with_options(path, options)
+    }
+
+    /// Load a new `Spec` from a stream, enforcing the limits of `options`.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe] if the spec is
+    /// invalid or an [OciSpecError::Validation] if a limit is exceeded.
+    pub fn from_reader_with_options<R: Read>(reader: R, options: &ParseOptions) -> Result<Self> {
+        crate::parse::from_reader_with_options(reader, options)
+    }
+
+    /// Load a new `Spec` from the provided JSON file `path` and run the
+    /// semantic validation of the builders on it, see [Spec::validate].
+    /// # Errors
+    /// This function will return the errors of [Spec::load] or an
+    /// [OciSpecError::Validation] if the spec does not pass validation.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let spec = Spec::load_validated("config.json").unwrap();
+    /// ```
+    pub fn load_validated<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let spec = Self::load(path)?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Run the semantic validation of the builders, e.g. [SpecBuilder] and
+    /// [MountBuilder], on the spec and its nested values.
+    ///
+    /// Deserialization does not run the validation of the builders, so a
+    /// loaded or modified spec may contain values the builders reject. The
+    /// path of a returned error is relative to the spec, e.g.
+    /// `mounts[0].gidMappings`.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] for the first
+    /// value which does not pass validation.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let spec: Spec = serde_json::from_str(r#"{
+    ///     "ociVersion": "1.0.2",
+    ///     "mounts": [{"destination": "/data", "uidMappings": [
+    ///         {"containerID": 0, "hostID": 1000, "size": 1}
+    ///     ]}]
+    /// }"#).unwrap();
+    /// let err = spec.validate().unwrap_err();
+    /// assert!(err.to_string().starts_with("mounts[0].gidMappings"));
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        validate_process(self.process.as_ref(), self.windows.is_some())?;
+        if let Some(annotations) = &self.annotations {
+            validate_annotations(annotations)?;
+        }
+        for (i, mount) in self.mounts.iter().flatten().enumerate() {
+            mount
+                .validate()
+                .map_err(with_parent(format!("mounts[{i}]")))?;
+        }
+        validate_mount_mappings(
+            self.mounts.as_deref().unwrap_or_default(),
+            self.linux.as_ref(),
+        )?;
+        if let Some(affinity) = self
+            .process
+            .as_ref()
+            .and_then(|process| process.exec_cpu_affinity().as_ref())
+        {
+            affinity
+                .validate()
+                .map_err(with_parent("process.execCPUAffinity"))?;
+        }
+        if let Some(linux) = &self.linux {
+            linux.validate().map_err(with_parent("linux"))?;
+            for (i, device) in linux.devices().iter().flatten().enumerate() {
+                device
+                    .validate()
+                    .map_err(with_parent(format!("linux.devices[{i}]")))?;
+            }
+        }
+        if let Some(resources) = self
+            .linux
+            .as_ref()
+            .and_then(|linux| linux.resources().as_ref())
+        {
+            if let Some(memory) = resources.memory() {
+                memory
+                    .validate()
+                    .map_err(with_parent("linux.resources.memory"))?;
+            }
+            if let Some(cpu) = resources.cpu() {
+                cpu.validate().map_err(with_parent("linux.resources.cpu"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Save a `Spec` to the provided JSON file `path`.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo] if a file cannot be created at the provided
+    /// path, an [OciSpecError::Validation] if a path of the spec is not valid UTF-8 or an
+    /// [OciSpecError::FileSerDe] if the spec cannot be serialized.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let mut spec = Spec::load("config.json").unwrap();
+    /// spec.save("my_config.json").unwrap();
+    /// ```
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.validate_utf8_paths()?;
+        let path = path.as_ref();
+        let file = fs::File::create(path).map_err(file_io_error(path))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self).map_err(file_serde_error(path))?;
+        writer.flush().map_err(file_io_error(path))?;
+        Ok(())
+    }
+
+    /// Attempts to write the spec to a string in the style of Go's
+    /// `json.Marshal` of the equivalent Go struct: in Go field order,
+    /// without zero valued `omitempty` fields and with Go string escaping.
+    /// Only the top level objects and the nested structs known to this
+    /// crate are reordered, other objects are written with sorted keys.
+    /// The output is not checked against the Go implementations and is
+    /// not guaranteed to be byte-identical to theirs.
+    /// # Errors
+    /// This function will return an [OciSpecError::SerDe] if the spec cannot
+    /// be serialized.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let json = Spec::default().to_string_go().unwrap();
+    /// assert!(json.starts_with(r#"{"ociVersion":"1.0.2-dev","process":{"user""#));
+    /// ```
+    pub fn to_string_go(&self) -> Result<String> {
+        self.validate_utf8_paths()?;
+        crate::go_compat::to_string_go(self, &crate::go_compat::runtime::SPEC)
+    }
+
+    /// Attempts to write the spec to a stream in the style of Go's
+    /// `json.Marshal`, see [Self::to_string_go].
+    /// # Errors
+    /// This function will return an [OciSpecError::Io] or
+    /// [OciSpecError::SerDe] if the spec cannot be written.
+    pub fn to_writer_go<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.validate_utf8_paths()?;
+        crate::go_compat::to_writer_go(self, writer, &crate::go_compat::runtime::SPEC)
+    }
+
+    /// Canonicalize the `root.path` of the `Spec` for the provided `bundle`.
+    pub fn canonicalize_rootfs<P: AsRef<Path>>(&mut self, bundle: P) -> Result<()> {
+        let root = self
+            .root
+            .as_ref()
+            .ok_or_else(|| oci_error("no root path provided for canonicalization"))?;
+        let path = Self::canonicalize_path(bundle, root.path())?;
+        self.root = Some(
+            RootBuilder::default()
+                .path(path)
+                .readonly(root.readonly().unwrap_or(false))
+                .build()
+                .map_err(|_| oci_error("failed to set canonicalized root"))?,
+        );
+        Ok(())
+    }
+
+    /// Return default rootless spec.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let spec = Spec::rootless(1000, 1000);
+    /// ```
+    pub fn rootless(uid: u32, gid: u32) -> Self {
+        Self {
+            mounts: get_rootless_mounts().into(),
+            linux: Some(Linux::rootless(uid, gid)),
+            ..Default::default()
+        }
+    }
+
+    /// Take the root filesystem out of the `Spec`, leaving `None` in its place.
+    pub fn take_root(&mut self) -> Option<Root> {
+        self.root.take()
+    }
+
+    /// Take the mounts out of the `Spec`, leaving `None` in its place.
+    pub fn take_mounts(&mut self) -> Option<Vec<Mount>> {
+        self.mounts.take()
+    }
+
+    /// Take the process out of the `Spec`, leaving `None` in its place.
+    pub fn take_process(&mut self) -> Option<Process> {
+        self.process.take()
+    }
+
+    /// Take the hooks out of the `Spec`, leaving `None` in its place.
+    pub fn take_hooks(&mut self) -> Option<Hooks> {
+        self.hooks.take()
+    }
+
+    /// Take the annotations out of the `Spec`, leaving `None` in its place.
+    pub fn take_annotations(&mut self) -> Option<HashMap<String, String>> {
+        self.annotations.take()
+    }
+
+    /// Take the Linux platform configuration out of the `Spec`, leaving `None` in its place.
+    pub fn take_linux(&mut self) -> Option<Linux> {
+        self.linux.take()
+    }
+
+    /// Take the Solaris platform configuration out of the `Spec`, leaving `None` in its place.
+    pub fn take_solaris(&mut self) -> Option<Solaris> {
+        self.solaris.take()
+    }
+
+    /// Take the Windows platform configuration out of the `Spec`, leaving `None` in its place.
+    pub fn take_windows(&mut self) -> Option<Windows> {
+        self.windows.take()
+    }
+
+    /// Take the VM configuration out of the `Spec`, leaving `None` in its place.
+    pub fn take_vm(&mut self) -> Option<VM> {
+        self.vm.take()
+    }
+
+    /// Take the z/OS platform configuration out of the `Spec`, leaving `None` in its place.
+    pub fn take_zos(&mut self) -> Option<ZOS> {
+        self.zos.take()
+    }
+
+    /// Convert the `Spec` back into a [`SpecBuilder`], so that it can be modified and
+    /// validated again when calling `build()`.
+    #[allow(deprecated)]
+    pub fn into_builder(self) -> SpecBuilder {
+        let Spec {
+            version,
+            root,
+            mounts,
+            process,
+            hostname,
+            domainname,
+            hooks,
+            annotations,
+            linux,
+            solaris,
+            windows,
+            vm,
+            zos,
+            uid_mappings,
+            gid_mappings,
+        } = self;
+        SpecBuilder {
+            version: Some(version),
+            root: Some(root),
+            mounts: Some(mounts),
+            process: Some(process),
+            hostname: Some(hostname),
+            domainname: Some(domainname),
+            hooks: Some(hooks),
+            annotations: Some(annotations),
+            linux: Some(linux),
+            solaris: Some(solaris),
+            windows: Some(windows),
+            vm: Some(vm),
+            zos: Some(zos),
+            uid_mappings: Some(uid_mappings),
+            gid_mappings: Some(gid_mappings),
+        }
+    }
+
+    /// Create a [`SpecBuilder`] pre-populated with a copy of this spec.
+    pub fn to_builder(&self) -> SpecBuilder {
+        self.clone().into_builder()
+    }
+
+    fn canonicalize_path<B, P>(bundle: B, path: P) -> Result<PathBuf>
+    where
+        B: AsRef<Path>,
+        P: AsRef<Path>,
+    {
+        Ok(if path.as_ref().is_absolute() {
+            fs::canonicalize(path.as_ref())?
+        } else {
+            let canonical_bundle_path = fs::canonicalize(&bundle)?;
+            fs::canonicalize(canonical_bundle_path.join(path.as_ref()))?
+        })
+    }
+}
+
+impl TryFrom<serde_json::Value> for Spec {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a JSON value into a runtime spec.
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        crate::from_value(value)
+    }
+}
+
+impl TryFrom<Spec> for serde_json::Value {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a runtime spec into a JSON value.
+    fn try_from(spec: Spec) -> Result<Self> {
+        crate::to_value(&spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_rootfs() {
+        let rootfs_name = "rootfs";
+        let bundle = tempfile::tempdir().expect("failed to create tmp test bundle dir");
+
+        // On macOS, `$TMPDIR` may not point to canonicalized path.
+        // ```
+        // $ echo $TMPDIR; realpath $TMPDIR
+        // /var/folders/_h/j_17023n23s3_50cq_gwhrrc0000gq/T/
+        // /private/var/folders/_h/j_17023n23s3_50cq_gwhrrc0000gq/T
+        // ```
+        let bundle = fs::canonicalize(bundle.path()).expect("failed to canonicalize bundle");
+
+        let rootfs_absolute_path = bundle.join(rootfs_name);
+        assert!(
+            rootfs_absolute_path.is_absolute(),
+            "rootfs path is not absolute path"
+        );
+        fs::create_dir_all(&rootfs_absolute_path).expect("failed to create the testing rootfs");
+        {
+            // Test the case with absolute path
+            let mut spec = SpecBuilder::default()
+                .root(
+                    RootBuilder::default()
+                        .path(rootfs_absolute_path.clone())
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap();
+
+            spec.canonicalize_rootfs(&bundle)
+                .expect("failed to canonicalize rootfs");
+
+            assert_eq!(
+                &rootfs_absolute_path,
+                spec.root.expect("no root in spec").path()
+            );
+        }
+        {
+            // Test the case with relative path
+            let mut spec = SpecBuilder::default()
+                .root(RootBuilder::default().path(rootfs_name).build().unwrap())
+                .build()
+                .unwrap();
+
+            spec.canonicalize_rootfs(&bundle)
+                .expect("failed to canonicalize rootfs");
+
+            assert_eq!(
+                &rootfs_absolute_path,
+                spec.root.expect("no root in spec").path()
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_save() {
+        let spec = Spec {
+            ..Default::default()
+        };
+        let test_dir = tempfile::tempdir().expect("failed to create tmp test dir");
+        let spec_path = test_dir.keep().join("config.json");
+
+        // Test first save the default config, and then load the saved config.
+        // The before and after should be the same.
+        spec.save(&spec_path).expect("failed to save spec");
+        let loaded_spec = Spec::load(&spec_path).expect("failed to load the saved spec.");
+        assert_eq!(
+            spec, loaded_spec,
+            "The saved spec is not the same as the loaded spec"
+        );
+    }
+
+    #[test]
+    fn test_rootless() {
+        const UID: u32 = 1000;
+        const GID: u32 = 1000;
+
+        let spec = Spec::default();
+        let spec_rootless = Spec::rootless(UID, GID);
+        assert!(
+            spec != spec_rootless,
+            "default spec and rootless spec should be different"
+        );
+
+        // Check rootless linux object.
+        let linux = spec_rootless
+            .linux
+            .expect("linux object should not be empty");
+        let uid_mappings = linux
+            .uid_mappings()
+            .clone()
+            .expect("uid mappings should not be empty");
+        let gid_mappings = linux
+            .gid_mappings()
+            .clone()
+            .expect("gid mappings should not be empty");
+        let namespaces = linux
+            .namespaces()
+            .clone()
+            .expect("namespaces should not be empty");
+        assert_eq!(uid_mappings.len(), 1, "uid mappings length should be 1");
+        assert_eq!(
+            uid_mappings[0].host_id(),
+            UID,
+            "uid mapping host id should be as defined"
+        );
+        assert_eq!(gid_mappings.len(), 1, "gid mappings length should be 1");
+        assert_eq!(
+            gid_mappings[0].host_id(),
+            GID,
+            "gid mapping host id should be as defined"
+        );
+        assert!(
+            !namespaces
+                .iter()
+                .any(|ns| ns.typ() == LinuxNamespaceType::Network),
+            "rootless spec should not contain network namespace type"
+        );
+        assert!(
+            namespaces
+                .iter()
+                .any(|ns| ns.typ() == LinuxNamespaceType::User),
+            "rootless spec should contain user namespace type"
+        );
+        assert!(
+            linux.resources().is_none(),
+            "resources in rootless spec should be empty"
+        );
+
+        // Check rootless mounts.
+        let mounts = spec_rootless.mounts.expect("mounts should not be empty");
+        assert!(
+            !mounts.iter().any(|m| {
+                if m.destination().to_string_lossy() == "/dev/pts" {
+                    m.options()
+                        .clone()
+                        .expect("options should not be empty")
+                        .iter()
+                        .any(|o| o == "gid=5")
+                } else {
+                    false
+                }
+            }),
+            "gid=5 in rootless should not be present"
+        );
+        let sys_mount = mounts
+            .iter()
+            .find(|m| m.destination().to_string_lossy() == "/sys")
+            .expect("sys mount should be present");
+        assert_eq!(
+            sys_mount.typ(),
+            &Some("none".to_string()),
+            "type should be changed in sys mount"
+        );
+        assert_eq!(
+            sys_mount
+                .source()
+                .clone()
+                .expect("source should not be empty in sys mount")
+                .to_string_lossy(),
+            "/sys",
+            "source should be changed in sys mount"
+        );
+        assert!(
+            sys_mount
+                .options()
+                .clone()
+                .expect("options should not be empty in sys mount")
+                .iter()
+                .any(|o| o == "rbind"),
+            "rbind option should be present in sys mount"
+        );
+
+        // Check that some other objects have same values.
+        assert!(spec.process == spec_rootless.process);
+        assert!(spec.root == spec_rootless.root);
+        assert!(spec.hooks == spec_rootless.hooks);
+    }
+
+    #[test]
+    fn test_validate_deserialized() {
+        assert!(Spec::default().validate().is_ok());
+
+        let spec: Spec = serde_json::from_str(
+            r#"{"ociVersion": "1.0.2", "linux": {"resources": {"cpu": {"period": 10}}}}"#,
+        )
+        .unwrap();
+        let err = spec.validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("linux.resources.cpu.period: value out of range"));
+
+        let spec: Spec = serde_json::from_str(
+            r#"{"ociVersion": "1.0.2", "linux": {"resources": {"memory": {"swappiness": 101}}}}"#,
+        )
+        .unwrap();
+        let err = spec.validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("linux.resources.memory.swappiness"));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json");
+        let mut spec = Spec::default();
+        let mut mount = spec.mounts().as_ref().unwrap()[1].clone();
+        mount.set_uid_mappings(Some(vec![LinuxIdMapping::default()]));
+        spec.mounts_mut().as_mut().unwrap()[1] = mount;
+        spec.save(&path).unwrap();
+        assert!(Spec::load(&path).is_ok());
+        let err = Spec::load_validated(&path).unwrap_err();
+        assert!(err.to_string().starts_with("mounts[1].gidMappings"));
+
+        let mut spec = Spec::default();
+        spec.set_annotations(Some(HashMap::from([(
+            "org.opencontainers.image.stopSignal".to_string(),
+            "SIGTERM".to_string(),
+        )])));
+        assert!(spec.validate().is_ok());
+        spec.annotations_mut()
+            .as_mut()
+            .unwrap()
+            .insert("org.opencontainers.runtime.key".to_string(), String::new());
+        let err = spec.validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("annotations.org.opencontainers.runtime.key"));
+        let mut spec = Spec::default();
+        spec.set_annotations(Some(HashMap::from([(
+            "org.opencontainers.image.unknown".to_string(),
+            String::new(),
+        )])));
+        assert!(spec.validate().is_err());
+        assert!(SpecBuilder::default()
+            .add_annotation("", "value")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_windows_process() {
+        let windows_spec = |process: serde_json::Value| -> Spec {
+            serde_json::from_value(serde_json::json!({
+                "ociVersion": "1.0.2",
+                "process": process,
+                "windows": {"layerFolders": ["C:\\layers\\base"]}
+            }))
+            .unwrap()
+        };
+
+        let spec = windows_spec(serde_json::json!({
+            "cwd": "C:\\",
+            "user": {"username": "NT AUTHORITY\\SYSTEM"},
+            "terminal": true,
+            "consoleSize": {"height": 25, "width": 80}
+        }));
+        assert!(spec.validate().is_ok());
+        assert!(windows_spec(serde_json::json!({
+            "cwd": "C:\\",
+            "user": {},
+            "consoleSize": {"height": 0, "width": 0}
+        }))
+        .validate()
+        .is_ok());
+        assert!(windows_spec(
+            serde_json::json!({"cwd": "C:\\", "user": {"username": "app@corp.example"}})
+        )
+        .validate()
+        .is_ok());
+
+        for (process, path) in [
+            (
+                serde_json::json!({"cwd": "C:\\", "user": {"username": "a\\b\\c"}}),
+                "process.user.username",
+            ),
+            (
+                serde_json::json!({"cwd": "C:\\", "user": {"username": "user@"}}),
+                "process.user.username",
+            ),
+            (
+                serde_json::json!({"cwd": "C:\\", "user": {"username": "a<b"}}),
+                "process.user.username",
+            ),
+            (
+                serde_json::json!({"cwd": "C:\\", "user": {"uid": 1000}}),
+                "process.user.uid",
+            ),
+            (
+                serde_json::json!({"cwd": "C:\\", "user": {}, "rlimits": []}),
+                "process.rlimits",
+            ),
+            (
+                serde_json::json!({"cwd": "C:\\", "user": {}, "consoleSize": {"height": 65536, "width": 80}}),
+                "process.consoleSize.height",
+            ),
+        ] {
+            match windows_spec(process).validate() {
+                Err(OciSpecError::Validation(err)) => assert_eq!(err.path(), path),
+                res => panic!("expected a validation error for {path}, got {res:?}"),
+            }
+        }
+
+        let mut spec = Spec::default();
+        assert!(spec.validate().is_ok());
+        spec.set_windows(Some(Windows::default()));
+        let err = spec.validate().unwrap_err();
+        assert!(err.to_string().starts_with("process.rlimits"));
+        assert!(SpecBuilder::default()
+            .process(Process::default())
+            .windows(Windows::default())
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_mount_mappings() {
+        let mapping = LinuxIdMapping::default();
+        let mount = |options: &[&str], mappings: bool| {
+            let mut mount = Mount::default();
+            mount.set_destination("/data".into());
+            mount.set_options(Some(options.iter().map(|o| o.to_string()).collect()));
+            if mappings {
+                mount.set_uid_mappings(Some(vec![mapping]));
+                mount.set_gid_mappings(Some(vec![mapping]));
+            }
+            mount
+        };
+        let spec = |mount: Mount, linux: Linux| {
+            let mut spec = Spec::default();
+            spec.set_mounts(Some(vec![mount]));
+            spec.set_linux(Some(linux));
+            spec
+        };
+        let rootless = Linux::rootless(1000, 1000);
+
+        assert!(spec(mount(&["rbind"], true), rootless.clone())
+            .validate()
+            .is_ok());
+        assert!(spec(mount(&["idmap"], true), Linux::default())
+            .validate()
+            .is_ok());
+        assert!(spec(mount(&["idmap"], false), rootless.clone())
+            .validate()
+            .is_ok());
+
+        let err = spec(mount(&["rbind"], true), Linux::default())
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("mounts[0].uidMappings"));
+        let err = spec(mount(&["ridmap"], false), Linux::default())
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("mounts[0].uidMappings"));
+
+        let mut linux = rootless;
+        linux.set_gid_mappings(None);
+        let err = spec(mount(&["idmap"], false), linux.clone())
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("linux.gidMappings"));
+        assert!(SpecBuilder::default()
+            .mounts(vec![mount(&["idmap"], false)])
+            .linux(linux)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_take_linux() {
+        let mut spec = Spec::default();
+        let linux = spec.take_linux().expect("default spec has linux");
+        assert_eq!(linux, Linux::default());
+        assert!(spec.linux().is_none());
+        assert!(spec.take_linux().is_none());
+        assert!(spec.process().is_some());
+    }
+
+    #[test]
+    fn test_builder_extend_mounts() {
+        let spec = SpecBuilder::default()
+            .add_mount(get_default_mounts()[0].clone())
+            .extend_mounts(get_default_mounts().into_iter().skip(1))
+            .add_annotation("org.example.key", "value")
+            .build()
+            .unwrap();
+        assert_eq!(spec.mounts(), &Some(get_default_mounts()));
+        assert_eq!(
+            spec.annotations()
+                .as_ref()
+                .and_then(|a| a.get("org.example.key"))
+                .map(String::as_str),
+            Some("value")
+        );
+    }
+
+    #[test]
+    fn test_to_builder() {
+        let spec = Spec::default();
+        let modified = spec.to_builder().hostname("modified").build().unwrap();
+        assert_eq!(modified.hostname().as_deref(), Some("modified"));
+        assert_eq!(modified.linux(), spec.linux());
+        assert_eq!(spec.clone().into_builder().build().unwrap(), spec);
+    }
+
+    #[test]
+    fn test_json_value_conversion() {
+        let spec = Spec::default();
+        let value: serde_json::Value = spec.clone().try_into().unwrap();
+        assert_eq!(value["hostname"], "youki");
+        assert_eq!(Spec::try_from(value).unwrap(), spec);
+    }
+
+    #[test]
+    fn test_deterministic_annotation_order() {
+        let annotations: HashMap<_, _> = (0..32)
+            .map(|i| (format!("org.example.key{i:02}"), i.to_string()))
+            .collect();
+        let spec = SpecBuilder::default()
+            .annotations(annotations)
+            .build()
+            .unwrap();
+        let json = serde_json::to_string(&spec).unwrap();
+        let positions: Vec<_> = (0..32)
+            .map(|i| json.find(&format!("org.example.key{i:02}")).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+}
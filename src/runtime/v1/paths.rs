@@ -0,0 +1,210 @@
+//! Handling of paths which are not valid UTF-8.
+
+use super::Spec;
+use crate::error::{FindingCode, OciSpecError, Result, ValidationError, ValidationErrorKind};
+use std::{ffi::OsStr, path::Path};
+
+impl Spec {
+    /// Return every path of the spec together with the path of its field,
+    /// using the serialized field names, e.g. `mounts[0].destination`.
+    pub fn paths(&self) -> Vec<(String, &Path)> {
+        let mut paths = Vec::new();
+        if let Some(root) = self.root() {
+            paths.push(("root.path".to_string(), root.path().as_path()));
+        }
+        for (i, mount) in self.mounts().iter().flatten().enumerate() {
+            paths.push((format!("mounts[{i}].destination"), mount.destination()));
+            if let Some(source) = mount.source() {
+                paths.push((format!("mounts[{i}].source"), source));
+            }
+        }
+        if let Some(process) = self.process() {
+            paths.push(("process.cwd".to_string(), process.cwd().as_path()));
+        }
+        if let Some(hooks) = self.hooks() {
+            let stages = [
+                ("prestart", hooks.prestart()),
+                ("createRuntime", hooks.create_runtime()),
+                ("createContainer", hooks.create_container()),
+                ("startContainer", hooks.start_container()),
+                ("poststart", hooks.poststart()),
+                ("poststop", hooks.poststop()),
+            ];
+            for (stage, hooks) in stages {
+                for (i, hook) in hooks.iter().flatten().enumerate() {
+                    paths.push((format!("hooks.{stage}[{i}].path"), hook.path()));
+                }
+            }
+        }
+        if let Some(linux) = self.linux() {
+            if let Some(cgroups_path) = linux.cgroups_path() {
+                paths.push(("linux.cgroupsPath".to_string(), cgroups_path));
+            }
+            for (i, namespace) in linux.namespaces().iter().flatten().enumerate() {
+                if let Some(path) = namespace.path() {
+                    paths.push((format!("linux.namespaces[{i}].path"), path));
+                }
+            }
+            for (i, device) in linux.devices().iter().flatten().enumerate() {
+                paths.push((format!("linux.devices[{i}].path"), device.path()));
+            }
+            if let Some(listener_path) = linux
+                .seccomp()
+                .as_ref()
+                .and_then(|s| s.listener_path().as_deref())
+            {
+                paths.push(("linux.seccomp.listenerPath".to_string(), listener_path));
+            }
+        }
+        if let Some(vm) = self.vm() {
+            if let Some(hypervisor) = vm.hypervisor() {
+                paths.push((
+                    "vm.hypervisor.path".to_string(),
+                    hypervisor.path().as_path(),
+                ));
+            }
+            paths.push(("vm.kernel.path".to_string(), vm.kernel().path().as_path()));
+            if let Some(image) = vm.image() {
+                paths.push(("vm.image.path".to_string(), image.path().as_path()));
+            }
+        }
+        if let Some(zos) = self.zos() {
+            for (i, namespace) in zos.namespaces().iter().flatten().enumerate() {
+                if let Some(path) = namespace.path() {
+                    paths.push((format!("zos.namespaces[{i}].path"), path));
+                }
+            }
+        }
+        paths
+    }
+
+    /// Check that every path of the spec is valid UTF-8 and can therefore be
+    /// serialized.
+    ///
+    /// The runtime spec serializes paths as JSON strings, which cannot
+    /// represent the arbitrary bytes Unix paths may contain. Instead of
+    /// silently replacing invalid bytes, serializing a spec with such a path
+    /// fails with the error of this function. No escaping scheme is applied,
+    /// because other runtimes would not be able to read it back. The paths
+    /// keep their [Path] type in memory, so specs can still hold arbitrary
+    /// byte paths, see [Spec::paths].
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] for the first
+    /// path which is not valid UTF-8.
+    pub fn validate_utf8_paths(&self) -> Result<()> {
+        match self.paths().into_iter().find(|(_, p)| p.to_str().is_none()) {
+            Some((field, path)) => Err(non_utf8_path(field, path.as_os_str())),
+            None => Ok(()),
+        }
+    }
+}
+
+fn non_utf8_path(field: String, path: &OsStr) -> OciSpecError {
+    ValidationError::new(
+        ValidationErrorKind::InvalidFormat,
+        field,
+        "path is not valid UTF-8 and cannot be serialized",
+    )
+    .with_code(FindingCode::PathNotUtf8)
+    .with_actual(path.to_string_lossy())
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::MountBuilder;
+
+    #[test]
+    fn utf8_paths() {
+        let spec = Spec::default();
+        assert!(spec.validate_utf8_paths().is_ok());
+        let fields: Vec<_> = spec.paths().into_iter().map(|(field, _)| field).collect();
+        assert!(fields.contains(&"root.path".to_string()));
+        assert!(fields.contains(&"mounts[0].destination".to_string()));
+    }
+
+    #[test]
+    fn all_paths() {
+        let spec: Spec = serde_json::from_value(serde_json::json!({
+            "ociVersion": "1.2.0",
+            "root": {"path": "rootfs"},
+            "mounts": [{"destination": "/data", "source": "/srv/data"}],
+            "process": {"cwd": "/", "user": {"uid": 0, "gid": 0}},
+            "hooks": {
+                "prestart": [{"path": "/bin/prestart"}],
+                "createRuntime": [{"path": "/bin/create-runtime"}],
+                "createContainer": [{"path": "/bin/create-container"}],
+                "startContainer": [{"path": "/bin/start-container"}],
+                "poststart": [{"path": "/bin/poststart"}],
+                "poststop": [{"path": "/bin/poststop"}]
+            },
+            "linux": {
+                "cgroupsPath": "/container",
+                "namespaces": [{"type": "network", "path": "/run/netns/a"}],
+                "devices": [{"type": "c", "path": "/dev/null", "major": 1, "minor": 3}],
+                "seccomp": {"defaultAction": "SCMP_ACT_ALLOW", "listenerPath": "/run/seccomp.sock"}
+            },
+            "vm": {
+                "hypervisor": {"path": "/usr/bin/qemu"},
+                "kernel": {"path": "/boot/vmlinuz"},
+                "image": {"path": "/images/root.img", "format": "raw"}
+            },
+            "zos": {"namespaces": [{"type": "mount", "path": "/proc/1/ns/mnt"}]}
+        }))
+        .unwrap();
+
+        let paths: Vec<_> = spec
+            .paths()
+            .into_iter()
+            .map(|(field, path)| (field, path.to_str().unwrap()))
+            .collect();
+        let expected = [
+            ("root.path", "rootfs"),
+            ("mounts[0].destination", "/data"),
+            ("mounts[0].source", "/srv/data"),
+            ("process.cwd", "/"),
+            ("hooks.prestart[0].path", "/bin/prestart"),
+            ("hooks.createRuntime[0].path", "/bin/create-runtime"),
+            ("hooks.createContainer[0].path", "/bin/create-container"),
+            ("hooks.startContainer[0].path", "/bin/start-container"),
+            ("hooks.poststart[0].path", "/bin/poststart"),
+            ("hooks.poststop[0].path", "/bin/poststop"),
+            ("linux.cgroupsPath", "/container"),
+            ("linux.namespaces[0].path", "/run/netns/a"),
+            ("linux.devices[0].path", "/dev/null"),
+            ("linux.seccomp.listenerPath", "/run/seccomp.sock"),
+            ("vm.hypervisor.path", "/usr/bin/qemu"),
+            ("vm.kernel.path", "/boot/vmlinuz"),
+            ("vm.image.path", "/images/root.img"),
+            ("zos.namespaces[0].path", "/proc/1/ns/mnt"),
+        ];
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(field, path)| (field.to_string(), path))
+            .collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_paths() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut spec = Spec::default();
+        let mount = MountBuilder::default()
+            .destination(Path::new(OsStr::from_bytes(b"/data/\xff")))
+            .build()
+            .unwrap();
+        spec.set_mounts(Some(vec![mount]));
+
+        let err = spec.validate_utf8_paths().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("mounts[0].destination: path is not valid UTF-8"));
+        assert!(spec
+            .save(std::env::temp_dir().join("non_utf8_paths.json"))
+            .is_err());
+        assert!(spec.to_string_go().is_err());
+    }
+}
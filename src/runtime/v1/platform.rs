@@ -0,0 +1,190 @@
+//! Dispatching on the platform specific sections of a spec.
+
+use super::{Linux, Solaris, Spec, Windows, VM, ZOS};
+use std::path::Path;
+use strum_macros::{Display as StrumDisplay, EnumString};
+
+/// The platform a spec is meant for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, StrumDisplay, EnumString)]
+#[strum(serialize_all = "lowercase")]
+#[non_exhaustive]
+pub enum TargetPlatform {
+    /// Linux containers.
+    Linux,
+    /// Windows containers.
+    Windows,
+    /// Solaris zones.
+    Solaris,
+    /// Virtual-machine-based containers.
+    Vm,
+    /// z/OS containers.
+    Zos,
+}
+
+/// The platform specific section of a spec, see [Spec::platform_config].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PlatformConfig<'a> {
+    /// The `linux` section.
+    Linux(&'a Linux),
+    /// The `windows` section.
+    Windows(&'a Windows),
+    /// The `solaris` section.
+    Solaris(&'a Solaris),
+    /// The `vm` section.
+    Vm(&'a VM),
+    /// The `zos` section.
+    Zos(&'a ZOS),
+}
+
+impl PlatformConfig<'_> {
+    /// Returns the platform the section configures.
+    pub fn platform(&self) -> TargetPlatform {
+        match self {
+            Self::Linux(_) => TargetPlatform::Linux,
+            Self::Windows(_) => TargetPlatform::Windows,
+            Self::Solaris(_) => TargetPlatform::Solaris,
+            Self::Vm(_) => TargetPlatform::Vm,
+            Self::Zos(_) => TargetPlatform::Zos,
+        }
+    }
+}
+
+impl Spec {
+    /// Returns the platform specific section of the spec, or `None` if none
+    /// is set.
+    ///
+    /// Virtual-machine-based containers configure their guest with one of
+    /// the other sections, so the `vm` section is returned before them,
+    /// since the container has to be run by a hypervisor-based runtime.
+    /// Otherwise the sections are returned in the order `linux`,
+    /// `windows`, `solaris` and `zos`: Linux containers on Windows (LCOW)
+    /// set both a `linux` and a `windows` section and run a Linux guest.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{PlatformConfig, Spec};
+    ///
+    /// match Spec::default().platform_config() {
+    ///     Some(PlatformConfig::Linux(linux)) => assert!(linux.namespaces().is_some()),
+    ///     other => panic!("unexpected platform {other:?}"),
+    /// }
+    /// ```
+    pub fn platform_config(&self) -> Option<PlatformConfig<'_>> {
+        self.vm()
+            .as_ref()
+            .map(PlatformConfig::Vm)
+            .or_else(|| self.linux().as_ref().map(PlatformConfig::Linux))
+            .or_else(|| self.windows().as_ref().map(PlatformConfig::Windows))
+            .or_else(|| self.solaris().as_ref().map(PlatformConfig::Solaris))
+            .or_else(|| self.zos().as_ref().map(PlatformConfig::Zos))
+    }
+
+    /// Infer the platform the spec is meant for.
+    ///
+    /// The platform of the [platform section](Spec::platform_config) is
+    /// returned if there is one. Otherwise fields which are only supported
+    /// on one platform are taken into account: a user name, a command line
+    /// or Windows paths point to Windows, Linux process attributes like
+    /// capabilities, AppArmor or SELinux labels point to Linux. Returns
+    /// `None` if the spec gives no hint.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{Spec, TargetPlatform};
+    ///
+    /// let mut spec = Spec::default();
+    /// spec.set_linux(None);
+    /// assert_eq!(spec.target_platform(), Some(TargetPlatform::Linux));
+    /// ```
+    pub fn target_platform(&self) -> Option<TargetPlatform> {
+        if let Some(config) = self.platform_config() {
+            return Some(config.platform());
+        }
+
+        let is_windows_path = |path: &Path| {
+            let path = path.to_string_lossy();
+            let bytes = path.as_bytes();
+            path.starts_with(r"\\")
+                || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+        };
+        let process = self.process().as_ref();
+        let windows = process.is_some_and(|process| {
+            process.user().username().is_some()
+                || process.command_line().is_some()
+                || is_windows_path(process.cwd())
+        }) || self
+            .root()
+            .as_ref()
+            .is_some_and(|root| is_windows_path(root.path()))
+            || self
+                .mounts()
+                .iter()
+                .flatten()
+                .any(|mount| is_windows_path(mount.destination()));
+        if windows {
+            return Some(TargetPlatform::Windows);
+        }
+
+        let linux = process.is_some_and(|process| {
+            process.capabilities().is_some()
+                || process.apparmor_profile().is_some()
+                || process.selinux_label().is_some()
+                || process.oom_score_adj().is_some()
+                || process.io_priority().is_some()
+                || process.scheduler().is_some()
+        });
+        linux.then_some(TargetPlatform::Linux)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{ProcessBuilder, UserBuilder};
+
+    #[test]
+    fn platform_config() {
+        let mut spec = Spec::default();
+        assert_eq!(spec.target_platform(), Some(TargetPlatform::Linux));
+
+        spec.set_vm(Some(VM::default()));
+        assert!(matches!(
+            spec.platform_config(),
+            Some(PlatformConfig::Vm(_))
+        ));
+        assert_eq!(spec.target_platform(), Some(TargetPlatform::Vm));
+
+        spec.set_vm(None).set_windows(Some(Windows::default()));
+        assert_eq!(spec.target_platform(), Some(TargetPlatform::Linux));
+        spec.set_linux(None);
+        assert_eq!(spec.target_platform(), Some(TargetPlatform::Windows));
+        spec.set_windows(None);
+
+        spec.set_solaris(Some(Solaris::default()));
+        assert_eq!(spec.target_platform(), Some(TargetPlatform::Solaris));
+        assert_eq!(TargetPlatform::Solaris.to_string(), "solaris");
+
+        spec.set_solaris(None);
+        spec.set_process(Some(
+            ProcessBuilder::default()
+                .user(UserBuilder::default().build().unwrap())
+                .cwd(r"C:\")
+                .build()
+                .unwrap(),
+        ));
+        assert_eq!(spec.platform_config(), None);
+        assert_eq!(spec.target_platform(), Some(TargetPlatform::Windows));
+
+        let mut process = ProcessBuilder::default()
+            .user(UserBuilder::default().build().unwrap())
+            .cwd("/")
+            .build()
+            .unwrap();
+        spec.set_process(Some(process.clone()));
+        assert_eq!(spec.target_platform(), Some(TargetPlatform::Linux));
+        process.set_capabilities(None);
+        spec.set_process(Some(process));
+        spec.set_root(None);
+        spec.set_mounts(None);
+        assert_eq!(spec.target_platform(), None);
+    }
+}
@@ -0,0 +1,357 @@
+use super::{
+    all_capabilities, get_default_maskedpaths, get_default_readonly_paths, get_default_seccomp,
+    Arch, Capabilities, Capability, LinuxCapabilities, LinuxDeviceCgroup, LinuxDeviceType,
+    LinuxNamespace, LinuxNamespaceType, Spec,
+};
+use crate::error::OciSpecError;
+use derive_builder::Builder;
+use getset::{CopyGetters, Getters, Setters};
+
+#[derive(Builder, Clone, CopyGetters, Debug, Getters, Setters, Eq, PartialEq)]
+#[builder(
+    default,
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(error = "OciSpecError")
+)]
+/// Options for [Spec::hardened_with].
+#[non_exhaustive]
+pub struct HardenedOptions {
+    #[getset(get = "pub", set = "pub")]
+    /// Capabilities kept in the bounding, effective and permitted sets of the
+    /// process. Defaults to `CAP_AUDIT_WRITE`, `CAP_KILL` and
+    /// `CAP_NET_BIND_SERVICE`.
+    capabilities: Capabilities,
+
+    #[getset(get_copy = "pub", set = "pub")]
+    /// Mount the root filesystem read-only. Defaults to `true`.
+    readonly_rootfs: bool,
+
+    #[getset(get_copy = "pub", set = "pub")]
+    /// Apply the profile returned by
+    /// [get_default_seccomp](crate::runtime::get_default_seccomp). Defaults
+    /// to `true`.
+    seccomp: bool,
+}
+
+impl Default for HardenedOptions {
+    fn default() -> Self {
+        HardenedOptions {
+            capabilities: [
+                Capability::AuditWrite,
+                Capability::Kill,
+                Capability::NetBindService,
+            ]
+            .into_iter()
+            .collect(),
+            readonly_rootfs: true,
+            seccomp: true,
+        }
+    }
+}
+
+/// The cgroup hierarchy of the host a spec is generated for.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum CgroupVersion {
+    /// The legacy cgroup v1 hierarchy.
+    V1,
+    /// The unified cgroup v2 hierarchy.
+    #[default]
+    V2,
+}
+
+#[derive(Builder, Clone, CopyGetters, Debug, Getters, Setters, Eq, PartialEq)]
+#[builder(
+    default,
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(error = "OciSpecError")
+)]
+/// Options for [Spec::with_defaults].
+#[non_exhaustive]
+pub struct DefaultOptions {
+    #[getset(get_copy = "pub", set = "pub")]
+    /// Generate a rootless spec mapping root in the container to the host
+    /// `(uid, gid)`. Defaults to `None`.
+    rootless: Option<(u32, u32)>,
+
+    #[getset(get_copy = "pub", set = "pub")]
+    /// The cgroup version of the host, which determines how the cgroup
+    /// filesystem is mounted. Defaults to [CgroupVersion::V2].
+    cgroup_version: CgroupVersion,
+
+    #[getset(get_copy = "pub", set = "pub")]
+    /// Apply the profile returned by
+    /// [get_default_seccomp](crate::runtime::get_default_seccomp). Defaults
+    /// to `false`.
+    seccomp: bool,
+
+    #[getset(get = "pub", set = "pub")]
+    /// The architectures of the seccomp profile. Defaults to the architecture
+    /// of the build target and its compat architectures, see
+    /// [Arch::native_with_compat].
+    architectures: Vec<Arch>,
+
+    #[getset(get_copy = "pub", set = "pub")]
+    /// Run the container in a new network namespace. Defaults to `true`.
+    network_namespace: bool,
+}
+
+impl Default for DefaultOptions {
+    fn default() -> Self {
+        DefaultOptions {
+            rootless: None,
+            cgroup_version: CgroupVersion::default(),
+            seccomp: false,
+            architectures: Arch::native_with_compat().to_vec(),
+            network_namespace: true,
+        }
+    }
+}
+
+impl Spec {
+    /// Return a default spec adjusted to the host described by `options`.
+    /// `Spec::with_defaults(DefaultOptions::default())` differs from
+    /// [Spec::default] only by mounting the cgroup v2 filesystem.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{DefaultOptionsBuilder, LinuxNamespaceType, Spec};
+    ///
+    /// let options = DefaultOptionsBuilder::default()
+    ///     .rootless((1000, 1000))
+    ///     .network_namespace(false)
+    ///     .build()
+    ///     .unwrap();
+    /// let spec = Spec::with_defaults(options);
+    /// let namespaces = spec.linux().as_ref().unwrap().namespaces().as_ref().unwrap();
+    /// assert!(namespaces.iter().any(|ns| ns.typ() == LinuxNamespaceType::User));
+    /// assert!(namespaces.iter().all(|ns| ns.typ() != LinuxNamespaceType::Network));
+    /// ```
+    pub fn with_defaults(options: DefaultOptions) -> Self {
+        let mut spec = match options.rootless {
+            Some((uid, gid)) => Spec::rootless(uid, gid),
+            None => Spec::default(),
+        };
+
+        if options.cgroup_version == CgroupVersion::V2 {
+            let cgroup = spec
+                .mounts_mut()
+                .iter_mut()
+                .flatten()
+                .find(|mount| mount.destination().as_os_str() == "/sys/fs/cgroup");
+            if let Some(cgroup) = cgroup {
+                cgroup.set_typ(Some("cgroup2".to_string()));
+                cgroup.set_source(Some("cgroup2".into()));
+            }
+        }
+
+        let linux = spec.linux_mut().get_or_insert_with(Default::default);
+        if options.seccomp {
+            let mut seccomp = get_default_seccomp();
+            seccomp.set_architectures(Some(options.architectures));
+            linux.set_seccomp(Some(seccomp));
+        }
+
+        let namespaces = linux.namespaces_mut().get_or_insert_with(Vec::new);
+        let has_network = namespaces
+            .iter()
+            .any(|ns| ns.typ() == LinuxNamespaceType::Network);
+        if options.network_namespace && !has_network {
+            let mut network = LinuxNamespace::default();
+            network.set_typ(LinuxNamespaceType::Network);
+            namespaces.push(network);
+        } else if !options.network_namespace {
+            namespaces.retain(|ns| ns.typ() != LinuxNamespaceType::Network);
+        }
+
+        spec
+    }
+
+    /// Return a spec following current hardening best practices, using the
+    /// default [HardenedOptions]. See [Spec::hardened_with].
+    pub fn hardened() -> Self {
+        Self::hardened_with(&HardenedOptions::default())
+    }
+
+    /// Return a spec following current hardening best practices:
+    ///
+    /// - only the capabilities from `options` are kept, the inheritable and
+    ///   ambient sets are empty
+    /// - `no_new_privileges` is set
+    /// - the default seccomp profile is applied
+    /// - the root filesystem is read-only
+    /// - the default masked and read-only paths are set
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{HardenedOptionsBuilder, Spec};
+    ///
+    /// let options = HardenedOptionsBuilder::default()
+    ///     .readonly_rootfs(false)
+    ///     .build()
+    ///     .unwrap();
+    /// let spec = Spec::hardened_with(&options);
+    /// assert_eq!(spec.root().as_ref().unwrap().readonly(), Some(false));
+    /// ```
+    pub fn hardened_with(options: &HardenedOptions) -> Self {
+        let mut spec = Spec::default();
+
+        if let Some(process) = spec.process_mut() {
+            let mut capabilities = LinuxCapabilities::default();
+            capabilities.set_bounding(Some(options.capabilities.clone()));
+            capabilities.set_effective(Some(options.capabilities.clone()));
+            capabilities.set_permitted(Some(options.capabilities.clone()));
+            capabilities.set_inheritable(Some(Capabilities::new()));
+            capabilities.set_ambient(Some(Capabilities::new()));
+            process.set_capabilities(Some(capabilities));
+            process.set_no_new_privileges(Some(true));
+        }
+
+        if let Some(root) = spec.root_mut() {
+            root.set_readonly(Some(options.readonly_rootfs));
+        }
+
+        if let Some(linux) = spec.linux_mut() {
+            linux.set_masked_paths(Some(get_default_maskedpaths()));
+            linux.set_readonly_paths(Some(get_default_readonly_paths()));
+            linux.set_seccomp(options.seccomp.then(get_default_seccomp));
+        }
+
+        spec
+    }
+
+    /// Turn the spec into a privileged configuration, the way container
+    /// engines implement `--privileged`:
+    ///
+    /// - all capabilities are granted in all capability sets
+    /// - access to all devices is allowed by the device cgroup
+    /// - no seccomp profile and no AppArmor profile are applied
+    /// - no paths are masked or made read-only
+    ///
+    /// Host devices are not added to the container, since discovering them is
+    /// up to the engine.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::Spec;
+    ///
+    /// let mut spec = Spec::default();
+    /// spec.make_privileged();
+    /// assert!(spec.linux().as_ref().unwrap().masked_paths().is_none());
+    /// ```
+    pub fn make_privileged(&mut self) {
+        if let Some(process) = self.process_mut() {
+            let all = all_capabilities();
+            let mut capabilities = LinuxCapabilities::default();
+            capabilities.set_bounding(Some(all.clone()));
+            capabilities.set_effective(Some(all.clone()));
+            capabilities.set_inheritable(Some(all.clone()));
+            capabilities.set_permitted(Some(all.clone()));
+            capabilities.set_ambient(Some(all));
+            process.set_capabilities(Some(capabilities));
+            process.set_apparmor_profile(None);
+        }
+
+        let linux = self.linux_mut().get_or_insert_with(Default::default);
+        linux.set_seccomp(None);
+        linux.set_masked_paths(None);
+        linux.set_readonly_paths(None);
+
+        let mut allow_all = LinuxDeviceCgroup::default();
+        allow_all
+            .set_allow(true)
+            .set_typ(Some(LinuxDeviceType::A))
+            .set_access(Some("rwm".to_string()));
+        linux
+            .resources_mut()
+            .get_or_insert_with(Default::default)
+            .set_devices(Some(vec![allow_all]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{Capability, LinuxSeccompAction};
+
+    #[test]
+    fn make_privileged() {
+        let mut spec = Spec::default();
+        spec.make_privileged();
+
+        let process = spec.process().as_ref().unwrap();
+        let capabilities = process.capabilities().as_ref().unwrap();
+        let effective = capabilities.effective().as_ref().unwrap();
+        assert!(effective.contains(&Capability::SysAdmin));
+        assert_eq!(effective, &all_capabilities());
+
+        let linux = spec.linux().as_ref().unwrap();
+        assert!(linux.seccomp().is_none());
+        assert!(linux.readonly_paths().is_none());
+        let devices = linux
+            .resources()
+            .as_ref()
+            .and_then(|r| r.devices().as_ref())
+            .unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].to_string(), "a *:* rwm");
+    }
+
+    #[test]
+    fn with_defaults() {
+        let spec = Spec::with_defaults(DefaultOptions::default());
+        let mut expected = Spec::default();
+        let cgroup = expected.mounts_mut().as_mut().unwrap().last_mut().unwrap();
+        cgroup.set_typ(Some("cgroup2".to_string()));
+        cgroup.set_source(Some("cgroup2".into()));
+        assert_eq!(spec, expected);
+
+        let options = DefaultOptionsBuilder::default()
+            .rootless((1000, 1000))
+            .cgroup_version(CgroupVersion::V1)
+            .seccomp(true)
+            .architectures(vec![Arch::ScmpArchAarch64])
+            .build()
+            .unwrap();
+        let spec = Spec::with_defaults(options);
+        let linux = spec.linux().as_ref().unwrap();
+        let seccomp = linux.seccomp().as_ref().unwrap();
+        assert_eq!(
+            seccomp.architectures().as_deref(),
+            Some(&[Arch::ScmpArchAarch64][..])
+        );
+        let namespaces = linux.namespaces().as_ref().unwrap();
+        assert!(namespaces
+            .iter()
+            .any(|ns| ns.typ() == LinuxNamespaceType::Network));
+        assert_eq!(linux.uid_mappings().as_ref().unwrap()[0].host_id(), 1000);
+    }
+
+    #[test]
+    fn hardened() {
+        let spec = Spec::hardened();
+        assert!(spec.lint().is_empty());
+        assert_eq!(spec.root().as_ref().unwrap().readonly(), Some(true));
+
+        let process = spec.process().as_ref().unwrap();
+        assert_eq!(process.no_new_privileges(), Some(true));
+        let capabilities = process.capabilities().as_ref().unwrap();
+        assert_eq!(
+            capabilities.bounding().as_ref(),
+            Some(HardenedOptions::default().capabilities())
+        );
+        assert!(capabilities.ambient().as_ref().unwrap().is_empty());
+
+        let seccomp = spec.linux().as_ref().unwrap().seccomp().as_ref().unwrap();
+        let verdict = seccomp.evaluate(Arch::ScmpArchX86_64, "clone3", &[]);
+        assert_eq!(verdict.action(), LinuxSeccompAction::ScmpActErrno);
+        assert_eq!(verdict.errno_ret(), Some(38));
+        for (name, args) in [
+            ("fsopen", &[][..]),
+            ("io_uring_setup", &[]),
+            ("clone", &[0x10000000]),
+        ] {
+            let verdict = seccomp.evaluate(Arch::ScmpArchX86_64, name, args);
+            assert_eq!(verdict.errno_ret(), Some(1), "{name}");
+        }
+    }
+}
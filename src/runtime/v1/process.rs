@@ -1,5 +1,5 @@
 use crate::{
-    error::OciSpecError,
+    error::{FindingCode, OciSpecError, ValidationError, ValidationErrorKind},
     runtime::{Capabilities, Capability},
 };
 use derive_builder::Builder;
@@ -32,6 +32,7 @@ use strum_macros::{Display as StrumDisplay, EnumString};
 )]
 /// Process contains information to start a specific application inside the
 /// container.
+#[non_exhaustive]
 pub struct Process {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[getset(get_copy = "pub", set = "pub")]
@@ -168,6 +169,148 @@ impl Default for Process {
     }
 }
 
+/// The value of `TERM` set for interactive processes which do not set one.
+const DEFAULT_TERM: &str = "TERM=xterm";
+
+impl Process {
+    /// Configure the process for interactive use with a terminal, like
+    /// `docker run -it`: `terminal` is enabled, the console gets the size of
+    /// the terminal of the user, if known, and `TERM` is set unless the
+    /// environment already sets it.
+    ///
+    /// The runtime allocates a pseudo terminal for the process, which it
+    /// passes on via the console socket instead of using the stdio of the
+    /// caller.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{BoxBuilder, Process};
+    ///
+    /// let mut process = Process::default();
+    /// let size = BoxBuilder::default().height(24u64).width(80u64).build().unwrap();
+    /// process.set_interactive(Some(size));
+    /// assert_eq!(process.terminal(), Some(true));
+    /// ```
+    pub fn set_interactive(&mut self, console_size: Option<Box>) -> &mut Self {
+        self.terminal = Some(true);
+        self.console_size = console_size;
+        let env = self.env.get_or_insert_with(Vec::new);
+        if !env.iter().any(|var| var.starts_with("TERM=")) {
+            env.push(DEFAULT_TERM.to_string());
+        }
+        self
+    }
+
+    /// Configure the process for detached use without a terminal, like
+    /// `docker run -d`: `terminal` is disabled and the console size and
+    /// `TERM`, which only apply to a terminal, are removed.
+    ///
+    /// The process uses the stdio passed by the caller of the runtime, e.g.
+    /// pipes or log files.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::Process;
+    ///
+    /// let mut process = Process::default();
+    /// process.set_detached();
+    /// assert_eq!(process.terminal(), Some(false));
+    /// assert!(!process.env().as_ref().unwrap().iter().any(|v| v.starts_with("TERM=")));
+    /// ```
+    pub fn set_detached(&mut self) -> &mut Self {
+        self.terminal = Some(false);
+        self.console_size = None;
+        if let Some(env) = &mut self.env {
+            env.retain(|var| !var.starts_with("TERM="));
+        }
+        self
+    }
+
+    /// Validate the settings of a process running in a Windows container,
+    /// which identifies its user by `user.username` only and has no use for
+    /// the POSIX fields of the process.
+    pub(crate) fn validate_windows(&self) -> Result<(), OciSpecError> {
+        if let Some(username) = self.user.username() {
+            validate_windows_username(username).map_err(|message| {
+                OciSpecError::from(
+                    ValidationError::new(
+                        ValidationErrorKind::InvalidFormat,
+                        "user.username",
+                        message,
+                    )
+                    .with_code(FindingCode::WindowsUsernameInvalid)
+                    .with_expected(r"a user name, optionally as DOMAIN\user or user@domain")
+                    .with_actual(username.as_str()),
+                )
+            })?;
+        }
+
+        let posix_fields = [
+            ("user.uid", self.user.uid() != 0),
+            ("user.gid", self.user.gid() != 0),
+            ("user.umask", self.user.umask().is_some()),
+            ("user.additionalGids", self.user.additional_gids().is_some()),
+            ("rlimits", self.rlimits.is_some()),
+            ("capabilities", self.capabilities.is_some()),
+        ];
+        if let Some((path, _)) = posix_fields.into_iter().find(|(_, set)| *set) {
+            return Err(ValidationError::new(
+                ValidationErrorKind::Conflict,
+                path,
+                "is only supported on Linux and POSIX platforms, \
+                 remove it for a spec with a windows section",
+            )
+            .with_code(FindingCode::WindowsUnsupportedField)
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Validate the console size, which both Linux and Windows terminals hold
+    /// as 16 bit values. A size of 0 leaves the size of the terminal as it
+    /// is.
+    pub(crate) fn validate_console_size(&self) -> Result<(), OciSpecError> {
+        let Some(console_size) = self.console_size else {
+            return Ok(());
+        };
+        for (path, value) in [
+            ("consoleSize.height", console_size.height),
+            ("consoleSize.width", console_size.width),
+        ] {
+            if value > u64::from(u16::MAX) {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::OutOfRange,
+                    path,
+                    "value out of range",
+                )
+                .with_code(FindingCode::ConsoleSizeOutOfRange)
+                .with_expected(format!("0..={}", u16::MAX))
+                .with_actual(value.to_string())
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ProcessBuilder {
+    /// Append a single `KEY=value` entry to the environment set on this builder.
+    pub fn add_env(self, env: impl Into<String>) -> Self {
+        self.extend_env([env.into()])
+    }
+
+    /// Append the provided `KEY=value` entries to the environment set on this
+    /// builder. Note that an environment which was never set on the builder
+    /// starts out empty rather than with the environment of [`Process::default`].
+    pub fn extend_env<I: IntoIterator<Item = String>>(mut self, env: I) -> Self {
+        self.env
+            .get_or_insert_with(|| Some(Vec::new()))
+            .get_or_insert_with(Vec::new)
+            .extend(env);
+        self
+    }
+}
+
 #[derive(
     Builder, Clone, Copy, CopyGetters, Debug, Default, Deserialize, Eq, PartialEq, Serialize,
 )]
@@ -180,6 +323,7 @@ impl Default for Process {
 #[getset(get_copy = "pub", set = "pub")]
 /// Box specifies dimensions of a rectangle. Used for specifying the size of
 /// a console.
+#[non_exhaustive]
 pub struct Box {
     #[serde(default)]
     /// Height is the vertical dimension of a box.
@@ -196,6 +340,7 @@ pub struct Box {
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 /// Available rlimit types (see <https://man7.org/linux/man-pages/man2/getrlimit.2.html>)
+#[non_exhaustive]
 pub enum PosixRlimitType {
     /// Limit in seconds of the amount of CPU time that the process can consume.
     #[default]
@@ -264,6 +409,7 @@ pub enum PosixRlimitType {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// RLimit types and restrictions.
+#[non_exhaustive]
 pub struct PosixRlimit {
     #[serde(rename = "type")]
     /// Type of Rlimit to set
@@ -300,6 +446,7 @@ pub struct PosixRlimit {
     build_fn(error = "OciSpecError")
 )]
 /// User id (uid) and group id (gid) tracks file permissions.
+#[non_exhaustive]
 pub struct User {
     #[serde(default)]
     #[getset(get_mut = "pub", get_copy = "pub", set = "pub")]
@@ -328,6 +475,35 @@ pub struct User {
     username: Option<String>,
 }
 
+impl User {
+    /// Sort the additional group ids and remove duplicates, the order of
+    /// the groups has no effect on the process.
+    pub fn normalize_additional_gids(&mut self) {
+        if let Some(gids) = &mut self.additional_gids {
+            gids.sort_unstable();
+            gids.dedup();
+        }
+    }
+
+    /// Add the group ids `gids` to the additional group ids, keeping them
+    /// sorted and free of duplicates.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::User;
+    ///
+    /// let mut user = User::default();
+    /// user.add_additional_gids([27, 10, 27]);
+    /// user.add_additional_gids([4]);
+    /// assert_eq!(user.additional_gids().as_deref(), Some(&[4, 10, 27][..]));
+    /// ```
+    pub fn add_additional_gids<I: IntoIterator<Item = u32>>(&mut self, gids: I) {
+        self.additional_gids
+            .get_or_insert_with(Vec::new)
+            .extend(gids);
+        self.normalize_additional_gids();
+    }
+}
+
 #[derive(Builder, Clone, Debug, Deserialize, Getters, Setters, Eq, PartialEq, Serialize)]
 #[builder(
     default,
@@ -338,6 +514,7 @@ pub struct User {
 #[getset(get = "pub", set = "pub")]
 /// LinuxCapabilities specifies the list of allowed capabilities that are
 /// kept for a process. <http://man7.org/linux/man-pages/man7/capabilities.7.html>
+#[non_exhaustive]
 pub struct LinuxCapabilities {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Bounding is the set of capabilities checked by the kernel.
@@ -393,6 +570,7 @@ impl Default for LinuxCapabilities {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// RLimit types and restrictions.
+#[non_exhaustive]
 pub struct LinuxIOPriority {
     #[serde(default)]
     /// Class represents an I/O scheduling class.
@@ -409,6 +587,7 @@ pub struct LinuxIOPriority {
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 /// IOPriorityClass represents an I/O scheduling class.
+#[non_exhaustive]
 pub enum IOPriorityClass {
     /// This is the realtime io class. This scheduling class is given
     /// higher priority than any other in the system, processes from this class are
@@ -443,6 +622,7 @@ pub enum IOPriorityClass {
 #[getset(get = "pub", set = "pub")]
 /// Scheduler represents the scheduling attributes for a process. It is based on
 /// the Linux sched_setattr(2) syscall.
+#[non_exhaustive]
 pub struct Scheduler {
     /// Policy represents the scheduling policy (e.g., SCHED_FIFO, SCHED_RR, SCHED_OTHER).
     policy: LinuxSchedulerPolicy,
@@ -493,6 +673,7 @@ impl Default for Scheduler {
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 ///  LinuxSchedulerPolicy represents different scheduling policies used with the Linux Scheduler
+#[non_exhaustive]
 pub enum LinuxSchedulerPolicy {
     /// SchedOther is the default scheduling policy
     SchedOther,
@@ -521,6 +702,7 @@ impl Default for LinuxSchedulerPolicy {
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 ///  LinuxSchedulerFlag represents the flags used by the Linux Scheduler.
+#[non_exhaustive]
 pub enum LinuxSchedulerFlag {
     /// SchedFlagResetOnFork represents the reset on fork scheduling flag
     SchedResetOnFork,
@@ -557,6 +739,7 @@ impl Default for LinuxSchedulerFlag {
 #[getset(get = "pub", set = "pub")]
 /// ExecCPUAffinity specifies CPU affinity used to execute the process.
 /// This setting is not applicable to the container's init process.
+#[non_exhaustive]
 pub struct ExecCPUAffinity {
     #[serde(
         default,
@@ -582,20 +765,43 @@ pub struct ExecCPUAffinity {
     cpu_affinity_final: Option<String>,
 }
 
+impl ExecCPUAffinity {
+    /// Run the validation of [ExecCPUAffinityBuilder], e.g. for a value
+    /// modified after deserialization.
+    pub(crate) fn validate(&self) -> Result<(), OciSpecError> {
+        ExecCPUAffinityBuilder {
+            initial: Some(self.initial.clone()),
+            cpu_affinity_final: Some(self.cpu_affinity_final.clone()),
+        }
+        .validate()
+    }
+}
+
 impl ExecCPUAffinityBuilder {
     fn validate(&self) -> Result<(), OciSpecError> {
         if let Some(Some(ref s)) = self.initial {
-            validate_cpu_affinity(s).map_err(|e| OciSpecError::Other(e.to_string()))?;
+            validate_cpu_affinity(s).map_err(|_| invalid_cpu_affinity("initial", s))?;
         }
 
         if let Some(Some(ref s)) = self.cpu_affinity_final {
-            validate_cpu_affinity(s).map_err(|e| OciSpecError::Other(e.to_string()))?;
+            validate_cpu_affinity(s).map_err(|_| invalid_cpu_affinity("final", s))?;
         }
 
         Ok(())
     }
 }
 
+fn invalid_cpu_affinity(path: &str, value: &str) -> OciSpecError {
+    ValidationError::new(
+        ValidationErrorKind::InvalidFormat,
+        path,
+        "invalid execCPUAffinity format",
+    )
+    .with_code(FindingCode::ExecCpuAffinityInvalid)
+    .with_actual(value)
+    .into()
+}
+
 fn deserialize<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
 where
     D: Deserializer<'de>,
@@ -625,6 +831,40 @@ fn validate_cpu_affinity(s: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Check the shape of a Windows user name: a plain name, `DOMAIN\\user` or
+/// `user@domain`, without the characters Windows forbids in account names.
+fn validate_windows_username(username: &str) -> Result<(), &'static str> {
+    const MAX_LEN: usize = 256;
+    const FORBIDDEN: &[char] = &[
+        '"', '/', '[', ']', ':', ';', '|', '=', ',', '+', '*', '?', '<', '>',
+    ];
+
+    if username.len() > MAX_LEN {
+        return Err("user name is too long");
+    }
+    let parts: Vec<&str> = match (username.split_once('\\'), username.split_once('@')) {
+        (Some(_), Some(_)) => return Err("user name mixes DOMAIN\\user and user@domain forms"),
+        (Some((domain, user)), None) | (None, Some((user, domain))) => vec![domain, user],
+        (None, None) => vec![username],
+    };
+    for part in parts {
+        if part.trim().is_empty() {
+            return Err("user name or domain is empty");
+        }
+        if part.ends_with('.') {
+            return Err("user name or domain ends with a period");
+        }
+        if part
+            .chars()
+            .any(|c| c.is_control() || c == '\\' || c == '@' || FORBIDDEN.contains(&c))
+        {
+            return Err("user name contains a character which is not allowed");
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -736,14 +976,20 @@ mod tests {
             .cpu_affinity_final("4-6,8".to_string())
             .build();
         let err = affinity.unwrap_err();
-        assert_eq!(err.to_string(), "Invalid execCPUAffinity format: 0-3,i");
+        assert_eq!(
+            err.to_string(),
+            "initial: invalid execCPUAffinity format (got 0-3,i)"
+        );
 
         let affinity = ExecCPUAffinityBuilder::default()
             .initial("-".to_string())
             .cpu_affinity_final("4-6,8".to_string())
             .build();
         let err = affinity.unwrap_err();
-        assert_eq!(err.to_string(), "Invalid execCPUAffinity format: -");
+        assert_eq!(
+            err.to_string(),
+            "initial: invalid execCPUAffinity format (got -)"
+        );
     }
 
     #[test]
@@ -753,14 +999,20 @@ mod tests {
             .cpu_affinity_final("0-l1".to_string())
             .build();
         let err = affinity.unwrap_err();
-        assert_eq!(err.to_string(), "Invalid execCPUAffinity format: 0-l1");
+        assert_eq!(
+            err.to_string(),
+            "final: invalid execCPUAffinity format (got 0-l1)"
+        );
 
         let affinity = ExecCPUAffinityBuilder::default()
             .initial("0-3,7".to_string())
             .cpu_affinity_final(",1,2".to_string())
             .build();
         let err = affinity.unwrap_err();
-        assert_eq!(err.to_string(), "Invalid execCPUAffinity format: ,1,2");
+        assert_eq!(
+            err.to_string(),
+            "final: invalid execCPUAffinity format (got ,1,2)"
+        );
     }
 
     #[test]
@@ -770,4 +1022,42 @@ mod tests {
         assert!(affinity.initial.is_none());
         assert!(affinity.cpu_affinity_final.is_none());
     }
+
+    #[test]
+    fn interactive_and_detached() {
+        let mut process = ProcessBuilder::default()
+            .env(vec!["TERM=screen".to_string()])
+            .build()
+            .unwrap();
+        process.set_interactive(None);
+        assert_eq!(process.terminal(), Some(true));
+        assert_eq!(process.env().as_ref().unwrap(), &["TERM=screen"]);
+
+        process.set_detached();
+        assert_eq!(process.terminal(), Some(false));
+        assert_eq!(process.console_size(), None);
+        assert!(process.env().as_ref().unwrap().is_empty());
+
+        let size = BoxBuilder::default()
+            .height(24u64)
+            .width(80u64)
+            .build()
+            .unwrap();
+        process.set_interactive(Some(size));
+        assert_eq!(process.console_size(), Some(size));
+        assert_eq!(process.env().as_ref().unwrap(), &["TERM=xterm"]);
+    }
+
+    #[test]
+    fn normalize_additional_gids() {
+        let mut user = User::default();
+        user.normalize_additional_gids();
+        assert_eq!(user.additional_gids(), &None);
+
+        user.set_additional_gids(Some(vec![10, 4, 10, 0, 4]));
+        user.normalize_additional_gids();
+        assert_eq!(user.additional_gids(), &Some(vec![0, 4, 10]));
+        user.add_additional_gids([3, 10]);
+        assert_eq!(user.additional_gids(), &Some(vec![0, 3, 4, 10]));
+    }
 }
@@ -0,0 +1,794 @@
+use super::{
+    all_capabilities, Arch, Capabilities, Capability, LinuxCpu, LinuxMemory, LinuxSeccomp,
+    LinuxSeccompAction, LinuxSeccompArg, LinuxSeccompFilterFlag, LinuxSyscall, Mount,
+    MountPropagation, Spec,
+};
+use crate::error::{oci_error, Result};
+use serde::{de::IntoDeserializer, Deserialize};
+use std::path::{Path, PathBuf};
+
+/// The CFS period in microseconds `--cpus` is translated with.
+const CPU_PERIOD: u64 = 100_000;
+
+#[derive(Clone, Debug, PartialEq)]
+/// A `docker run` or `podman run` option which translates into changes of a
+/// [Spec], see [Spec::apply_run_options].
+#[non_exhaustive]
+pub enum RunOption {
+    /// `--cap-add`, add capabilities to the bounding, effective and
+    /// permitted sets. `ALL` adds every capability.
+    CapAdd(Capabilities),
+
+    /// `--cap-drop`, drop capabilities from all sets. `ALL` drops every
+    /// capability.
+    CapDrop(Capabilities),
+
+    /// `-v` or `--volume` with a host path, a bind mount replacing any mount
+    /// at its destination.
+    Volume(Mount),
+
+    /// `-m` or `--memory`, the memory limit in bytes.
+    Memory(i64),
+
+    /// `--cpus`, the number of CPUs the container may use.
+    Cpus(f64),
+
+    /// `--security-opt seccomp=`, the path of a seccomp profile in the
+    /// format of Docker and Podman or `None` for `unconfined`.
+    Seccomp(Option<PathBuf>),
+
+    /// `--security-opt apparmor=`, the AppArmor profile or `None` for
+    /// `unconfined`.
+    Apparmor(Option<String>),
+
+    /// `--security-opt no-new-privileges`.
+    NoNewPrivileges(bool),
+}
+
+impl RunOption {
+    /// Parse the run options of the command line arguments `args`. Options
+    /// take their value either as the next argument or after a `=`, e.g.
+    /// `--memory 512m` or `--memory=512m`.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if an argument is not a supported option or its value is invalid.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::RunOption;
+    ///
+    /// let options = RunOption::parse_args(["--cap-drop=ALL", "-m", "1g"]).unwrap();
+    /// assert_eq!(options[1], RunOption::Memory(1 << 30));
+    /// ```
+    pub fn parse_args<I, S>(args: I) -> Result<Vec<Self>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut args = args.into_iter();
+        let mut options = Vec::new();
+        while let Some(arg) = args.next() {
+            let arg = arg.as_ref();
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, value.to_string()),
+                _ => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| oci_error(format!("missing value for {arg}")))?;
+                    (arg, value.as_ref().to_string())
+                }
+            };
+            options.push(Self::parse(name, &value)?);
+        }
+        Ok(options)
+    }
+
+    /// Parse the run option `name`, e.g. `--cap-add`, with its `value`.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if `name` is not a supported option or `value` is invalid.
+    pub fn parse(name: &str, value: &str) -> Result<Self> {
+        match name {
+            "--cap-add" => Ok(Self::CapAdd(parse_capabilities(value)?)),
+            "--cap-drop" => Ok(Self::CapDrop(parse_capabilities(value)?)),
+            "-v" | "--volume" => Ok(Self::Volume(parse_volume(value)?)),
+            "-m" | "--memory" => Ok(Self::Memory(parse_bytes(value)?)),
+            "--cpus" => match value.parse::<f64>() {
+                Ok(cpus) if cpus > 0.0 && cpus.is_finite() => Ok(Self::Cpus(cpus)),
+                _ => Err(oci_error(format!("invalid number of CPUs: {value}"))),
+            },
+            "--security-opt" => parse_security_opt(value),
+            name => Err(oci_error(format!("unsupported run option: {name}"))),
+        }
+    }
+}
+
+fn parse_capabilities(value: &str) -> Result<Capabilities> {
+    let mut capabilities = Capabilities::new();
+    for name in value.split(',').map(str::trim) {
+        if name.eq_ignore_ascii_case("ALL") {
+            capabilities.extend(all_capabilities());
+            continue;
+        }
+        let capability = Capability::deserialize(name.into_deserializer())
+            .map_err(|err: serde::de::value::Error| oci_error(err.to_string()))?;
+        capabilities.insert(capability);
+    }
+    Ok(capabilities)
+}
+
+fn parse_volume(value: &str) -> Result<Mount> {
+    let invalid = |reason: &str| oci_error(format!("invalid volume {value}: {reason}"));
+
+    let mut parts = value.splitn(3, ':');
+    let source = parts.next().unwrap_or_default();
+    let destination = parts.next().ok_or_else(|| invalid("missing destination"))?;
+    if !Path::new(source).is_absolute() {
+        return Err(invalid("named volumes are managed by the engine"));
+    }
+    if !Path::new(destination).is_absolute() {
+        return Err(invalid("destination is not absolute"));
+    }
+
+    let mut mount = Mount::default();
+    mount
+        .set_destination(destination.into())
+        .set_typ(Some("bind".to_string()))
+        .set_source(Some(source.into()))
+        .set_options(Some(vec!["rbind".to_string()]));
+    for option in parts
+        .next()
+        .into_iter()
+        .flat_map(|options| options.split(','))
+    {
+        match option {
+            "ro" | "rw" => mount.set_read_only(option == "ro"),
+            // Relabeling and copying content into volumes is done by engines.
+            "z" | "Z" | "nocopy" => {}
+            option => {
+                let propagation = option
+                    .parse::<MountPropagation>()
+                    .map_err(|_| invalid(&format!("unknown option {option}")))?;
+                mount.set_propagation(Some(propagation));
+            }
+        }
+    }
+    Ok(mount)
+}
+
+/// Parse a size like `512m` with the binary units `b`, `k`, `m`, `g` and `t`.
+fn parse_bytes(value: &str) -> Result<i64> {
+    let invalid = || oci_error(format!("invalid size: {value}"));
+
+    let lower = value.trim().to_ascii_lowercase();
+    let number = lower.trim_end_matches('b');
+    let (number, shift) = match number.char_indices().last() {
+        Some((i, 'k')) => (&number[..i], 10),
+        Some((i, 'm')) => (&number[..i], 20),
+        Some((i, 'g')) => (&number[..i], 30),
+        Some((i, 't')) => (&number[..i], 40),
+        _ => (number, 0),
+    };
+    let size = number.parse::<f64>().map_err(|_| invalid())? * (1u64 << shift) as f64;
+    if !size.is_finite() || size < 1.0 || size >= i64::MAX as f64 {
+        return Err(invalid());
+    }
+    Ok(size as i64)
+}
+
+fn parse_security_opt(value: &str) -> Result<RunOption> {
+    let (name, setting) = value
+        .split_once(['=', ':'])
+        .map_or((value, None), |(name, setting)| (name, Some(setting)));
+    match (name, setting) {
+        ("seccomp", Some("unconfined")) => Ok(RunOption::Seccomp(None)),
+        ("seccomp", Some(path)) => Ok(RunOption::Seccomp(Some(path.into()))),
+        ("apparmor", Some("unconfined")) => Ok(RunOption::Apparmor(None)),
+        ("apparmor", Some(profile)) => Ok(RunOption::Apparmor(Some(profile.to_string()))),
+        ("no-new-privileges", None | Some("true")) => Ok(RunOption::NoNewPrivileges(true)),
+        ("no-new-privileges", Some("false")) => Ok(RunOption::NoNewPrivileges(false)),
+        _ => Err(oci_error(format!("unsupported security option: {value}"))),
+    }
+}
+
+impl Spec {
+    /// Apply `docker run` or `podman run` options onto the spec.
+    ///
+    /// Capabilities are changed like engines do, regardless of the order of
+    /// the options: `--cap-add ALL` and `--cap-drop ALL` take effect first,
+    /// then individual capabilities are dropped and finally added. All other
+    /// options are applied in order.
+    ///
+    /// Seccomp profiles are converted like engines do for the architecture
+    /// and kernel of the host: the `archMap` entry of the native architecture
+    /// selects the architectures, and syscall rules are only kept if their
+    /// `includes` and `excludes` conditions on the architecture, the
+    /// bounding capabilities of the process and the kernel version match.
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if the spec has no process but options change it or a seccomp profile
+    /// is invalid, or an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// or [OciSpecError::FileSerDe](crate::OciSpecError::FileSerDe) if a
+    /// seccomp profile cannot be loaded.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{Capability, RunOption, Spec};
+    ///
+    /// let mut spec = Spec::default();
+    /// let options = RunOption::parse_args([
+    ///     "--cap-drop=ALL",
+    ///     "--cap-add=NET_BIND_SERVICE",
+    ///     "-v",
+    ///     "/srv/data:/data:ro",
+    ///     "--cpus=1.5",
+    /// ])
+    /// .unwrap();
+    /// spec.apply_run_options(&options).unwrap();
+    ///
+    /// let process = spec.process().as_ref().unwrap();
+    /// let capabilities = process.capabilities().as_ref().unwrap();
+    /// assert_eq!(capabilities.bounding().as_ref().unwrap().len(), 1);
+    /// let mounts = spec.mounts().as_ref().unwrap();
+    /// assert!(mounts.last().unwrap().is_read_only());
+    /// ```
+    pub fn apply_run_options(&mut self, options: &[RunOption]) -> Result<()> {
+        if self.process().is_none() {
+            if let Some(option) = options.iter().find(|option| {
+                matches!(
+                    option,
+                    RunOption::CapAdd(_)
+                        | RunOption::CapDrop(_)
+                        | RunOption::Apparmor(_)
+                        | RunOption::NoNewPrivileges(_)
+                )
+            }) {
+                return Err(oci_error(format!(
+                    "cannot apply {option:?} to a spec without a process"
+                )));
+            }
+        }
+        self.apply_capability_options(options);
+
+        for option in options {
+            match option {
+                RunOption::Volume(mount) => {
+                    let mounts = self.mounts_mut().get_or_insert_with(Vec::new);
+                    mounts.retain(|m| m.destination() != mount.destination());
+                    mounts.push(mount.clone());
+                }
+                RunOption::Memory(limit) => {
+                    self.linux_resources_mut()
+                        .memory_mut()
+                        .get_or_insert_with(LinuxMemory::default)
+                        .set_limit(Some(*limit));
+                }
+                RunOption::Cpus(cpus) => {
+                    let quota = (cpus * CPU_PERIOD as f64).round() as i64;
+                    self.linux_resources_mut()
+                        .cpu_mut()
+                        .get_or_insert_with(LinuxCpu::default)
+                        .set_quota(Some(quota))
+                        .set_period(Some(CPU_PERIOD));
+                }
+                RunOption::Seccomp(path) => {
+                    let seccomp = match path {
+                        Some(path) => {
+                            let profile: EngineSeccompProfile = crate::from_file(path)?;
+                            let bounding = self
+                                .process()
+                                .as_ref()
+                                .and_then(|process| process.capabilities().as_ref())
+                                .and_then(|capabilities| capabilities.bounding().clone())
+                                .unwrap_or_default();
+                            Some(profile.to_linux_seccomp(&SeccompHost {
+                                goarch: native_goarch(),
+                                arch: Arch::native_with_compat()[0],
+                                capabilities: &bounding,
+                                kernel: host_kernel_version(),
+                            })?)
+                        }
+                        None => None,
+                    };
+                    self.linux_mut()
+                        .get_or_insert_with(Default::default)
+                        .set_seccomp(seccomp);
+                }
+                RunOption::Apparmor(profile) => {
+                    if let Some(process) = self.process_mut() {
+                        process.set_apparmor_profile(profile.clone());
+                    }
+                }
+                RunOption::NoNewPrivileges(no_new_privileges) => {
+                    if let Some(process) = self.process_mut() {
+                        process.set_no_new_privileges(Some(*no_new_privileges));
+                    }
+                }
+                RunOption::CapAdd(_) | RunOption::CapDrop(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_capability_options(&mut self, options: &[RunOption]) {
+        let mut added = Capabilities::new();
+        let mut dropped = Capabilities::new();
+        for option in options {
+            match option {
+                RunOption::CapAdd(capabilities) => added.extend(capabilities),
+                RunOption::CapDrop(capabilities) => dropped.extend(capabilities),
+                _ => {}
+            }
+        }
+        if added.is_empty() && dropped.is_empty() {
+            return;
+        }
+        let Some(process) = self.process_mut() else {
+            return;
+        };
+
+        let all = all_capabilities();
+        let change = |set: &Option<Capabilities>, add: bool| {
+            let mut set = set.clone().unwrap_or_default();
+            if dropped.is_superset(&all) {
+                set.clear();
+            }
+            if add && added.is_superset(&all) {
+                set.extend(&all);
+            }
+            set.retain(|capability| added.contains(capability) || !dropped.contains(capability));
+            if add {
+                set.extend(&added);
+            }
+            Some(set)
+        };
+        let current = process.capabilities().clone().unwrap_or_default();
+        let mut capabilities = current.clone();
+        capabilities
+            .set_bounding(change(current.bounding(), true))
+            .set_effective(change(current.effective(), true))
+            .set_permitted(change(current.permitted(), true))
+            .set_inheritable(change(current.inheritable(), false))
+            .set_ambient(change(current.ambient(), false));
+        process.set_capabilities(Some(capabilities));
+    }
+
+    fn linux_resources_mut(&mut self) -> &mut super::LinuxResources {
+        self.linux_mut()
+            .get_or_insert_with(Default::default)
+            .resources_mut()
+            .get_or_insert_with(Default::default)
+    }
+}
+
+/// A seccomp profile in the format of Docker and Podman, which extends
+/// [LinuxSeccomp] with conditions of syscall rules and architectures which
+/// depend on the host.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineSeccompProfile {
+    default_action: LinuxSeccompAction,
+    #[serde(default)]
+    default_errno_ret: Option<u32>,
+    #[serde(default)]
+    architectures: Option<Vec<Arch>>,
+    #[serde(default)]
+    arch_map: Option<Vec<EngineArchMap>>,
+    #[serde(default)]
+    flags: Option<Vec<LinuxSeccompFilterFlag>>,
+    #[serde(default)]
+    listener_path: Option<PathBuf>,
+    #[serde(default)]
+    listener_metadata: Option<String>,
+    #[serde(default)]
+    syscalls: Option<Vec<EngineSyscall>>,
+}
+
+/// An architecture of a profile with its compat architectures.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineArchMap {
+    architecture: Arch,
+    #[serde(default)]
+    sub_architectures: Option<Vec<Arch>>,
+}
+
+/// A syscall rule of a profile.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineSyscall {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    names: Option<Vec<String>>,
+    action: LinuxSeccompAction,
+    #[serde(default)]
+    errno_ret: Option<u32>,
+    #[serde(default)]
+    args: Option<Vec<LinuxSeccompArg>>,
+    #[serde(default)]
+    includes: Option<EngineSyscallFilter>,
+    #[serde(default)]
+    excludes: Option<EngineSyscallFilter>,
+}
+
+/// The conditions of a syscall rule, `arches` are `GOARCH` values.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineSyscallFilter {
+    #[serde(default)]
+    arches: Option<Vec<String>>,
+    #[serde(default)]
+    caps: Option<Vec<String>>,
+    #[serde(default)]
+    min_kernel: Option<String>,
+}
+
+/// The host properties the conditions of a profile are evaluated against.
+struct SeccompHost<'a> {
+    goarch: &'a str,
+    arch: Arch,
+    capabilities: &'a Capabilities,
+    kernel: Option<(u32, u32)>,
+}
+
+impl SeccompHost<'_> {
+    fn has_capability(&self, name: &str) -> bool {
+        let capability: std::result::Result<Capability, serde::de::value::Error> =
+            Capability::deserialize(name.into_deserializer());
+        capability.is_ok_and(|capability| self.capabilities.contains(&capability))
+    }
+
+    /// Returns true if the kernel is at least `min_kernel`, e.g. `4.8`.
+    fn has_kernel(&self, min_kernel: &str) -> Result<bool> {
+        let min_kernel = parse_kernel_version(min_kernel)
+            .ok_or_else(|| oci_error(format!("invalid minKernel {min_kernel}")))?;
+        let kernel = self
+            .kernel
+            .ok_or_else(|| oci_error("profile depends on the unknown kernel version"))?;
+        Ok(kernel >= min_kernel)
+    }
+}
+
+impl EngineSeccompProfile {
+    fn to_linux_seccomp(&self, host: &SeccompHost) -> Result<LinuxSeccomp> {
+        let mut architectures = self.architectures.clone().unwrap_or_default();
+        if let Some(arch_map) = &self.arch_map {
+            if !architectures.is_empty() {
+                return Err(oci_error(
+                    "seccomp profile sets both architectures and archMap",
+                ));
+            }
+            for entry in arch_map
+                .iter()
+                .filter(|entry| entry.architecture == host.arch)
+            {
+                architectures.push(entry.architecture);
+                architectures.extend(entry.sub_architectures.iter().flatten());
+            }
+        }
+
+        let mut syscalls = Vec::new();
+        for syscall in self.syscalls.iter().flatten() {
+            let names = match (&syscall.name, &syscall.names) {
+                (Some(_), Some(names)) if !names.is_empty() => {
+                    return Err(oci_error("seccomp syscall sets both name and names"))
+                }
+                (Some(name), _) => vec![name.clone()],
+                (None, names) => names.clone().unwrap_or_default(),
+            };
+            if syscall.applies_to(host)? {
+                let mut rule = LinuxSyscall::default();
+                rule.set_names(names)
+                    .set_action(syscall.action)
+                    .set_errno_ret(syscall.errno_ret)
+                    .set_args(syscall.args.clone());
+                syscalls.push(rule);
+            }
+        }
+
+        let mut seccomp = LinuxSeccomp::default();
+        seccomp
+            .set_default_action(self.default_action)
+            .set_default_errno_ret(self.default_errno_ret)
+            .set_architectures((!architectures.is_empty()).then_some(architectures))
+            .set_flags(self.flags.clone())
+            .set_listener_path(self.listener_path.clone())
+            .set_listener_metadata(self.listener_metadata.clone())
+            .set_syscalls(Some(syscalls));
+        Ok(seccomp)
+    }
+}
+
+impl EngineSyscall {
+    /// Returns true if the host matches all `includes` and none of the
+    /// `excludes` conditions.
+    fn applies_to(&self, host: &SeccompHost) -> Result<bool> {
+        let filter = EngineSyscallFilter::default();
+        let includes = self.includes.as_ref().unwrap_or(&filter);
+        let excludes = self.excludes.as_ref().unwrap_or(&filter);
+
+        let in_arches =
+            |arches: &Option<Vec<String>>| arches.iter().flatten().any(|arch| arch == host.goarch);
+        if includes.arches.as_ref().is_some_and(|a| !a.is_empty()) && !in_arches(&includes.arches) {
+            return Ok(false);
+        }
+        if !includes
+            .caps
+            .iter()
+            .flatten()
+            .all(|cap| host.has_capability(cap))
+        {
+            return Ok(false);
+        }
+        if let Some(min_kernel) = &includes.min_kernel {
+            if !host.has_kernel(min_kernel)? {
+                return Ok(false);
+            }
+        }
+        if in_arches(&excludes.arches)
+            || excludes
+                .caps
+                .iter()
+                .flatten()
+                .any(|cap| host.has_capability(cap))
+        {
+            return Ok(false);
+        }
+        if let Some(min_kernel) = &excludes.min_kernel {
+            if host.has_kernel(min_kernel)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Parse the major and minor version of a kernel release like `6.8.0-45`.
+fn parse_kernel_version(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// The version of the running kernel, `None` if it cannot be read.
+fn host_kernel_version() -> Option<(u32, u32)> {
+    let release = std::fs::read_to_string("/proc/sys/kernel/osrelease").ok()?;
+    parse_kernel_version(release.trim())
+}
+
+/// The `GOARCH` name of the build target, which profiles use in conditions.
+fn native_goarch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "x86" => "386",
+        "aarch64" => "arm64",
+        "loongarch64" => "loong64",
+        "mips" if cfg!(target_endian = "little") => "mipsle",
+        "mips64" if cfg!(target_endian = "little") => "mips64le",
+        "powerpc64" if cfg!(target_endian = "little") => "ppc64le",
+        "powerpc64" => "ppc64",
+        arch => arch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_run_options() -> Result<()> {
+        let options = RunOption::parse_args([
+            "--cap-add",
+            "cap_sys_admin,NET_ADMIN",
+            "--volume=/srv:/srv:ro,rshared,Z",
+            "--memory=1.5G",
+            "--security-opt",
+            "seccomp=unconfined",
+            "--security-opt=no-new-privileges",
+        ])?;
+        assert_eq!(
+            options[0],
+            RunOption::CapAdd([Capability::SysAdmin, Capability::NetAdmin].into())
+        );
+        let RunOption::Volume(mount) = &options[1] else {
+            panic!("expected a volume, got {:?}", options[1]);
+        };
+        assert_eq!(
+            mount.options().as_deref(),
+            Some(&["rbind".to_string(), "ro".into(), "rshared".into()][..])
+        );
+        assert_eq!(options[2], RunOption::Memory(1536 << 20));
+        assert_eq!(options[3], RunOption::Seccomp(None));
+        assert_eq!(options[4], RunOption::NoNewPrivileges(true));
+
+        assert_eq!(parse_bytes("512")?, 512);
+        assert_eq!(parse_bytes("64kb")?, 64 << 10);
+        for invalid in [
+            &["--cap-add", "CAP_TIME_TRAVEL"][..],
+            &["-v", "data:/data"],
+            &["-v", "/data"],
+            &["-v", "/data:/data:exec"],
+            &["-m", "0"],
+            &["-m", "lots"],
+            &["--cpus", "-1"],
+            &["--security-opt", "label=disable"],
+            &["--privileged", "true"],
+            &["--memory"],
+        ] {
+            assert!(RunOption::parse_args(invalid).is_err(), "{invalid:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn apply_run_options() -> Result<()> {
+        let mut spec = Spec::default();
+        let options = RunOption::parse_args([
+            "--cap-add=SYS_PTRACE",
+            "--cap-drop=ALL",
+            "--cap-add=NET_RAW",
+            "-v",
+            "/proc:/proc",
+            "-m",
+            "256m",
+            "--cpus",
+            "0.5",
+            "--security-opt",
+            "apparmor=unconfined",
+        ])?;
+        spec.apply_run_options(&options)?;
+
+        let process = spec.process().as_ref().unwrap();
+        let capabilities = process.capabilities().as_ref().unwrap();
+        assert_eq!(
+            capabilities.effective().as_ref(),
+            Some(&[Capability::SysPtrace, Capability::NetRaw].into())
+        );
+        assert_eq!(capabilities.ambient().as_ref(), Some(&Capabilities::new()));
+        assert_eq!(process.apparmor_profile(), &None);
+
+        let mounts = spec.mounts().as_ref().unwrap();
+        let proc_mounts: Vec<_> = mounts
+            .iter()
+            .filter(|m| m.destination() == Path::new("/proc"))
+            .collect();
+        assert_eq!(proc_mounts.len(), 1);
+        assert_eq!(proc_mounts[0].typ().as_deref(), Some("bind"));
+
+        let resources = spec.linux().as_ref().unwrap().resources().as_ref().unwrap();
+        assert_eq!(
+            resources.memory().as_ref().unwrap().limit(),
+            Some(256 << 20)
+        );
+        let cpu = resources.cpu().as_ref().unwrap();
+        assert_eq!((cpu.quota(), cpu.period()), (Some(50_000), Some(100_000)));
+
+        let profile = tempfile::NamedTempFile::new()?;
+        std::fs::write(profile.path(), r#"{"defaultAction": "SCMP_ACT_ERRNO"}"#)?;
+        spec.apply_run_options(&[RunOption::Seccomp(Some(profile.path().into()))])?;
+        assert!(spec.linux().as_ref().unwrap().seccomp().is_some());
+        assert!(spec
+            .apply_run_options(&[RunOption::Seccomp(Some("/nonexistent".into()))])
+            .is_err());
+
+        spec.set_process(None);
+        assert!(spec
+            .apply_run_options(&[RunOption::NoNewPrivileges(true)])
+            .is_err());
+        assert!(spec
+            .apply_run_options(&[RunOption::Apparmor(None)])
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn convert_engine_seccomp_profile() -> Result<()> {
+        let profile: EngineSeccompProfile = serde_json::from_str(
+            r#"{
+                "defaultAction": "SCMP_ACT_ERRNO",
+                "defaultErrnoRet": 1,
+                "archMap": [
+                    {
+                        "architecture": "SCMP_ARCH_X86_64",
+                        "subArchitectures": ["SCMP_ARCH_X86", "SCMP_ARCH_X32"]
+                    },
+                    {
+                        "architecture": "SCMP_ARCH_AARCH64",
+                        "subArchitectures": ["SCMP_ARCH_ARM"]
+                    }
+                ],
+                "syscalls": [
+                    {"names": ["read", "write"], "action": "SCMP_ACT_ALLOW"},
+                    {
+                        "names": ["mount", "unshare", "setns"],
+                        "action": "SCMP_ACT_ALLOW",
+                        "includes": {"caps": ["CAP_SYS_ADMIN"]}
+                    },
+                    {
+                        "names": ["bpf"],
+                        "action": "SCMP_ACT_ALLOW",
+                        "includes": {"caps": ["CAP_SYS_ADMIN", "CAP_BPF"]}
+                    },
+                    {
+                        "names": ["arch_prctl"],
+                        "action": "SCMP_ACT_ALLOW",
+                        "includes": {"arches": ["amd64"]}
+                    },
+                    {
+                        "names": ["clone"],
+                        "action": "SCMP_ACT_ALLOW",
+                        "excludes": {"caps": ["CAP_SYS_ADMIN"]},
+                        "args": [{"index": 0, "value": 2114060288, "op": "SCMP_CMP_MASKED_EQ"}]
+                    },
+                    {
+                        "names": ["ptrace"],
+                        "action": "SCMP_ACT_ALLOW",
+                        "includes": {"minKernel": "4.8"}
+                    }
+                ]
+            }"#,
+        )?;
+        let names = |seccomp: &LinuxSeccomp| -> Vec<String> {
+            seccomp
+                .syscalls()
+                .iter()
+                .flatten()
+                .flat_map(|syscall| syscall.names().clone())
+                .collect()
+        };
+
+        let unprivileged = Capabilities::new();
+        let seccomp = profile.to_linux_seccomp(&SeccompHost {
+            goarch: "arm64",
+            arch: Arch::ScmpArchAarch64,
+            capabilities: &unprivileged,
+            kernel: Some((4, 4)),
+        })?;
+        assert_eq!(
+            seccomp.architectures().as_deref(),
+            Some(&[Arch::ScmpArchAarch64, Arch::ScmpArchArm][..])
+        );
+        assert_eq!(names(&seccomp), ["read", "write", "clone"]);
+        assert_eq!(seccomp.default_errno_ret(), Some(1));
+
+        let sys_admin: Capabilities = [Capability::SysAdmin].into();
+        let seccomp = profile.to_linux_seccomp(&SeccompHost {
+            goarch: "amd64",
+            arch: Arch::ScmpArchX86_64,
+            capabilities: &sys_admin,
+            kernel: Some((6, 1)),
+        })?;
+        assert_eq!(seccomp.architectures().as_ref().map(Vec::len), Some(3));
+        assert_eq!(
+            names(&seccomp),
+            [
+                "read",
+                "write",
+                "mount",
+                "unshare",
+                "setns",
+                "arch_prctl",
+                "ptrace"
+            ]
+        );
+
+        let unknown_kernel = SeccompHost {
+            goarch: "amd64",
+            arch: Arch::ScmpArchX86_64,
+            capabilities: &sys_admin,
+            kernel: None,
+        };
+        assert!(profile.to_linux_seccomp(&unknown_kernel).is_err());
+
+        let both: EngineSeccompProfile = serde_json::from_str(
+            r#"{
+                "defaultAction": "SCMP_ACT_ERRNO",
+                "architectures": ["SCMP_ARCH_X86_64"],
+                "archMap": [{"architecture": "SCMP_ARCH_X86_64"}]
+            }"#,
+        )?;
+        assert!(both.to_linux_seccomp(&unknown_kernel).is_err());
+        assert_eq!(parse_kernel_version("6.18.44-fc-v130"), Some((6, 18)));
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use super::{Arch, LinuxSeccomp, LinuxSeccompAction, LinuxSeccompArg, LinuxSeccompOperator};
+use getset::CopyGetters;
+
+#[derive(Clone, Copy, CopyGetters, Debug, Eq, PartialEq)]
+#[getset(get_copy = "pub")]
+/// The outcome of a syscall evaluated against a seccomp profile.
+#[non_exhaustive]
+pub struct SeccompVerdict {
+    /// The action the filter takes for the syscall.
+    action: LinuxSeccompAction,
+
+    /// The error or trace return value of the action.
+    errno_ret: Option<u32>,
+}
+
+impl SeccompVerdict {
+    /// Returns true if the syscall is executed, possibly after being logged.
+    pub fn is_allowed(&self) -> bool {
+        matches!(
+            self.action,
+            LinuxSeccompAction::ScmpActAllow | LinuxSeccompAction::ScmpActLog
+        )
+    }
+}
+
+impl LinuxSeccomp {
+    /// Evaluate which action the profile takes for the syscall `name` called
+    /// with the argument values `args` on the architecture `arch`, without
+    /// loading a filter into the kernel.
+    ///
+    /// The profile applies to the architectures it lists, the build target's
+    /// architecture and [Arch::ScmpArchNative], or to every architecture if
+    /// it lists none. Other architectures are killed, which is the bad
+    /// architecture action of libseccomp.
+    ///
+    /// Rules with argument conditions are checked before rules without,
+    /// otherwise the first matching rule of the profile decides. The
+    /// conditions of a rule have to match all, unless a rule has several
+    /// conditions for the same argument, which runtimes load as alternatives.
+    /// Missing argument values are zero. The profile's default action applies
+    /// if no rule matches.
+    /// # Example
+    /// ```
+    /// use oci_spec::runtime::{get_default_seccomp, Arch, LinuxSeccompAction};
+    ///
+    /// let profile = get_default_seccomp();
+    /// let verdict = profile.evaluate(Arch::ScmpArchX86_64, "clone", &[]);
+    /// assert!(verdict.is_allowed());
+    /// let verdict = profile.evaluate(Arch::ScmpArchX86_64, "kexec_load", &[]);
+    /// assert_eq!(verdict.action(), LinuxSeccompAction::ScmpActErrno);
+    /// assert_eq!(verdict.errno_ret(), Some(1));
+    /// let verdict = profile.evaluate(Arch::ScmpArchX86_64, "clone3", &[]);
+    /// assert_eq!(verdict.errno_ret(), Some(38));
+    /// ```
+    pub fn evaluate(&self, arch: Arch, name: &str, args: &[u64]) -> SeccompVerdict {
+        let architectures = self.architectures().as_deref().unwrap_or_default();
+        if !architectures.is_empty()
+            && arch != Arch::ScmpArchNative
+            && !architectures.contains(&arch)
+            && Arch::native_with_compat().first() != Some(&arch)
+        {
+            return SeccompVerdict {
+                action: LinuxSeccompAction::ScmpActKillThread,
+                errno_ret: None,
+            };
+        }
+
+        let rules: Vec<_> = self
+            .syscalls()
+            .iter()
+            .flatten()
+            .filter(|syscall| syscall.names().iter().any(|n| n == name))
+            .collect();
+        let conditional = rules
+            .iter()
+            .filter(|syscall| syscall.args().as_ref().is_some_and(|a| !a.is_empty()));
+        let unconditional = rules
+            .iter()
+            .filter(|syscall| syscall.args().as_ref().is_none_or(|a| a.is_empty()));
+        conditional
+            .chain(unconditional)
+            .find(|syscall| {
+                let conditions = syscall.args().as_deref().unwrap_or_default();
+                let alternatives = conditions.iter().enumerate().any(|(i, condition)| {
+                    conditions[..i]
+                        .iter()
+                        .any(|other| other.index() == condition.index())
+                });
+                if alternatives {
+                    conditions.iter().any(|condition| condition.matches(args))
+                } else {
+                    conditions.iter().all(|condition| condition.matches(args))
+                }
+            })
+            .map_or(
+                SeccompVerdict {
+                    action: self.default_action(),
+                    errno_ret: self.default_errno_ret(),
+                },
+                |syscall| SeccompVerdict {
+                    action: syscall.action(),
+                    errno_ret: syscall.errno_ret(),
+                },
+            )
+    }
+}
+
+impl LinuxSeccompArg {
+    /// Returns true if the argument value at the index of the condition in
+    /// `args` fulfills the condition, missing values are zero.
+    pub fn matches(&self, args: &[u64]) -> bool {
+        let arg = args.get(self.index()).copied().unwrap_or_default();
+        let value = self.value();
+        match self.op() {
+            LinuxSeccompOperator::ScmpCmpNe => arg != value,
+            LinuxSeccompOperator::ScmpCmpLt => arg < value,
+            LinuxSeccompOperator::ScmpCmpLe => arg <= value,
+            LinuxSeccompOperator::ScmpCmpEq => arg == value,
+            LinuxSeccompOperator::ScmpCmpGe => arg >= value,
+            LinuxSeccompOperator::ScmpCmpGt => arg > value,
+            LinuxSeccompOperator::ScmpCmpMaskedEq => {
+                arg & value == self.value_two().unwrap_or_default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{
+        get_default_seccomp, LinuxSeccompArgBuilder, LinuxSeccompBuilder, LinuxSyscallBuilder,
+    };
+
+    #[test]
+    fn evaluate_seccomp() {
+        let arg = |index: usize, value: u64, op| {
+            LinuxSeccompArgBuilder::default()
+                .index(index)
+                .value(value)
+                .op(op)
+                .build()
+                .unwrap()
+        };
+        // Namespace flags are denied for clone, clone3 is not supported.
+        let clone = LinuxSyscallBuilder::default()
+            .names(vec!["clone".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![LinuxSeccompArgBuilder::default()
+                .index(0usize)
+                .value(0x7e020000u64)
+                .value_two(0u64)
+                .op(LinuxSeccompOperator::ScmpCmpMaskedEq)
+                .build()
+                .unwrap()])
+            .build()
+            .unwrap();
+        let clone3 = LinuxSyscallBuilder::default()
+            .names(vec!["clone3".to_string()])
+            .action(LinuxSeccompAction::ScmpActErrno)
+            .errno_ret(38u32)
+            .build()
+            .unwrap();
+        let personality = LinuxSyscallBuilder::default()
+            .names(vec!["personality".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![
+                arg(0, 0x0, LinuxSeccompOperator::ScmpCmpEq),
+                arg(0, 0x8, LinuxSeccompOperator::ScmpCmpEq),
+            ])
+            .build()
+            .unwrap();
+        let write = LinuxSyscallBuilder::default()
+            .names(vec!["write".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .build()
+            .unwrap();
+        let log_stdout = LinuxSyscallBuilder::default()
+            .names(vec!["write".to_string()])
+            .action(LinuxSeccompAction::ScmpActLog)
+            .args(vec![arg(0, 1, LinuxSeccompOperator::ScmpCmpEq)])
+            .build()
+            .unwrap();
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .default_errno_ret(1u32)
+            .architectures(vec![Arch::ScmpArchX86_64, Arch::ScmpArchX86])
+            .syscalls(vec![clone, clone3, personality, write, log_stdout])
+            .build()
+            .unwrap();
+
+        let x86_64 = Arch::ScmpArchX86_64;
+        let verdict = profile.evaluate(x86_64, "clone3", &[]);
+        assert_eq!(verdict.action(), LinuxSeccompAction::ScmpActErrno);
+        assert_eq!(verdict.errno_ret(), Some(38));
+        assert!(profile.evaluate(x86_64, "clone", &[0x11]).is_allowed());
+        assert!(!profile
+            .evaluate(x86_64, "clone", &[0x10000000])
+            .is_allowed());
+        assert!(profile.evaluate(x86_64, "personality", &[0x8]).is_allowed());
+        assert!(!profile.evaluate(x86_64, "personality", &[0x1]).is_allowed());
+        assert_eq!(
+            profile.evaluate(x86_64, "write", &[1]).action(),
+            LinuxSeccompAction::ScmpActLog
+        );
+        assert_eq!(
+            profile.evaluate(x86_64, "write", &[2]).action(),
+            LinuxSeccompAction::ScmpActAllow
+        );
+        let verdict = profile.evaluate(x86_64, "reboot", &[]);
+        assert_eq!(verdict.action(), LinuxSeccompAction::ScmpActErrno);
+        assert_eq!(verdict.errno_ret(), Some(1));
+        if Arch::native_with_compat()[0] != Arch::ScmpArchS390x {
+            assert_eq!(
+                profile.evaluate(Arch::ScmpArchS390x, "write", &[]).action(),
+                LinuxSeccompAction::ScmpActKillThread
+            );
+        }
+    }
+
+    #[test]
+    fn evaluate_default_seccomp() {
+        let profile = get_default_seccomp();
+        let x86_64 = Arch::ScmpArchX86_64;
+        let eperm = |name: &str, args: &[u64]| {
+            let verdict = profile.evaluate(x86_64, name, args);
+            verdict.action() == LinuxSeccompAction::ScmpActErrno && verdict.errno_ret() == Some(1)
+        };
+
+        for name in [
+            "mount",
+            "fsopen",
+            "fsconfig",
+            "fsmount",
+            "fspick",
+            "move_mount",
+            "open_tree",
+            "mount_setattr",
+            "io_uring_setup",
+            "io_uring_enter",
+            "io_uring_register",
+            "unshare",
+            "setns",
+        ] {
+            assert!(eperm(name, &[]), "{name}");
+        }
+
+        // CLONE_VM | CLONE_FS | CLONE_FILES | CLONE_SIGHAND | CLONE_THREAD
+        // of a new thread and SIGCHLD of fork are allowed.
+        assert!(profile.evaluate(x86_64, "clone", &[0x10f00]).is_allowed());
+        assert!(profile.evaluate(x86_64, "clone", &[0x11]).is_allowed());
+        assert!(profile.evaluate(x86_64, "clone", &[]).is_allowed());
+        for flag in [
+            0x00020000, 0x02000000, 0x04000000, 0x08000000, 0x10000000, 0x20000000, 0x40000000,
+        ] {
+            assert!(eperm("clone", &[flag | 0x11]), "{flag:#x}");
+        }
+        assert!(eperm("clone", &[0x10000000 | 0x40000000]));
+
+        let verdict = profile.evaluate(x86_64, "clone3", &[]);
+        assert_eq!(verdict.errno_ret(), Some(38));
+        assert!(profile.evaluate(x86_64, "read", &[]).is_allowed());
+    }
+}
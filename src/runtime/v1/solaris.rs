@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 #[getset(get = "pub", set = "pub")]
 /// Solaris contains platform-specific configuration for Solaris application
 /// containers.
+#[non_exhaustive]
 pub struct Solaris {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// SMF FMRI which should go "online" before we start the container
@@ -58,6 +59,7 @@ pub struct Solaris {
 #[getset(get = "pub", set = "pub")]
 /// SolarisAnet provides the specification for automatic creation of network
 /// resources for this container.
+#[non_exhaustive]
 pub struct SolarisAnet {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Specify a name for the automatically created VNIC datalink.
@@ -101,6 +103,7 @@ pub struct SolarisAnet {
 #[getset(get = "pub", set = "pub")]
 /// SolarisCappedCPU allows users to set limit on the amount of CPU time
 /// that can be used by container.
+#[non_exhaustive]
 pub struct SolarisCappedCPU {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// The amount of CPUs.
@@ -119,6 +122,7 @@ pub struct SolarisCappedCPU {
 #[getset(get = "pub", set = "pub")]
 /// SolarisCappedMemory allows users to set the physical and swap caps on
 /// the memory that can be used by this container.
+#[non_exhaustive]
 pub struct SolarisCappedMemory {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// The physical caps on the memory.
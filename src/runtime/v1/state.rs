@@ -0,0 +1,381 @@
+use crate::error::{
+    file_io_error, file_serde_error, oci_error, FindingCode, OciSpecError, ValidationError,
+    ValidationErrorKind,
+};
+
+use std::{
+    fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use derive_builder::Builder;
+use getset::{Getters, MutGetters, Setters};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Display};
+
+/// ContainerState represents the state of a container.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ContainerState {
+    /// Creating indicates that the container is being created,
+    Creating,
+
+    /// Created indicates that the runtime has finished the create operation,
+    /// but the container exists but has not been run yet.
+    Created,
+
+    /// Running indicates that the container process has executed the
+    /// user-specified program but has not exited
+    Running,
+
+    /// Stopped indicates that the container process has exited,
+    /// and does not have a created or running process.
+    #[default]
+    Stopped,
+}
+
+impl Display for ContainerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContainerState::Creating => write!(f, "creating"),
+            ContainerState::Created => write!(f, "created"),
+            ContainerState::Running => write!(f, "running"),
+            ContainerState::Stopped => write!(f, "stopped"),
+        }
+    }
+}
+
+/// State holds information about the runtime state of the container.
+#[derive(
+    Builder,
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    Getters,
+    MutGetters,
+    Setters,
+    PartialEq,
+    Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[builder(
+    default,
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(error = "OciSpecError")
+)]
+#[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
+pub struct State {
+    /// version is the version of the specification that is supported.
+    #[serde(default, rename = "ociVersion")]
+    version: String,
+
+    /// id is the container ID
+    #[serde(default)]
+    id: String,
+
+    /// status is the runtime status of the container.
+    #[serde(default)]
+    status: ContainerState,
+
+    /// pid is the process ID for the container process.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pid: Option<i32>,
+
+    /// bundle is the path to the container's bundle directory.
+    #[serde(default)]
+    bundle: PathBuf,
+
+    /// annotations are key values associated with the container.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
+    annotations: Option<HashMap<String, String>>,
+}
+
+impl State {
+    /// Load a State from the provided JSON file path.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo] if the file does not exist or an
+    /// [OciSpecError::FileSerDe] if the JSON is invalid.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, OciSpecError> {
+        let path = path.as_ref();
+        let file = fs::File::open(path).map_err(file_io_error(path))?;
+        let reader = BufReader::new(file);
+        let state = serde_json::from_reader(reader).map_err(file_serde_error(path))?;
+        Ok(state)
+    }
+
+    /// Save a State to the provided JSON file path.
+    /// # Errors
+    /// This function will return an [OciSpecError::FileIo] if a file cannot be created at the provided
+    /// path or an [OciSpecError::FileSerDe] if the state cannot be serialized.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), OciSpecError> {
+        let path = path.as_ref();
+        let file = fs::File::create(path).map_err(file_io_error(path))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self).map_err(file_serde_error(path))?;
+        writer.flush().map_err(file_io_error(path))?;
+        Ok(())
+    }
+
+    /// Read the State an OCI hook receives on its standard input.
+    ///
+    /// The input is read to EOF, surrounding whitespace is ignored and so are
+    /// fields added by newer versions of the specification.
+    /// # Errors
+    /// This function will return an [OciSpecError::Io] if stdin cannot be read
+    /// or an [OciSpecError::SerDe] if the state is not valid JSON.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::runtime::State;
+    ///
+    /// let state = State::from_hook_stdin().unwrap();
+    /// println!("hook called for container {}", state.id());
+    /// ```
+    pub fn from_hook_stdin() -> Result<Self, OciSpecError> {
+        Self::from_hook_reader(io::stdin().lock())
+    }
+
+    /// Read the State passed to an OCI hook from `reader`, see
+    /// [State::from_hook_stdin].
+    pub fn from_hook_reader<R: Read>(mut reader: R) -> Result<Self, OciSpecError> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(oci_error("no container state was passed to the hook"));
+        }
+        Ok(serde_json::from_str(input)?)
+    }
+}
+
+/// SeccompFdName is the name of the seccomp notify file descriptor.
+/// Used in ContainerProcessState.fds to identify seccomp listener file descriptors.
+/// See: <https://github.com/opencontainers/runtime-spec/blob/main/specs-go/state.go>
+pub const SECCOMP_FD_NAME: &str = "seccompFd";
+
+/// ContainerProcessState holds information about the state of a container process.
+#[derive(
+    Builder,
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    Getters,
+    MutGetters,
+    Setters,
+    PartialEq,
+    Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[builder(
+    default,
+    pattern = "owned",
+    setter(into, strip_option),
+    build_fn(validate = "Self::validate", error = "OciSpecError")
+)]
+#[getset(get_mut = "pub", get = "pub", set = "pub")]
+#[non_exhaustive]
+pub struct ContainerProcessState {
+    /// version is the version of the specification that is supported.
+    #[serde(default, rename = "ociVersion")]
+    version: String,
+
+    /// fds is a string array containing the names of the file descriptors passed.
+    /// The index of the name in this array corresponds to index of the file
+    /// descriptor in the `SCM_RIGHTS` array.
+    #[serde(default)]
+    fds: Vec<String>,
+
+    /// pid is the process ID as seen by the runtime.
+    #[serde(default)]
+    pid: i32,
+
+    /// opaque metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metadata: Option<String>,
+
+    /// state of the container.
+    #[serde(default)]
+    state: State,
+}
+
+impl ContainerProcessStateBuilder {
+    fn validate(&self) -> Result<(), OciSpecError> {
+        let pid = self.pid.unwrap_or_default();
+        if pid <= 0 {
+            return Err(ValidationError::new(
+                ValidationErrorKind::OutOfRange,
+                "pid",
+                "must be a valid process ID",
+            )
+            .with_code(FindingCode::StatePidInvalid)
+            .with_expected("greater than 0")
+            .with_actual(pid.to_string())
+            .into());
+        }
+
+        let fds = self.fds.as_deref().unwrap_or_default();
+        for (i, name) in fds.iter().enumerate() {
+            if name.is_empty() {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Missing,
+                    format!("fds[{i}]"),
+                    "file descriptor name must not be empty",
+                )
+                .with_code(FindingCode::StateFdNameEmpty)
+                .into());
+            }
+            if fds[..i].contains(name) {
+                return Err(ValidationError::new(
+                    ValidationErrorKind::Conflict,
+                    format!("fds[{i}]"),
+                    "file descriptor names must be unique",
+                )
+                .with_code(FindingCode::StateFdNameDuplicate)
+                .with_actual(name.as_str())
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ContainerProcessState {
+    /// Return the index of the seccomp notify file descriptor in the
+    /// `SCM_RIGHTS` array, as needed for the seccomp listener handshake.
+    /// # Errors
+    /// This function will return an [OciSpecError::Validation] if no file
+    /// descriptor is named [SECCOMP_FD_NAME].
+    pub fn seccomp_fd_index(&self) -> Result<usize, OciSpecError> {
+        self.fds
+            .iter()
+            .position(|name| name == SECCOMP_FD_NAME)
+            .ok_or_else(|| {
+                ValidationError::new(
+                    ValidationErrorKind::Missing,
+                    "fds",
+                    format!("no {SECCOMP_FD_NAME} file descriptor was passed"),
+                )
+                .with_code(FindingCode::StateSeccompFdMissing)
+                .into()
+            })
+    }
+}
+
+impl TryFrom<serde_json::Value> for State {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a JSON value into a container state.
+    fn try_from(value: serde_json::Value) -> Result<Self, OciSpecError> {
+        crate::from_value(value)
+    }
+}
+
+impl TryFrom<State> for serde_json::Value {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a container state into a JSON value.
+    fn try_from(state: State) -> Result<Self, OciSpecError> {
+        crate::to_value(&state)
+    }
+}
+
+impl TryFrom<serde_json::Value> for ContainerProcessState {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a JSON value into a container process state.
+    fn try_from(value: serde_json::Value) -> Result<Self, OciSpecError> {
+        crate::from_value(value)
+    }
+}
+
+impl TryFrom<ContainerProcessState> for serde_json::Value {
+    type Error = OciSpecError;
+
+    /// Attempts to convert a container process state into a JSON value.
+    fn try_from(state: ContainerProcessState) -> Result<Self, OciSpecError> {
+        crate::to_value(&state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_process_state_builder() {
+        let state = ContainerProcessStateBuilder::default()
+            .pid(1234)
+            .fds(vec!["stdin".to_string(), SECCOMP_FD_NAME.to_string()])
+            .build()
+            .unwrap();
+        assert_eq!(state.seccomp_fd_index().unwrap(), 1);
+
+        assert!(ContainerProcessStateBuilder::default().build().is_err());
+        let err = ContainerProcessStateBuilder::default()
+            .pid(1234)
+            .fds(vec![
+                SECCOMP_FD_NAME.to_string(),
+                SECCOMP_FD_NAME.to_string(),
+            ])
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "fds[1]: file descriptor names must be unique (got seccompFd)"
+        );
+        assert!(ContainerProcessStateBuilder::default()
+            .pid(1234)
+            .fds(vec![String::new()])
+            .build()
+            .is_err());
+
+        let state = ContainerProcessStateBuilder::default()
+            .pid(1234)
+            .build()
+            .unwrap();
+        assert!(state.seccomp_fd_index().is_err());
+    }
+
+    #[test]
+    fn test_from_hook_reader() {
+        let input = "{\"ociVersion\":\"1.2.0\",\"id\":\"abc\",\"status\":\"creating\",\
+                     \"pid\":42,\"bundle\":\"/bundle\",\"newerField\":{}}\n\n";
+        let state = State::from_hook_reader(input.as_bytes()).unwrap();
+        assert_eq!(state.id(), "abc");
+        assert_eq!(state.status(), &ContainerState::Creating);
+        assert_eq!(state.pid(), &Some(42));
+
+        assert!(State::from_hook_reader(" \n".as_bytes()).is_err());
+        assert!(State::from_hook_reader("{".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_load_save() {
+        let state = State {
+            ..Default::default()
+        };
+        let test_dir = tempfile::tempdir().expect("failed to create tmp test dir");
+        let state_path = test_dir.keep().join("state.json");
+
+        // Test first save the default config, and then load the saved config.
+        // The before and after should be the same.
+        state.save(&state_path).expect("failed to save state");
+        let loaded_state = State::load(&state_path).expect("failed to load state");
+        assert_eq!(
+            state, loaded_state,
+            "The saved state is not the same as the loaded state"
+        );
+    }
+}
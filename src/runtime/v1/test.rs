@@ -33,7 +33,7 @@ fn test_linux_device_cgroup_to_string() {
 #[test]
 fn test_load_sample_spec() {
     let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("src/runtime/test/fixture/sample.json");
+        .join("src/runtime/v1/test/fixture/sample.json");
     let err = Spec::load(fixture_path);
     assert!(err.is_ok(), "failed to load spec: {err:?}");
 }
@@ -41,7 +41,7 @@ fn test_load_sample_spec() {
 #[test]
 fn test_load_sample_state() {
     let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("src/runtime/test/fixture/sample_state.json");
+        .join("src/runtime/v1/test/fixture/sample_state.json");
     let err = State::load(fixture_path);
     assert!(err.is_ok(), "failed to load state: {err:?}");
 }
@@ -49,7 +49,7 @@ fn test_load_sample_state() {
 #[test]
 fn test_load_sample_windows_spec() {
     let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("src/runtime/test/fixture/sample_windows.json");
+        .join("src/runtime/v1/test/fixture/sample_windows.json");
     let err = Spec::load(fixture_path);
     assert!(err.is_ok(), "failed to load spec: {err:?}");
 }
@@ -57,7 +57,7 @@ fn test_load_sample_windows_spec() {
 #[test]
 fn test_load_sample_zos_spec() {
     let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("src/runtime/test/fixture/sample_zos.json");
+        .join("src/runtime/v1/test/fixture/sample_zos.json");
     let err = Spec::load(fixture_path);
     assert!(err.is_ok(), "failed to load spec: {err:?}");
 }
@@ -65,7 +65,7 @@ fn test_load_sample_zos_spec() {
 #[test]
 fn test_linux_netdevice_lifecycle() {
     let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("src/runtime/test/fixture/sample.json");
+        .join("src/runtime/v1/test/fixture/sample.json");
 
     let spec =
         Spec::load(fixture_path).unwrap_or_else(|err| panic!("Failed to load spec: {}", err));
@@ -0,0 +1,213 @@
+use super::User;
+use crate::{
+    error::{file_io_error, oci_error, Result},
+    rootfs::resolve,
+};
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+};
+
+/// The path of the user database relative to the root filesystem.
+const PASSWD_FILE: &str = "etc/passwd";
+
+/// The path of the group database relative to the root filesystem.
+const GROUP_FILE: &str = "etc/group";
+
+/// The maximum size of a database which is read.
+const MAX_DATABASE_SIZE: u64 = 16 << 20;
+
+impl User {
+    /// Look up the user `name` in the passwd and group databases of the
+    /// host, see [User::lookup_in].
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if the user does not exist, or an [OciSpecError::FileIo](crate::OciSpecError::FileIo)
+    /// if a database cannot be read.
+    pub fn lookup(name: &str) -> Result<Self> {
+        User::lookup_in("/", name)
+    }
+
+    /// Look up the user `name` in the `/etc/passwd` and `/etc/group` files
+    /// of the root filesystem `rootfs`, like engines do before writing
+    /// `config.json`, and return a user with its uid, primary gid and the
+    /// additional gids of the groups it is a member of.
+    ///
+    /// Symbolic links are resolved inside `rootfs` and only regular files
+    /// are read, so an untrusted image cannot make the lookup read files of
+    /// the host.
+    ///
+    /// `name` is either a user name or a numeric uid. A numeric uid which
+    /// is not in the passwd database is used as is with the gid 0. A
+    /// missing group database is treated as empty. The additional gids
+    /// contain the primary gid and are sorted, see
+    /// [User::normalize_additional_gids].
+    /// # Errors
+    /// This function will return an [OciSpecError::Other](crate::OciSpecError::Other)
+    /// if the user does not exist or a database is not a regular file or too
+    /// large, or an [OciSpecError::FileIo](crate::OciSpecError::FileIo) if a
+    /// database cannot be read.
+    /// # Example
+    /// ``` no_run
+    /// use oci_spec::runtime::User;
+    ///
+    /// let user = User::lookup_in("rootfs", "nobody").unwrap();
+    /// println!("{}:{}", user.uid(), user.gid());
+    /// ```
+    pub fn lookup_in<P: AsRef<Path>>(rootfs: P, name: &str) -> Result<Self> {
+        let rootfs = rootfs.as_ref();
+        let passwd = read_database(rootfs, PASSWD_FILE)?;
+        let numeric = name.parse::<u32>().ok();
+
+        let entry = passwd
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .find_map(|line| {
+                let [user, _, uid, gid, ..] = fields(line)?[..] else {
+                    return None;
+                };
+                let (uid, gid) = (uid.parse::<u32>().ok()?, gid.parse::<u32>().ok()?);
+                (user == name || Some(uid) == numeric).then(|| (user.to_string(), uid, gid))
+            });
+        let mut user = User::default();
+        let Some((name, uid, gid)) = entry else {
+            let uid = numeric.ok_or_else(|| {
+                oci_error(format!(
+                    "user {name} not found in {}",
+                    rootfs.join(PASSWD_FILE).display()
+                ))
+            })?;
+            user.set_uid(uid);
+            return Ok(user);
+        };
+        user.set_uid(uid);
+        user.set_gid(gid);
+
+        let groups = read_database(rootfs, GROUP_FILE)?;
+        let member_gids = groups
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let [_, _, gid, members] = fields(line)?[..] else {
+                    return None;
+                };
+                members
+                    .split(',')
+                    .any(|member| member.trim() == name)
+                    .then(|| gid.parse::<u32>().ok())?
+            });
+        user.add_additional_gids(std::iter::once(gid).chain(member_gids));
+        Ok(user)
+    }
+}
+
+/// Read the database at `path` inside `rootfs`, `None` if it does not exist.
+fn read_database(rootfs: &Path, path: &str) -> Result<Option<String>> {
+    let path = resolve(rootfs, path)?;
+    let metadata = match fs::symlink_metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(file_io_error(&path)(err)),
+    };
+    if !metadata.is_file() {
+        return Err(oci_error(format!(
+            "{} is not a regular file",
+            path.display()
+        )));
+    }
+
+    let file = File::open(&path).map_err(file_io_error(&path))?;
+    let mut content = String::new();
+    file.take(MAX_DATABASE_SIZE + 1)
+        .read_to_string(&mut content)
+        .map_err(file_io_error(&path))?;
+    if content.len() as u64 > MAX_DATABASE_SIZE {
+        return Err(oci_error(format!(
+            "{} is larger than {MAX_DATABASE_SIZE} bytes",
+            path.display()
+        )));
+    }
+    Ok(Some(content))
+}
+
+/// Split a database line into its colon separated fields, `None` for
+/// comments, empty lines and NIS compat entries.
+fn fields(line: &str) -> Option<Vec<&str>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with(['#', '+', '-']) {
+        return None;
+    }
+    Some(line.split(':').collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_user() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let rootfs = tmp.path();
+        fs::create_dir(rootfs.join("etc"))?;
+        fs::write(
+            rootfs.join(PASSWD_FILE),
+            "# users\n\
+             root:x:0:0:root:/root:/bin/sh\n\
+             +nis\n\
+             app:x:1000:1000::/home/app:/bin/sh\n\
+             broken:x:uid:1\n",
+        )?;
+
+        // Without a group database only the primary group is set.
+        let user = User::lookup_in(rootfs, "app")?;
+        assert_eq!((user.uid(), user.gid()), (1000, 1000));
+        assert_eq!(user.additional_gids(), &Some(vec![1000]));
+
+        fs::write(
+            rootfs.join(GROUP_FILE),
+            "root:x:0:\n\
+             wheel:x:10:root, app\n\
+             video:x:44:app\n\
+             app:x:1000:\n\
+             audio:x:63:other\n",
+        )?;
+        let user = User::lookup_in(rootfs, "app")?;
+        assert_eq!(user.additional_gids(), &Some(vec![10, 44, 1000]));
+        assert_eq!(User::lookup_in(rootfs, "1000")?, user);
+
+        let root = User::lookup_in(rootfs, "root")?;
+        assert_eq!(root.additional_gids(), &Some(vec![0, 10]));
+
+        let unknown = User::lookup_in(rootfs, "2000")?;
+        assert_eq!((unknown.uid(), unknown.gid()), (2000, 0));
+        assert_eq!(unknown.additional_gids(), &None);
+        assert!(User::lookup_in(rootfs, "nobody").is_err());
+        assert!(User::lookup_in(rootfs, "broken").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn lookup_stays_in_rootfs() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let rootfs = tmp.path().join("rootfs");
+        fs::create_dir_all(rootfs.join("etc"))?;
+        fs::write(tmp.path().join("passwd"), "app:x:1000:1000::/:/bin/sh\n")?;
+        fs::write(rootfs.join("passwd"), "app:x:2000:2000::/:/bin/sh\n")?;
+
+        // Relative and absolute links are resolved inside the rootfs.
+        std::os::unix::fs::symlink("../../passwd", rootfs.join("etc/passwd"))?;
+        std::os::unix::fs::symlink("/dev/zero", rootfs.join("etc/group"))?;
+        let user = User::lookup_in(&rootfs, "app")?;
+        assert_eq!((user.uid(), user.gid()), (2000, 2000));
+        assert_eq!(user.additional_gids(), &Some(vec![2000]));
+
+        fs::remove_file(rootfs.join("etc/group"))?;
+        fs::create_dir(rootfs.join("etc/group"))?;
+        let err = User::lookup_in(&rootfs, "app").unwrap_err();
+        assert!(err.to_string().contains("not a regular file"), "{err}");
+        Ok(())
+    }
+}
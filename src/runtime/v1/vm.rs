@@ -15,6 +15,7 @@ use std::path::PathBuf;
 )]
 #[getset(get = "pub", set = "pub")]
 /// VM contains information for virtual-machine-based containers.
+#[non_exhaustive]
 pub struct VM {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Hypervisor specifies hypervisor-related configuration for
@@ -43,6 +44,7 @@ pub struct VM {
 #[getset(get = "pub", set = "pub")]
 /// VMHypervisor contains information about the hypervisor to use for a
 /// virtual machine.
+#[non_exhaustive]
 pub struct VMHypervisor {
     /// Path is the host path to the hypervisor used to manage the virtual
     /// machine.
@@ -65,6 +67,7 @@ pub struct VMHypervisor {
 #[getset(get = "pub", set = "pub")]
 /// VMKernel contains information about the kernel to use for a virtual
 /// machine.
+#[non_exhaustive]
 pub struct VMKernel {
     /// Path is the host path to the kernel used to boot the virtual
     /// machine.
@@ -91,6 +94,7 @@ pub struct VMKernel {
 )]
 #[getset(get = "pub", set = "pub")]
 /// VMImage contains information about the virtual machine root image.
+#[non_exhaustive]
 pub struct VMImage {
     /// Path is the host path to the root image that the VM kernel would
     /// boot into.
@@ -26,6 +26,7 @@ use std::collections::HashMap;
 )]
 /// Windows defines the runtime configuration for Windows based containers,
 /// including Hyper-V containers.
+#[non_exhaustive]
 pub struct Windows {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[getset(get = "pub", set = "pub")]
@@ -44,7 +45,11 @@ pub struct Windows {
     /// the container.
     resources: Option<WindowsResources>,
 
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_sorted"
+    )]
     #[getset(get = "pub", set = "pub")]
     /// CredentialSpec contains a JSON object describing a group Managed
     /// Service Account (gMSA) specification.
@@ -88,6 +93,7 @@ pub struct Windows {
 #[getset(get = "pub", set = "pub")]
 /// WindowsDevice represents information about a host device to be mapped
 /// into the container.
+#[non_exhaustive]
 pub struct WindowsDevice {
     /// Device identifier: interface class GUID, etc..
     id: String,
@@ -107,6 +113,7 @@ pub struct WindowsDevice {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// Available windows resources.
+#[non_exhaustive]
 pub struct WindowsResources {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Memory restriction configuration.
@@ -132,6 +139,7 @@ pub struct WindowsResources {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// WindowsMemoryResources contains memory resource management settings.
+#[non_exhaustive]
 pub struct WindowsMemoryResources {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Memory limit in bytes.
@@ -149,6 +157,7 @@ pub struct WindowsMemoryResources {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// WindowsCPUResources contains CPU resource management settings.
+#[non_exhaustive]
 pub struct WindowsCPUResources {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Number of CPUs available to the container.
@@ -176,6 +185,7 @@ pub struct WindowsCPUResources {
 )]
 #[getset(get_copy = "pub", set = "pub")]
 /// WindowsStorageResources contains storage resource management settings.
+#[non_exhaustive]
 pub struct WindowsStorageResources {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Specifies maximum Iops for the system drive.
@@ -204,6 +214,7 @@ pub struct WindowsStorageResources {
 #[getset(get = "pub", set = "pub")]
 /// WindowsHyperV contains information for configuring a container to run
 /// with Hyper-V isolation.
+#[non_exhaustive]
 pub struct WindowsHyperV {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// UtilityVMPath is an optional path to the image used for the Utility
@@ -222,6 +233,7 @@ pub struct WindowsHyperV {
     build_fn(error = "OciSpecError")
 )]
 /// WindowsNetwork contains network settings for Windows containers.
+#[non_exhaustive]
 pub struct WindowsNetwork {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[getset(get = "pub", set = "pub")]
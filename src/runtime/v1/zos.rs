@@ -2,7 +2,7 @@ use crate::error::OciSpecError;
 use derive_builder::Builder;
 use getset::{CopyGetters, Getters, Setters};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 use strum_macros::Display as StrumDisplay;
 
 #[derive(
@@ -26,6 +26,7 @@ use strum_macros::Display as StrumDisplay;
 )]
 #[getset(get = "pub", set = "pub")]
 /// ZOS contains information for z/OS based containers.
+#[non_exhaustive]
 pub struct ZOS {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Namespaces contains the namespaces that are created and/or joined by the container
@@ -38,6 +39,7 @@ pub struct ZOS {
 #[strum(serialize_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 /// Available z/OS namespace types.
+#[non_exhaustive]
 pub enum ZOSNamespaceType {
     #[default]
     /// PID Namespace for isolating process IDs
@@ -66,6 +68,14 @@ impl TryFrom<&str> for ZOSNamespaceType {
     }
 }
 
+impl FromStr for ZOSNamespaceType {
+    type Err = OciSpecError;
+
+    fn from_str(namespace: &str) -> Result<Self, Self::Err> {
+        Self::try_from(namespace)
+    }
+}
+
 #[derive(
     Builder,
     Clone,
@@ -86,6 +96,7 @@ impl TryFrom<&str> for ZOSNamespaceType {
     build_fn(error = "OciSpecError")
 )]
 /// ZOSNamespace is the configuration for a z/OS namespace.
+#[non_exhaustive]
 pub struct ZOSNamespace {
     #[serde(rename = "type")]
     #[getset(get_copy = "pub", set = "pub")]